@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS recordings_schedule
+(
+	id          INTEGER   NOT NULL PRIMARY KEY AUTOINCREMENT,
+	created_at  TIMESTAMP NOT NULL,
+	station_id  INTEGER   NOT NULL REFERENCES radio_stations (id) ON DELETE CASCADE,
+	weekdays    INTEGER   NOT NULL, -- bitmask, Monday is the lowest bit.
+	start_time  TEXT      NOT NULL, -- local time.
+	end_time    TEXT      NOT NULL  -- local time, the next day if not after start.
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS alarms
+(
+	id                  INTEGER   NOT NULL PRIMARY KEY AUTOINCREMENT,
+	created_at          TIMESTAMP NOT NULL,
+	station_id          INTEGER   NOT NULL REFERENCES radio_stations (id) ON DELETE CASCADE,
+	fallback_station_id INTEGER   REFERENCES radio_stations (id) ON DELETE SET NULL,
+	weekdays            INTEGER   NOT NULL, -- bitmask, Monday is the lowest bit.
+	time                TEXT      NOT NULL, -- local time.
+	volume              INTEGER   NOT NULL,
+	ramp_secs           INTEGER   NOT NULL
+);
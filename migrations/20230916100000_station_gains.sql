@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS station_gains
+(
+	station_id INTEGER   NOT NULL PRIMARY KEY REFERENCES radio_stations (id) ON DELETE CASCADE,
+	updated_at TIMESTAMP NOT NULL,
+	gain       REAL      NOT NULL -- dB, adapted by loudness normalization.
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS eq_presets
+(
+	id         INTEGER   NOT NULL PRIMARY KEY AUTOINCREMENT,
+	created_at TIMESTAMP NOT NULL,
+	name       TEXT      NOT NULL UNIQUE,
+	gains      TEXT      NOT NULL -- comma separated dB of each band, from the lowest.
+);
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS settings
+(
+	key        TEXT      NOT NULL PRIMARY KEY,
+	updated_at TIMESTAMP NOT NULL,
+	value      TEXT      NOT NULL
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS station_headers
+(
+	station_id INTEGER NOT NULL REFERENCES radio_stations (id) ON DELETE CASCADE,
+	name       TEXT    NOT NULL,
+	value      TEXT    NOT NULL, -- sent with the stream requests, overrides the configured header.
+	PRIMARY KEY (station_id, name)
+);
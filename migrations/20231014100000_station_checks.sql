@@ -0,0 +1,7 @@
+-- result of the last stream check, all NULL if never checked.
+ALTER TABLE radio_stations ADD COLUMN checked_at TIMESTAMP NULL;
+ALTER TABLE radio_stations ADD COLUMN check_latency_ms INTEGER NULL;
+ALTER TABLE radio_stations ADD COLUMN check_error TEXT NULL; -- NULL if the stream was decoded.
+ALTER TABLE radio_stations ADD COLUMN check_codec TEXT NULL;
+ALTER TABLE radio_stations ADD COLUMN check_bitrate INTEGER NULL;
+ALTER TABLE radio_stations ADD COLUMN check_sample_rate INTEGER NULL;
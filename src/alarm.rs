@@ -0,0 +1,229 @@
+use std::time::{Duration, Instant};
+
+use chrono::{Local, NaiveDateTime};
+
+use crate::models::{Alarm, Station};
+use crate::player::{Player, State};
+use crate::storage::Storage;
+
+/// Starts playing saved stations by their [Alarm]s with volume ramp-up.
+/// Driven by [`AlarmClock::tick`], from the TUI loop or in headless mode.
+pub struct AlarmClock<S: Storage> {
+    storage: S,
+    alarms: Vec<Alarm>,
+    loaded_at: Option<Instant>,
+    /// Alarms went off, so they aren't started again within their grace period.
+    fired: Vec<(i64, NaiveDateTime)>,
+    ringing: Option<Ringing>,
+    restore: Option<Restore>,
+}
+
+/// Alarm which is connecting or raising volume.
+struct Ringing {
+    alarm: Alarm,
+    is_fallback: bool,
+    /// Time the stream started playing, volume is raised since then.
+    playing_since: Option<Instant>,
+}
+
+/// User's volume from before the alarm, restored once the alarm is stopped.
+struct Restore {
+    volume: i8,
+    is_muted: bool,
+    /// Last volume set by the alarm, any other means the user has changed it.
+    alarm_volume: i8,
+}
+
+impl<S: Storage> AlarmClock<S> {
+    /// Alarms are re-read periodically to catch changes made elsewhere.
+    const RELOAD_PERIOD: Duration = Duration::from_secs(30);
+
+    pub fn new(storage: S) -> Self {
+        Self {
+            storage,
+            alarms: vec![],
+            loaded_at: None,
+            fired: vec![],
+            ringing: None,
+            restore: None,
+        }
+    }
+
+    /// Forces alarms to be re-read on the next tick.
+    pub fn reload(&mut self) {
+        self.loaded_at = None;
+    }
+
+    /// Starts due alarm or raises volume of the ringing one, should be called every second.
+    /// Returns station which started playing.
+    pub async fn tick<P: Player>(&mut self, player: &P) -> anyhow::Result<Option<Station>> {
+        if self
+            .loaded_at
+            .is_none_or(|t| t.elapsed() >= Self::RELOAD_PERIOD)
+        {
+            self.alarms = self.storage.alarms().await?;
+            self.loaded_at = Some(Instant::now());
+        }
+
+        let now = Local::now().naive_local();
+
+        self.fired.retain(|&(id, at)| {
+            self.alarms
+                .iter()
+                .any(|a| a.id == id && a.due(now) == Some(at))
+        });
+
+        if self.ringing.is_some() {
+            return self.ring(player).await;
+        }
+
+        self.restore_volume(player);
+
+        // other alarms due at the same time ring once the first one is stopped.
+        let Some((alarm, at)) = self
+            .alarms
+            .iter()
+            .filter_map(|a| a.due(now).map(|at| (a, at)))
+            .find(|&(a, at)| !self.fired.contains(&(a.id, at)))
+        else {
+            return Ok(None);
+        };
+
+        log::info!(
+            "alarm {} went off, playing {}",
+            alarm.id,
+            alarm.station.name
+        );
+
+        self.fired.push((alarm.id, at));
+        self.ringing = Some(Ringing {
+            alarm: alarm.clone(),
+            is_fallback: false,
+            playing_since: None,
+        });
+
+        // kept from the first alarm if they ring in a row.
+        let restore = self.restore.get_or_insert_with(|| Restore {
+            volume: player.volume(),
+            is_muted: player.is_muted(),
+            alarm_volume: 0,
+        });
+        restore.alarm_volume = 0;
+
+        player.set_volume(0);
+        player.set_muted(false);
+        let headers = self.storage.station_headers(alarm.station.id).await?;
+        let gain = self.storage.station_gain(alarm.station.id).await?;
+        player.play(&alarm.station.url, &headers, gain.unwrap_or_default())?;
+
+        Ok(Some(alarm.station.clone()))
+    }
+
+    async fn ring<P: Player>(&mut self, player: &P) -> anyhow::Result<Option<Station>> {
+        let Some(ref mut ringing) = self.ringing else {
+            return Ok(None);
+        };
+
+        let alarm = &ringing.alarm;
+
+        match player.state() {
+            State::Connecting | State::Reconnecting { .. } => Ok(None),
+            State::Playing => {
+                if self
+                    .restore
+                    .as_ref()
+                    .is_some_and(|r| r.alarm_volume != player.volume())
+                {
+                    log::info!("alarm {} volume changed by the user", alarm.id);
+
+                    self.ringing = None;
+                    self.restore = None;
+
+                    return Ok(None);
+                }
+
+                let elapsed = ringing
+                    .playing_since
+                    .get_or_insert_with(Instant::now)
+                    .elapsed();
+
+                player.set_volume(ramp_volume(alarm.volume, elapsed, alarm.ramp));
+
+                if elapsed >= alarm.ramp {
+                    self.ringing = None;
+                }
+
+                if let Some(ref mut restore) = self.restore {
+                    restore.alarm_volume = player.volume();
+                }
+
+                Ok(None)
+            }
+            State::Failed(_) if !ringing.is_fallback && alarm.fallback.is_some() => {
+                let fallback = alarm.fallback.clone().expect("checked above");
+
+                log::warn!("alarm {} falls back to {}", alarm.id, fallback.name);
+
+                ringing.is_fallback = true;
+                let headers = self.storage.station_headers(fallback.id).await?;
+                let gain = self.storage.station_gain(fallback.id).await?;
+                player.play(&fallback.url, &headers, gain.unwrap_or_default())?;
+
+                Ok(Some(fallback))
+            }
+            // stopped by the user or can't be played at all.
+            State::Failed(_) | State::Stopped => {
+                self.ringing = None;
+                self.restore_volume(player);
+
+                Ok(None)
+            }
+        }
+    }
+
+    /// Restores the user's volume once the alarm's station is stopped,
+    /// unless the user has changed volume since.
+    fn restore_volume<P: Player>(&mut self, player: &P) {
+        let Some(ref restore) = self.restore else {
+            return;
+        };
+
+        if restore.alarm_volume != player.volume() {
+            self.restore = None;
+        } else if matches!(player.state(), State::Failed(_) | State::Stopped) {
+            player.set_volume(restore.volume);
+            player.set_muted(restore.is_muted);
+            self.restore = None;
+        }
+    }
+}
+
+/// Returns volume raised proportionally to the time passed since the ramp-up start.
+fn ramp_volume(volume: i8, elapsed: Duration, ramp: Duration) -> i8 {
+    let ratio = elapsed.as_secs_f32() / ramp.as_secs_f32().max(f32::EPSILON);
+
+    #[allow(clippy::cast_possible_truncation)]
+    let raised = (f32::from(volume) * ratio.min(1.0)).round() as i8;
+
+    raised
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::ramp_volume;
+
+    #[test]
+    fn test_ramp_volume() {
+        let ramp = Duration::from_mins(1);
+        let test_data = [(80, 0, 0), (80, 30, 40), (80, 60, 80), (80, 90, 80)];
+
+        for (volume, elapsed, want) in test_data {
+            assert_eq!(
+                ramp_volume(volume, Duration::from_secs(elapsed), ramp),
+                want
+            );
+        }
+    }
+}
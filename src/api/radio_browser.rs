@@ -1,32 +1,42 @@
-use reqwest::{redirect::Policy, ClientBuilder, Url};
+use reqwest::Url;
 use serde::Deserialize;
 
+use crate::http::HttpConfig;
 use crate::models::{OrderBy, Station, StationsFilter};
 
 use super::Client;
 
-const APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 const PROVIDER_NAME: &str = "radio-browser";
 
 #[derive(Debug, Clone)]
 pub struct RadioBrowser {
     addr: Url,
     client: reqwest::Client,
+    codecs: Vec<String>,
 }
 
 impl RadioBrowser {
-    pub fn new() -> Self {
+    pub fn new(http: &HttpConfig) -> anyhow::Result<Self> {
         let addr = "https://de1.api.radio-browser.info"
             .parse()
             .expect("invalid address");
 
-        let client = ClientBuilder::new()
-            .user_agent(APP_USER_AGENT)
-            .redirect(Policy::default())
-            .build()
-            .expect("can't build client");
+        Ok(Self {
+            addr,
+            client: http.client()?,
+            codecs: vec![],
+        })
+    }
+
+    /// Keeps only stations with given codecs in search results, all stations are kept if empty.
+    pub fn with_codecs(mut self, codecs: Vec<String>) -> Self {
+        self.codecs = codecs;
+
+        self
+    }
 
-        Self { addr, client }
+    fn is_supported(&self, codec: &str) -> bool {
+        self.codecs.is_empty() || self.codecs.iter().any(|c| c.eq_ignore_ascii_case(codec))
     }
 
     fn search_url(&self, filter: &StationsFilter) -> Url {
@@ -46,7 +56,7 @@ impl RadioBrowser {
 
         if let Some(order_by) = filter.order_by.as_ref() {
             url.query_pairs_mut().append_pair("order", order_by.into());
-        };
+        }
 
         url
     }
@@ -62,11 +72,9 @@ impl Client for RadioBrowser {
         let resp = self.client.get(url).send().await?;
         let data = resp.json::<Vec<RadioStation>>().await?;
 
-        let codecs = ["MP3", "FLAC"];
-
         Ok(data
             .into_iter()
-            .filter(|s| codecs.contains(&s.codec.as_str()))
+            .filter(|s| self.is_supported(s.codec.trim()))
             .map(Station::from)
             .collect())
     }
@@ -96,6 +104,7 @@ impl From<RadioStation> for Station {
             bitrate: value.bitrate,
             tags: value.tags.into(),
             country: value.country,
+            check: None,
         }
     }
 }
@@ -110,11 +119,13 @@ impl From<&OrderBy> for &str {
 
 #[cfg(test)]
 mod tests {
+    use crate::http::HttpConfig;
+
     use super::{OrderBy, RadioBrowser, StationsFilter};
 
     #[test]
     fn test_search_url() {
-        let rb = RadioBrowser::new();
+        let rb = RadioBrowser::new(&HttpConfig::default()).unwrap();
         let test_data = [
             (
                 StationsFilter {
@@ -154,4 +165,23 @@ mod tests {
             assert_eq!(rb.search_url(&filter).query(), Some(want));
         }
     }
+
+    #[test]
+    fn test_is_supported() {
+        let rb = RadioBrowser::new(&HttpConfig::default()).unwrap();
+        assert!(rb.is_supported("UNKNOWN"));
+
+        let rb = rb.with_codecs(vec!["MP3".to_string(), "AAC+".to_string()]);
+        let test_data = [
+            ("MP3", true),
+            ("mp3", true),
+            ("AAC+", true),
+            ("AAC", false),
+            ("UNKNOWN", false),
+        ];
+
+        for (codec, want) in test_data {
+            assert_eq!(rb.is_supported(codec), want, "{codec}");
+        }
+    }
 }
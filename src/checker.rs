@@ -0,0 +1,268 @@
+use std::time::Instant;
+
+use chrono::Utc;
+use futures::StreamExt;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+use crate::http::HttpConfig;
+use crate::models::{Station, StationCheck, StationsFilter};
+use crate::player;
+use crate::storage::Storage;
+
+/// Stations checked at once by default.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Checks streams of the saved stations in background, results are stored with the stations.
+/// Started by [`StationChecker::start`], results are taken by [`StationChecker::next`].
+pub struct StationChecker<S: Storage> {
+    storage: S,
+    http: HttpConfig,
+    concurrency: usize,
+    results: Option<UnboundedReceiver<Station>>,
+    progress: Progress,
+}
+
+/// Number of the stations checked by the running or the last check.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Progress {
+    pub total: usize,
+    pub checked: usize,
+    pub broken: usize,
+}
+
+impl<S: Storage> StationChecker<S> {
+    pub fn new(storage: S, http: HttpConfig) -> Self {
+        Self {
+            storage,
+            http,
+            concurrency: DEFAULT_CONCURRENCY,
+            results: None,
+            progress: Progress::default(),
+        }
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+
+        self
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.results.is_some()
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// Starts checking all saved stations, returns their number.
+    pub async fn start(&mut self) -> anyhow::Result<usize> {
+        anyhow::ensure!(!self.is_running(), "stations are being checked already");
+
+        let mut targets = vec![];
+
+        for station in self.storage.search(&StationsFilter::default()).await? {
+            let headers = self.storage.station_headers(station.id).await?;
+            targets.push((station, self.http.with_headers(&headers)));
+        }
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let concurrency = self.concurrency;
+
+        self.progress = Progress {
+            total: targets.len(),
+            ..Progress::default()
+        };
+        self.results = Some(rx);
+
+        tokio::spawn(async move {
+            let mut checks = futures::stream::iter(targets)
+                .map(|(station, http)| check(station, http))
+                .buffer_unordered(concurrency);
+
+            while let Some(station) = checks.next().await {
+                // checker is dropped, nobody waits for the rest.
+                if tx.send(station).is_err() {
+                    return;
+                }
+            }
+        });
+
+        Ok(self.progress.total)
+    }
+
+    /// Waits for the next checked station, `None` once all are checked.
+    /// Never completes if no check is running, so it can wait along with other events.
+    /// Cancel safe, its result must be stored by [`StationChecker::save`].
+    pub async fn next(&mut self) -> Option<Station> {
+        let Some(ref mut results) = self.results else {
+            return std::future::pending().await;
+        };
+
+        let Some(station) = results.recv().await else {
+            self.results = None;
+            return None;
+        };
+
+        self.progress.checked += 1;
+
+        if station.is_broken() {
+            self.progress.broken += 1;
+        }
+
+        Some(station)
+    }
+
+    /// Stores result of the station check.
+    pub async fn save(&self, station: &Station) -> anyhow::Result<()> {
+        if let Some(ref check) = station.check {
+            self.storage.set_station_check(station.id, check).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the station with the result of its check.
+async fn check(mut station: Station, http: HttpConfig) -> Station {
+    let started = Instant::now();
+    let url = station.url.clone();
+
+    // stream requests are blocking.
+    let result = tokio::task::spawn_blocking(move || player::probe(&url, &http))
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|r| r);
+
+    let latency = started.elapsed();
+
+    if let Err(ref e) = result {
+        log::warn!("check {}: {e:?}", station.url);
+    }
+
+    station.check = Some(StationCheck {
+        checked_at: Utc::now(),
+        latency,
+        error: result.as_ref().err().map(|e| format!("{e:#}")),
+        codec: result
+            .as_ref()
+            .ok()
+            .and_then(|i| i.codec().map(ToString::to_string)),
+        bitrate: result.as_ref().ok().and_then(player::StreamInfo::bitrate),
+        sample_rate: result.as_ref().ok().map(player::StreamInfo::sample_rate),
+    });
+
+    station
+}
+
+/// Checks all saved stations and prints the results, fails if some are broken.
+pub async fn run<S: Storage>(
+    storage: S,
+    http: HttpConfig,
+    concurrency: usize,
+) -> anyhow::Result<()> {
+    let mut checker = StationChecker::new(storage, http).with_concurrency(concurrency);
+    checker.start().await?;
+
+    while let Some(station) = checker.next().await {
+        checker.save(&station).await?;
+        println!("{}", summary(&station));
+    }
+
+    let progress = checker.progress();
+    println!(
+        "{} of {} stations are broken",
+        progress.broken, progress.total
+    );
+
+    anyhow::ensure!(progress.broken == 0, "some stations are broken");
+
+    Ok(())
+}
+
+/// Formats result of the station check, e.g. `OK   350 ms  Radio (MP3 128 kbps 44100 Hz)`.
+fn summary(station: &Station) -> String {
+    let name = station.name.trim();
+
+    let Some(ref check) = station.check else {
+        return format!("-     {name}");
+    };
+
+    let latency = format!("{:>5} ms", check.latency.as_millis());
+
+    if let Some(ref error) = check.error {
+        return format!("FAIL {latency}  {name}: {error}");
+    }
+
+    let mut format = check.codec.clone().unwrap_or_else(|| "?".to_string());
+
+    if let Some(bitrate) = check.bitrate {
+        format = format!("{format} {bitrate} kbps");
+    }
+
+    if let Some(sample_rate) = check.sample_rate {
+        format = format!("{format} {sample_rate} Hz");
+    }
+
+    format!("OK   {latency}  {name} ({format})")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::Utc;
+
+    use crate::models::{Station, StationCheck};
+
+    use super::summary;
+
+    fn station(check: Option<StationCheck>) -> Station {
+        Station {
+            id: 1,
+            provider: String::new(),
+            provider_id: String::new(),
+            name: " Radio ".to_string(),
+            url: String::new(),
+            codec: String::new(),
+            bitrate: 0,
+            tags: "".into(),
+            country: String::new(),
+            check,
+        }
+    }
+
+    fn check(error: Option<&str>, codec: Option<&str>, bitrate: Option<u32>) -> StationCheck {
+        StationCheck {
+            checked_at: Utc::now(),
+            latency: Duration::from_millis(350),
+            error: error.map(ToString::to_string),
+            codec: codec.map(ToString::to_string),
+            bitrate,
+            sample_rate: codec.map(|_| 44_100),
+        }
+    }
+
+    #[test]
+    fn test_summary() {
+        let test_data = [
+            (None, "-     Radio"),
+            (
+                Some(check(None, Some("MP3"), Some(128))),
+                "OK     350 ms  Radio (MP3 128 kbps 44100 Hz)",
+            ),
+            (
+                Some(check(None, Some("AAC"), None)),
+                "OK     350 ms  Radio (AAC 44100 Hz)",
+            ),
+            (
+                Some(check(Some("get http response"), None, None)),
+                "FAIL   350 ms  Radio: get http response",
+            ),
+        ];
+
+        for (check, want) in test_data {
+            assert_eq!(summary(&station(check)), want);
+        }
+    }
+}
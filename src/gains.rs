@@ -0,0 +1,68 @@
+use std::time::{Duration, Instant};
+
+use crate::models::Station;
+use crate::player::{Player, State};
+use crate::storage::Storage;
+
+/// Remembers loudness normalization gain per saved station, so the next playback
+/// is started by [`Player::play`] with the adjustment made last time.
+/// Driven by [`StationGains::tick`].
+pub struct StationGains<S: Storage> {
+    storage: S,
+    /// Saved station being played and [`Player::plays`] it was started at,
+    /// each playback is saved apart from the previous one.
+    playing: Option<(i64, u64)>,
+    saved_at: Option<Instant>,
+}
+
+impl<S: Storage> StationGains<S> {
+    /// Adapted gain is stored periodically, so it survives crashes and station switches.
+    const SAVE_PERIOD: Duration = Duration::from_secs(10);
+
+    pub fn new(storage: S) -> Self {
+        Self {
+            storage,
+            playing: None,
+            saved_at: None,
+        }
+    }
+
+    /// Stores the adapted gain of the played station, should be called every second.
+    pub async fn tick<P: Player>(
+        &mut self,
+        player: &P,
+        station: Option<&Station>,
+    ) -> anyhow::Result<()> {
+        // stations found in the directory aren't stored, so have no id.
+        let playing = station
+            .map(|s| s.id)
+            .filter(|&id| id > 0)
+            .map(|id| (id, player.plays()));
+
+        // gain isn't adapted yet right after the start.
+        if playing != self.playing {
+            self.playing = playing;
+            self.saved_at = None;
+
+            return Ok(());
+        }
+
+        let Some((id, _)) = playing else {
+            return Ok(());
+        };
+
+        if !player.is_normalizing() || player.state() != State::Playing {
+            return Ok(());
+        }
+
+        if self
+            .saved_at
+            .is_none_or(|t| t.elapsed() >= Self::SAVE_PERIOD)
+        {
+            self.storage.set_station_gain(id, player.gain()).await?;
+            self.saved_at = Some(Instant::now());
+        }
+
+        Ok(())
+    }
+}
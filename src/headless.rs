@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use crate::alarm::AlarmClock;
+use crate::player::Player;
+use crate::scheduler::Scheduler;
+use crate::storage::Storage;
+
+const TICK_PERIOD: Duration = Duration::from_secs(1);
+
+/// Serves scheduled recordings and alarms without the TUI until interrupted by Ctrl-C.
+pub async fn run<P, S>(player: P, storage: S, mut scheduler: Scheduler<S>) -> anyhow::Result<()>
+where
+    P: Player,
+    S: Storage + Clone,
+{
+    let mut alarms = AlarmClock::new(storage);
+    let mut ticker = tokio::time::interval(TICK_PERIOD);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if let Err(e) = scheduler.tick().await {
+                    log::error!("scheduler: {e:?}");
+                }
+
+                if let Err(e) = alarms.tick(&player).await {
+                    log::error!("alarm clock: {e:?}");
+                }
+            },
+            result = tokio::signal::ctrl_c() => break result?,
+        }
+    }
+
+    player.stop();
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use chrono::{Datelike, NaiveDateTime, NaiveTime};
+
+use super::{Station, Weekdays};
+
+/// Starts playing a saved [Station] at the given time, e.g. "Weekdays 07:30".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Alarm {
+    pub id: i64,
+    pub station: Station,
+    /// Played if the main station can't be connected.
+    pub fallback: Option<Station>,
+    pub weekdays: Weekdays,
+    /// Local time alarm goes off at.
+    pub time: NaiveTime,
+    /// Volume reached at the end of the ramp-up, in percentage [0 - 100].
+    pub volume: i8,
+    /// Time volume is raised from zero to `volume`.
+    pub ramp: Duration,
+}
+
+impl Alarm {
+    /// Alarm missed by more than this, e.g. while tradio wasn't running, is skipped.
+    const GRACE: Duration = Duration::from_mins(1);
+
+    /// Returns time the alarm went off at if it should be ringing at `now`.
+    pub fn due(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let at = now.date().and_time(self.time);
+        let until = at + chrono::Duration::from_std(Self::GRACE).ok()?;
+
+        (self.weekdays.contains(now.weekday()) && (at..until).contains(&now)).then_some(at)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+    use super::{Alarm, Station};
+
+    #[test]
+    fn test_due() {
+        // 2023-09-04 is Monday.
+        let at = |day: u32, time: &str| -> NaiveDateTime {
+            NaiveDate::from_ymd_opt(2023, 9, day)
+                .unwrap()
+                .and_time(NaiveTime::parse_from_str(time, "%H:%M:%S").unwrap())
+        };
+
+        let alarm = Alarm {
+            id: 1,
+            station: new_station(),
+            fallback: None,
+            weekdays: "weekdays".parse().unwrap(),
+            time: NaiveTime::from_hms_opt(7, 30, 0).unwrap(),
+            volume: 50,
+            ramp: Duration::from_mins(1),
+        };
+
+        let test_data = [
+            (at(4, "07:30:00"), Some(at(4, "07:30:00"))),
+            (at(4, "07:30:59"), Some(at(4, "07:30:00"))),
+            (at(4, "07:31:00"), None),
+            (at(4, "07:29:59"), None),
+            (at(3, "07:30:10"), None),
+        ];
+
+        for (now, want) in test_data {
+            assert_eq!(alarm.due(now), want, "{now}");
+        }
+    }
+
+    fn new_station() -> Station {
+        Station {
+            id: 1,
+            provider: String::new(),
+            provider_id: String::new(),
+            name: "Radio".to_string(),
+            url: String::new(),
+            codec: String::new(),
+            bitrate: 0,
+            tags: "".into(),
+            country: String::new(),
+            check: None,
+        }
+    }
+}
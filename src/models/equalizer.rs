@@ -0,0 +1,122 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Context;
+
+/// Center frequencies of the graphic equalizer bands in Hz, an octave apart.
+pub const EQ_FREQUENCIES: [u32; 10] = [31, 62, 125, 250, 500, 1000, 2000, 4000, 8000, 16000];
+
+/// Named [`EqGains`], either built in or stored by the user.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EqPreset {
+    /// Zero for built-in presets.
+    pub id: i64,
+    pub name: String,
+    pub gains: EqGains,
+}
+
+impl EqPreset {
+    /// Presets available without the storage.
+    pub fn builtin() -> Vec<Self> {
+        let preset = |name: &str, gains| Self {
+            id: 0,
+            name: name.to_string(),
+            gains: EqGains(gains),
+        };
+
+        vec![
+            preset("Flat", [0; 10]),
+            preset("Bass boost", [6, 5, 4, 2, 0, 0, 0, 0, 0, 0]),
+            preset("Speech", [-6, -4, -2, 0, 2, 4, 4, 2, 0, -2]),
+        ]
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        self.id == 0
+    }
+}
+
+/// Gains of the equalizer bands in dB, ordered as [`EQ_FREQUENCIES`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct EqGains([i8; 10]);
+
+impl EqGains {
+    /// Limit of the band gain in dB.
+    pub const MAX: i8 = 12;
+
+    pub fn get(&self, band: usize) -> i8 {
+        self.0.get(band).copied().unwrap_or_default()
+    }
+
+    /// Sets band gain clamped to [`Self::MAX`], no effect if band doesn't exist.
+    pub fn set(&mut self, band: usize, gain: i8) {
+        if let Some(value) = self.0.get_mut(band) {
+            *value = gain.clamp(-Self::MAX, Self::MAX);
+        }
+    }
+
+    pub fn is_flat(&self) -> bool {
+        self.0.iter().all(|&g| g == 0)
+    }
+
+    /// Returns bands with their frequencies and gains.
+    pub fn bands(&self) -> impl Iterator<Item = (u32, i8)> + '_ {
+        EQ_FREQUENCIES.into_iter().zip(self.0.iter().copied())
+    }
+}
+
+impl fmt::Display for EqGains {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gains: Vec<String> = self.0.iter().map(ToString::to_string).collect();
+
+        f.write_str(&gains.join(","))
+    }
+}
+
+impl FromStr for EqGains {
+    type Err = anyhow::Error;
+
+    /// Parses comma separated gains of all bands like `6,5,4,2,0,0,0,0,0,0`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut gains = Self::default();
+        let parts: Vec<&str> = value.split(',').map(str::trim).collect();
+
+        anyhow::ensure!(
+            parts.len() == EQ_FREQUENCIES.len(),
+            "expected {} gains, got {}",
+            EQ_FREQUENCIES.len(),
+            parts.len()
+        );
+
+        for (band, part) in parts.into_iter().enumerate() {
+            let gain = part
+                .parse()
+                .with_context(|| format!("invalid gain {part}"))?;
+
+            gains.set(band, gain);
+        }
+
+        Ok(gains)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EqGains;
+
+    #[test]
+    fn test_gains_from_str() {
+        let test_data = [
+            ("0,0,0,0,0,0,0,0,0,0", Some("0,0,0,0,0,0,0,0,0,0")),
+            ("6, 5,4,2,0,0,0,0,0,-3", Some("6,5,4,2,0,0,0,0,0,-3")),
+            ("20,0,0,0,0,0,0,0,0,-20", Some("12,0,0,0,0,0,0,0,0,-12")),
+            ("1,2,3", None),
+            ("a,0,0,0,0,0,0,0,0,0", None),
+        ];
+
+        for (value, want) in test_data {
+            let got = value.parse::<EqGains>().ok().map(|g| g.to_string());
+            assert_eq!(got.as_deref(), want, "{value}");
+        }
+    }
+}
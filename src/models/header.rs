@@ -0,0 +1,116 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Context;
+use reqwest::header::{HeaderName, HeaderValue};
+
+/// HTTP header sent with requests, e.g. `User-Agent: tradio`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Header {
+    pub name: String,
+    pub value: String,
+}
+
+impl Header {
+    /// Separates headers typed on a single line, unlike `;` it's rare in values.
+    pub const SEPARATOR: char = '|';
+
+    /// Parses headers separated by [`Self::SEPARATOR`], empty parts are skipped.
+    pub fn parse_list(value: &str) -> anyhow::Result<Vec<Self>> {
+        value
+            .split(Self::SEPARATOR)
+            .filter(|p| !p.trim().is_empty())
+            .map(str::parse)
+            .collect()
+    }
+
+    /// Joins headers to be parsed by [`Self::parse_list`].
+    pub fn join_list(headers: &[Self]) -> String {
+        headers
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(&format!(" {} ", Self::SEPARATOR))
+    }
+}
+
+/// Formatted as `<name>: <value>`.
+impl fmt::Display for Header {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.value)
+    }
+}
+
+impl FromStr for Header {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (name, header_value) = value
+            .split_once(':')
+            .with_context(|| format!("header {value} has no value"))?;
+
+        let name = name.trim();
+        let header_value = header_value.trim();
+
+        // rejected once sent otherwise, far from where the header was typed.
+        HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("invalid header name {name}"))?;
+        HeaderValue::from_str(header_value)
+            .with_context(|| format!("invalid value of header {name}"))?;
+
+        Ok(Self {
+            name: name.to_string(),
+            value: header_value.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Header;
+
+    fn header(name: &str, value: &str) -> Header {
+        Header {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_list() {
+        let test_data = [
+            ("", Some(vec![])),
+            (
+                "User-Agent: Mozilla/5.0 (X11; Linux x86_64)",
+                Some(vec![header(
+                    "User-Agent",
+                    "Mozilla/5.0 (X11; Linux x86_64)",
+                )]),
+            ),
+            (
+                "Referer: https://example.com/ | X-Token:abc|",
+                Some(vec![
+                    header("Referer", "https://example.com/"),
+                    header("X-Token", "abc"),
+                ]),
+            ),
+            ("Referer", None),
+            ("Bad Name: value", None),
+            (": value", None),
+        ];
+
+        for (value, want) in test_data {
+            let got = Header::parse_list(value).ok();
+            assert_eq!(got, want, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_join_list() {
+        let headers = vec![header("Referer", "https://example.com/"), header("X", "1")];
+        let joined = Header::join_list(&headers);
+
+        assert_eq!(joined, "Referer: https://example.com/ | X: 1");
+        assert_eq!(Header::parse_list(&joined).unwrap(), headers);
+    }
+}
@@ -1,3 +1,11 @@
-pub use station::{OrderBy, Station, StationsFilter};
+pub use alarm::Alarm;
+pub use equalizer::{EqGains, EqPreset};
+pub use header::Header;
+pub use schedule::{Schedule, Weekdays};
+pub use station::{OrderBy, Station, StationCheck, StationsFilter};
 
+mod alarm;
+mod equalizer;
+mod header;
+mod schedule;
 mod station;
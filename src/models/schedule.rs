@@ -0,0 +1,229 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
+
+use super::Station;
+
+/// Recurring recording of a saved [Station], e.g. "Sat 20:00–22:00".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Schedule {
+    pub id: i64,
+    pub station: Station,
+    pub weekdays: Weekdays,
+    /// Local time recording starts at.
+    pub start: NaiveTime,
+    /// Local time recording ends at, the next day if not after `start`.
+    pub end: NaiveTime,
+}
+
+impl Schedule {
+    /// Returns start and end of the recording window containing `now`.
+    pub fn window(&self, now: NaiveDateTime) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let mut length = self.end - self.start;
+
+        if length <= Duration::zero() {
+            length = length + Duration::days(1);
+        }
+
+        // window started yesterday may be still open after midnight.
+        [now.date().pred_opt(), Some(now.date())]
+            .into_iter()
+            .flatten()
+            .filter(|day| self.weekdays.contains(day.weekday()))
+            .map(|day| {
+                let start = day.and_time(self.start);
+                (start, start + length)
+            })
+            .find(|(start, end)| (*start..*end).contains(&now))
+    }
+
+    /// Parses window like `20:00-22:00` into start and end times.
+    pub fn parse_window(value: &str) -> anyhow::Result<(NaiveTime, NaiveTime)> {
+        let (start, end) = value
+            .split_once(['-', '–'])
+            .ok_or_else(|| anyhow::anyhow!("window must be formatted as HH:MM-HH:MM"))?;
+
+        let start = NaiveTime::parse_from_str(start.trim(), "%H:%M")?;
+        let end = NaiveTime::parse_from_str(end.trim(), "%H:%M")?;
+
+        anyhow::ensure!(start != end, "window can't be empty");
+
+        Ok((start, end))
+    }
+}
+
+/// Set of days of the week, stored as a bitmask with Monday as the lowest bit.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Weekdays(u8);
+
+impl Weekdays {
+    const ALL: [Weekday; 7] = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ];
+
+    pub fn from_bits(bits: u8) -> Self {
+        Self(bits & 0x7F)
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub fn contains(self, day: Weekday) -> bool {
+        self.0 & Self::bit(day) != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    fn bit(day: Weekday) -> u8 {
+        1 << day.num_days_from_monday()
+    }
+}
+
+impl FromIterator<Weekday> for Weekdays {
+    fn from_iter<T: IntoIterator<Item = Weekday>>(iter: T) -> Self {
+        Self(iter.into_iter().fold(0, |bits, day| bits | Self::bit(day)))
+    }
+}
+
+impl fmt::Display for Weekdays {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0x7F => return f.write_str("Daily"),
+            0x1F => return f.write_str("Weekdays"),
+            0x60 => return f.write_str("Weekends"),
+            _ => {}
+        }
+
+        let days: Vec<String> = Self::ALL
+            .into_iter()
+            .filter(|day| self.contains(*day))
+            .map(|day| day.to_string())
+            .collect();
+
+        f.write_str(&days.join(","))
+    }
+}
+
+impl FromStr for Weekdays {
+    type Err = anyhow::Error;
+
+    /// Parses `daily`, `weekdays`, `weekends` or comma separated days like `mon,sat`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().to_lowercase();
+
+        let weekdays = match value.as_str() {
+            "daily" => Self(0x7F),
+            "weekdays" => Self(0x1F),
+            "weekends" => Self(0x60),
+            _ => value
+                .split(',')
+                .map(|day| {
+                    day.trim()
+                        .parse::<Weekday>()
+                        .map_err(|_| anyhow::anyhow!("unknown day of the week: {day}"))
+                })
+                .collect::<anyhow::Result<Self>>()?,
+        };
+
+        anyhow::ensure!(!weekdays.is_empty(), "no days of the week");
+
+        Ok(weekdays)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+    use super::{Schedule, Station, Weekdays};
+
+    #[test]
+    fn test_weekdays() {
+        let test_data = [
+            ("daily", Some("Daily")),
+            ("Weekends", Some("Weekends")),
+            ("mon,tue,wed,thu,fri", Some("Weekdays")),
+            ("sat, mon", Some("Mon,Sat")),
+            ("friday", Some("Fri")),
+            ("", None),
+            ("mon,funday", None),
+        ];
+
+        for (value, want) in test_data {
+            let got = value.parse::<Weekdays>().ok().map(|w| w.to_string());
+            assert_eq!(got.as_deref(), want, "{value}");
+        }
+    }
+
+    #[test]
+    fn test_window() {
+        let schedule = |weekdays: &str, window: &str| {
+            let (start, end) = Schedule::parse_window(window).unwrap();
+
+            Schedule {
+                id: 1,
+                station: new_station(),
+                weekdays: weekdays.parse().unwrap(),
+                start,
+                end,
+            }
+        };
+
+        // 2023-09-02 is Saturday.
+        let at = |day: u32, time: &str| -> NaiveDateTime {
+            NaiveDate::from_ymd_opt(2023, 9, day)
+                .unwrap()
+                .and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+        };
+
+        let test_data = [
+            (
+                schedule("sat", "20:00-22:00"),
+                at(2, "21:00"),
+                Some((at(2, "20:00"), at(2, "22:00"))),
+            ),
+            (schedule("sat", "20:00-22:00"), at(2, "22:00"), None),
+            (schedule("sat", "20:00-22:00"), at(2, "19:59"), None),
+            (schedule("sun", "20:00-22:00"), at(2, "21:00"), None),
+            (
+                schedule("sat", "23:00-01:00"),
+                at(3, "00:30"),
+                Some((at(2, "23:00"), at(3, "01:00"))),
+            ),
+            (schedule("sun", "23:00-01:00"), at(3, "00:30"), None),
+        ];
+
+        for (schedule, now, want) in test_data {
+            assert_eq!(schedule.window(now), want, "{now}");
+        }
+
+        assert!(Weekdays::from_bits(0xFF).contains(Weekday::Sun));
+        assert!(Schedule::parse_window("20:00").is_err());
+        assert!(Schedule::parse_window("20:00-20:00").is_err());
+    }
+
+    fn new_station() -> Station {
+        Station {
+            id: 1,
+            provider: String::new(),
+            provider_id: String::new(),
+            name: "Radio".to_string(),
+            url: String::new(),
+            codec: String::new(),
+            bitrate: 0,
+            tags: "".into(),
+            country: String::new(),
+            check: None,
+        }
+    }
+}
@@ -1,4 +1,8 @@
+use std::fmt;
 use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Station {
@@ -11,6 +15,29 @@ pub struct Station {
     pub bitrate: u32,
     pub tags: Tags,
     pub country: String,
+    /// Result of the last stream check, `None` if never checked.
+    pub check: Option<StationCheck>,
+}
+
+impl Station {
+    /// Returns `true` if the last check couldn't play the stream.
+    pub fn is_broken(&self) -> bool {
+        self.check.as_ref().is_some_and(|c| c.error.is_some())
+    }
+}
+
+/// Result of opening the station stream and decoding its first packet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StationCheck {
+    pub checked_at: DateTime<Utc>,
+    /// Time to the first decoded audio or to the failure.
+    pub latency: Duration,
+    /// Reason the stream can't be played, `None` if it's alive.
+    pub error: Option<String>,
+    /// Format found by the check, `None` if unknown or failed.
+    pub codec: Option<String>,
+    pub bitrate: Option<u32>,
+    pub sample_rate: Option<u32>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -30,9 +57,9 @@ impl DerefMut for Tags {
     }
 }
 
-impl ToString for Tags {
-    fn to_string(&self) -> String {
-        self.join(",")
+impl fmt::Display for Tags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.join(","))
     }
 }
 
@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use reqwest::Url;
+
+/// Parsed HLS playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub enum M3u8 {
+    /// Multivariant playlist listing the same stream in different qualities.
+    Master(Vec<Variant>),
+    /// Playlist listing media segments.
+    Media(MediaPlaylist),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Variant {
+    pub url: String,
+    pub bandwidth: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaPlaylist {
+    pub target_duration: Duration,
+    pub media_sequence: u64,
+    pub segments: Vec<Segment>,
+    pub ended: bool,
+    pub encrypted: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Segment {
+    pub url: String,
+    /// Initialization section (`EXT-X-MAP`) required to decode the segment.
+    pub map: Option<String>,
+}
+
+/// Reports if the playlist uses HLS tags, plain M3U playlists don't.
+pub fn is_hls(body: &str) -> bool {
+    body.lines()
+        .any(|line| line.trim_start().starts_with("#EXT-X-"))
+}
+
+impl M3u8 {
+    pub fn parse(body: &str, base: &str) -> anyhow::Result<Self> {
+        let base = Url::parse(base).context("invalid playlist url")?;
+        let resolve = |uri: &str| -> anyhow::Result<String> {
+            Ok(base.join(uri.trim()).context("invalid segment url")?.into())
+        };
+
+        let mut variants = vec![];
+        let mut media = MediaPlaylist {
+            target_duration: Duration::from_secs(10),
+            media_sequence: 0,
+            segments: vec![],
+            ended: false,
+            encrypted: false,
+        };
+
+        let mut bandwidth = None;
+        let mut map = None;
+
+        for line in body.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(attrs) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+                bandwidth = Some(
+                    attribute(attrs, "BANDWIDTH")
+                        .and_then(|v| v.parse().ok())
+                        .unwrap_or_default(),
+                );
+            } else if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+                let secs: f64 = value.parse().context("invalid target duration")?;
+                media.target_duration = Duration::try_from_secs_f64(secs.max(1.0))
+                    .context("invalid target duration")?;
+            } else if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+                media.media_sequence = value.parse().context("invalid media sequence")?;
+            } else if let Some(attrs) = line.strip_prefix("#EXT-X-MAP:") {
+                let uri = attribute(attrs, "URI").context("map without uri")?;
+                map = Some(resolve(uri)?);
+            } else if let Some(attrs) = line.strip_prefix("#EXT-X-KEY:") {
+                media.encrypted = attribute(attrs, "METHOD").is_some_and(|m| m != "NONE");
+            } else if line == "#EXT-X-ENDLIST" {
+                media.ended = true;
+            } else if line.starts_with('#') {
+                // other tags don't affect playback.
+            } else if let Some(bandwidth) = bandwidth.take() {
+                variants.push(Variant {
+                    url: resolve(line)?,
+                    bandwidth,
+                });
+            } else {
+                media.segments.push(Segment {
+                    url: resolve(line)?,
+                    map: map.clone(),
+                });
+            }
+        }
+
+        if variants.is_empty() {
+            Ok(Self::Media(media))
+        } else {
+            Ok(Self::Master(variants))
+        }
+    }
+}
+
+/// Picks the best quality variant, radio streams are cheap enough to always
+/// prefer the highest bandwidth.
+pub fn select_variant(variants: &[Variant]) -> Option<&Variant> {
+    variants.iter().max_by_key(|v| v.bandwidth)
+}
+
+/// Returns value of the attribute from `NAME=value,NAME="quoted, value"` list.
+fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attrs;
+
+    while !rest.is_empty() {
+        let (key, tail) = rest.split_once('=')?;
+        let tail = tail.trim_start();
+
+        let (value, tail) = if let Some(quoted) = tail.strip_prefix('"') {
+            let end = quoted.find('"')?;
+            let tail = &quoted[end + 1..];
+
+            (&quoted[..end], tail.split_once(',').map_or("", |(_, t)| t))
+        } else {
+            tail.split_once(',').unwrap_or((tail, ""))
+        };
+
+        if key.trim().eq_ignore_ascii_case(name) {
+            return Some(value.trim());
+        }
+
+        rest = tail;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{is_hls, select_variant, M3u8, MediaPlaylist, Segment, Variant};
+
+    #[test]
+    fn test_parse_master() {
+        let body = "#EXTM3U\n\
+            #EXT-X-STREAM-INF:PROGRAM-ID=1,BANDWIDTH=101760,CODECS=\"mp4a.40.5\"\n\
+            low/index.m3u8\n\
+            #EXT-X-STREAM-INF:BANDWIDTH=320000,CODECS=\"mp4a.40.2, mp4a.40.5\"\n\
+            http://cdn.example/high/index.m3u8\n";
+
+        let playlist = M3u8::parse(body, "http://a.example/live/master.m3u8").unwrap();
+        let want = vec![
+            Variant {
+                url: "http://a.example/live/low/index.m3u8".to_string(),
+                bandwidth: 101_760,
+            },
+            Variant {
+                url: "http://cdn.example/high/index.m3u8".to_string(),
+                bandwidth: 320_000,
+            },
+        ];
+
+        assert!(is_hls(body));
+        assert_eq!(playlist, M3u8::Master(want.clone()));
+        assert_eq!(select_variant(&want), Some(&want[1]));
+    }
+
+    #[test]
+    fn test_parse_media() {
+        let body = "#EXTM3U\n\
+            #EXT-X-VERSION:7\n\
+            #EXT-X-TARGETDURATION:6\n\
+            #EXT-X-MEDIA-SEQUENCE:271\n\
+            #EXT-X-MAP:URI=\"init.mp4\"\n\
+            #EXTINF:6.0,\n\
+            seg271.m4s\n\
+            #EXTINF:6.0,\n\
+            seg272.m4s\n\
+            #EXT-X-KEY:METHOD=NONE\n\
+            #EXT-X-ENDLIST\n";
+
+        let playlist = M3u8::parse(body, "http://a.example/live/index.m3u8").unwrap();
+        let segment = |name: &str| Segment {
+            url: format!("http://a.example/live/{name}"),
+            map: Some("http://a.example/live/init.mp4".to_string()),
+        };
+
+        assert_eq!(
+            playlist,
+            M3u8::Media(MediaPlaylist {
+                target_duration: Duration::from_secs(6),
+                media_sequence: 271,
+                segments: vec![segment("seg271.m4s"), segment("seg272.m4s")],
+                ended: true,
+                encrypted: false,
+            })
+        );
+
+        for value in ["1e400", "inf", "1e20", "six"] {
+            let body = format!("#EXTM3U\n#EXT-X-TARGETDURATION:{value}\nseg1.ts\n");
+
+            assert!(
+                M3u8::parse(&body, "http://a.example/live/index.m3u8").is_err(),
+                "{value}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_hls() {
+        assert!(!is_hls(
+            "#EXTM3U\n#EXTINF:-1,Radio\nhttp://a.example/stream\n"
+        ));
+        assert!(is_hls("#EXTM3U\n#EXT-X-TARGETDURATION:10\n"));
+    }
+}
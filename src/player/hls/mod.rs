@@ -0,0 +1,271 @@
+use std::io;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use anyhow::Context;
+use reqwest::blocking::Client;
+
+pub use m3u8::is_hls;
+
+use m3u8::{M3u8, MediaPlaylist};
+
+mod m3u8;
+mod ts;
+
+/// Number of downloaded segments waiting to be decoded.
+const QUEUE_LEN: usize = 3;
+
+/// Segments to start from, counting back from the live edge.
+const LIVE_EDGE_SEGMENTS: usize = 3;
+
+/// Reads HLS stream as a continuous byte stream of the audio elementary stream.
+///
+/// Segments are downloaded by a background thread which refreshes the live
+/// playlist, the reader ends when the playlist is ended or a download fails.
+pub struct HlsReader {
+    // symphonia requires `Sync` readers, mutex is never contended.
+    chunks: Mutex<Receiver<io::Result<Vec<u8>>>>,
+    chunk: Vec<u8>,
+    offset: usize,
+}
+
+impl HlsReader {
+    /// Builds reader from already downloaded playlist `body` of the `url`.
+    pub fn new(client: Client, url: &str, body: &str) -> anyhow::Result<Self> {
+        let (url, playlist) = match M3u8::parse(body, url)? {
+            M3u8::Media(playlist) => (url.to_string(), playlist),
+            M3u8::Master(variants) => {
+                let variant = m3u8::select_variant(&variants).context("no variants")?;
+                log::debug!("hls variant {} ({} bps)", variant.url, variant.bandwidth);
+
+                let body = client
+                    .get(&variant.url)
+                    .send()?
+                    .error_for_status()?
+                    .text()?;
+
+                match M3u8::parse(&body, &variant.url)? {
+                    M3u8::Media(playlist) => (variant.url.clone(), playlist),
+                    M3u8::Master(_) => anyhow::bail!("nested multivariant playlist"),
+                }
+            }
+        };
+
+        anyhow::ensure!(
+            !playlist.encrypted,
+            "encrypted hls streams aren't supported"
+        );
+
+        let (tx, rx) = mpsc::sync_channel(QUEUE_LEN);
+        let fetcher = Fetcher {
+            client,
+            url,
+            tx,
+            next_sequence: None,
+            map: None,
+            demuxer: ts::Demuxer::default(),
+        };
+
+        thread::Builder::new()
+            .name("tradio-hls".to_string())
+            .spawn(move || fetcher.run(playlist))?;
+
+        Ok(Self {
+            chunks: Mutex::new(rx),
+            chunk: vec![],
+            offset: 0,
+        })
+    }
+}
+
+impl io::Read for HlsReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.offset == self.chunk.len() {
+            match self.chunks.get_mut().unwrap().recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.offset = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.chunk.len() - self.offset);
+        buf[..len].copy_from_slice(&self.chunk[self.offset..self.offset + len]);
+        self.offset += len;
+
+        Ok(len)
+    }
+}
+
+struct Fetcher {
+    client: Client,
+    url: String,
+    tx: SyncSender<io::Result<Vec<u8>>>,
+
+    next_sequence: Option<u64>,
+    map: Option<String>,
+    demuxer: ts::Demuxer,
+}
+
+impl Fetcher {
+    fn run(mut self, mut playlist: MediaPlaylist) {
+        loop {
+            let loaded_at = Instant::now();
+
+            let sent = match self.fetch_segments(&playlist) {
+                Ok(sent) => sent,
+                Err(e) => {
+                    log::error!("hls {}: {e:?}", self.url);
+                    let _ = self.tx.send(Err(io::Error::other(e)));
+                    return;
+                }
+            };
+
+            // reader is dropped.
+            let Some(sent) = sent else {
+                return;
+            };
+
+            if playlist.ended {
+                return;
+            }
+
+            // reload playlist after the target duration, or earlier if it had no new segments.
+            let period = if sent > 0 {
+                playlist.target_duration
+            } else {
+                playlist.target_duration / 2
+            };
+
+            thread::sleep(period.saturating_sub(loaded_at.elapsed()));
+
+            playlist = match self.reload() {
+                Ok(playlist) => playlist,
+                Err(e) => {
+                    log::error!("hls reload {}: {e:?}", self.url);
+                    let _ = self.tx.send(Err(io::Error::other(e)));
+                    return;
+                }
+            };
+        }
+    }
+
+    fn reload(&self) -> anyhow::Result<MediaPlaylist> {
+        let body = self
+            .client
+            .get(&self.url)
+            .send()?
+            .error_for_status()?
+            .text()?;
+
+        match M3u8::parse(&body, &self.url)? {
+            M3u8::Media(playlist) => Ok(playlist),
+            M3u8::Master(_) => anyhow::bail!("media playlist became multivariant"),
+        }
+    }
+
+    /// Downloads new segments, returns how many were sent or `None` if reader is dropped.
+    fn fetch_segments(&mut self, playlist: &MediaPlaylist) -> anyhow::Result<Option<usize>> {
+        anyhow::ensure!(
+            !playlist.encrypted,
+            "encrypted hls streams aren't supported"
+        );
+
+        let first = playlist.media_sequence;
+        let live_edge = first + playlist.segments.len().saturating_sub(LIVE_EDGE_SEGMENTS) as u64;
+
+        let start = match self.next_sequence {
+            // fell behind the live window, continue from its start.
+            Some(next) if next < first => {
+                log::warn!("hls {}: skipped {} segments", self.url, first - next);
+                first
+            }
+            Some(next) => next,
+            None => live_edge,
+        };
+
+        let mut sent = 0;
+
+        for (sequence, segment) in (first..).zip(playlist.segments.iter()) {
+            if sequence < start {
+                continue;
+            }
+
+            let mut data = vec![];
+
+            if segment.map != self.map {
+                if let Some(ref map) = segment.map {
+                    data = self.download(map)?;
+                }
+
+                self.map.clone_from(&segment.map);
+            }
+
+            let bytes = self.download(&segment.url)?;
+            self.demux(&bytes, &mut data);
+
+            if self.tx.send(Ok(data)).is_err() {
+                return Ok(None);
+            }
+
+            self.next_sequence = Some(sequence + 1);
+            sent += 1;
+        }
+
+        Ok(Some(sent))
+    }
+
+    /// Downloads the segment, timeouts are the ones of the shared client.
+    fn download(&self, url: &str) -> anyhow::Result<Vec<u8>> {
+        let bytes = self
+            .client
+            .get(url)
+            .send()?
+            .error_for_status()?
+            .bytes()
+            .with_context(|| format!("download segment {url}"))?;
+
+        Ok(bytes.to_vec())
+    }
+
+    fn demux(&mut self, bytes: &[u8], out: &mut Vec<u8>) {
+        if ts::is_ts(bytes) {
+            self.demuxer.push(bytes, out);
+        } else {
+            out.extend_from_slice(skip_id3(bytes));
+        }
+    }
+}
+
+/// Packed audio segments start with an ID3 tag carrying the timestamp,
+/// it must be removed to get a continuous elementary stream.
+fn skip_id3(data: &[u8]) -> &[u8] {
+    if data.len() < 10 || &data[..3] != b"ID3" {
+        return data;
+    }
+
+    let size = data[6..10]
+        .iter()
+        .fold(0_usize, |size, &b| size << 7 | usize::from(b & 0x7F));
+    let footer = if data[5] & 0x10 == 0 { 0 } else { 10 };
+
+    data.get(10 + size + footer..).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::skip_id3;
+
+    #[test]
+    fn test_skip_id3() {
+        let mut data = b"ID3\x04\x00\x00\x00\x00\x01\x01".to_vec();
+        data.extend([0; 129]);
+        data.extend([0xFF, 0xF1]);
+
+        assert_eq!(skip_id3(&data), [0xFF, 0xF1]);
+        assert_eq!(skip_id3(&[0xFF, 0xF1]), [0xFF, 0xF1]);
+    }
+}
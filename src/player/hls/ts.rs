@@ -0,0 +1,188 @@
+//! Minimal MPEG-TS demuxer extracting the first audio elementary stream.
+//!
+//! Symphonia can't read transport streams, but audio carried in HLS segments
+//! is plain ADTS AAC or MPEG audio once PES headers are stripped.
+
+const PACKET_LEN: usize = 188;
+const SYNC_BYTE: u8 = 0x47;
+const PAT_PID: u16 = 0;
+
+/// Elementary stream types symphonia can decode.
+const AUDIO_STREAM_TYPES: [u8; 3] = [
+    0x03, // MPEG-1 audio
+    0x04, // MPEG-2 audio
+    0x0F, // ADTS AAC
+];
+
+/// Reports if data looks like a transport stream.
+pub fn is_ts(data: &[u8]) -> bool {
+    data.len() >= PACKET_LEN
+        && data
+            .iter()
+            .step_by(PACKET_LEN)
+            .take(3)
+            .all(|&b| b == SYNC_BYTE)
+}
+
+#[derive(Debug, Default)]
+pub struct Demuxer {
+    pmt_pid: Option<u16>,
+    audio_pid: Option<u16>,
+}
+
+impl Demuxer {
+    /// Appends audio payload of all complete packets in `data` to `out`.
+    pub fn push(&mut self, data: &[u8], out: &mut Vec<u8>) {
+        for packet in data.chunks_exact(PACKET_LEN) {
+            if packet[0] != SYNC_BYTE {
+                continue;
+            }
+
+            let start = packet[1] & 0x40 != 0;
+            let pid = u16::from(packet[1] & 0x1F) << 8 | u16::from(packet[2]);
+            let Some(payload) = payload(packet) else {
+                continue;
+            };
+
+            if pid == PAT_PID && start {
+                self.pmt_pid = parse_pat(payload).or(self.pmt_pid);
+            } else if Some(pid) == self.pmt_pid && start {
+                self.audio_pid = parse_pmt(payload).or(self.audio_pid);
+            } else if Some(pid) == self.audio_pid {
+                if start {
+                    out.extend_from_slice(pes_data(payload));
+                } else {
+                    out.extend_from_slice(payload);
+                }
+            }
+        }
+    }
+}
+
+/// Returns packet payload skipping adaptation field.
+fn payload(packet: &[u8]) -> Option<&[u8]> {
+    let control = (packet[3] >> 4) & 0x03;
+
+    if control & 0x01 == 0 {
+        return None;
+    }
+
+    let offset = if control & 0x02 != 0 {
+        5 + usize::from(packet[4])
+    } else {
+        4
+    };
+
+    packet.get(offset..)
+}
+
+/// Returns PSI section body without header and CRC.
+fn section(payload: &[u8], header_len: usize) -> Option<&[u8]> {
+    let pointer = usize::from(*payload.first()?);
+    let table = payload.get(1 + pointer..)?;
+    let length = usize::from(table.get(1)? & 0x0F) << 8 | usize::from(*table.get(2)?);
+
+    table.get(3 + header_len..(3 + length).checked_sub(4)?)
+}
+
+fn parse_pat(payload: &[u8]) -> Option<u16> {
+    section(payload, 5)?
+        .chunks_exact(4)
+        .find(|program| program[0] != 0 || program[1] != 0)
+        .map(|program| u16::from(program[2] & 0x1F) << 8 | u16::from(program[3]))
+}
+
+fn parse_pmt(payload: &[u8]) -> Option<u16> {
+    let body = section(payload, 5)?;
+    let info_len = usize::from(body.get(2)? & 0x0F) << 8 | usize::from(*body.get(3)?);
+    let mut streams = body.get(4 + info_len..)?;
+
+    while streams.len() >= 5 {
+        let stream_type = streams[0];
+        let pid = u16::from(streams[1] & 0x1F) << 8 | u16::from(streams[2]);
+        let es_info_len = usize::from(streams[3] & 0x0F) << 8 | usize::from(streams[4]);
+
+        if AUDIO_STREAM_TYPES.contains(&stream_type) {
+            return Some(pid);
+        }
+
+        streams = streams.get(5 + es_info_len..)?;
+    }
+
+    None
+}
+
+/// Strips PES header from the first payload of a PES packet.
+fn pes_data(payload: &[u8]) -> &[u8] {
+    if payload.len() < 9 || payload[..3] != [0, 0, 1] {
+        return &[];
+    }
+
+    payload
+        .get(9 + usize::from(payload[8])..)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_ts, Demuxer, PACKET_LEN};
+
+    fn packet(pid: u16, start: bool, payload: &[u8]) -> Vec<u8> {
+        let stuffing = PACKET_LEN - 4 - payload.len();
+        #[allow(clippy::cast_possible_truncation)]
+        let mut packet = vec![
+            0x47,
+            u8::from(start) << 6 | (pid >> 8) as u8,
+            pid as u8,
+            if stuffing > 0 { 0x30 } else { 0x10 },
+        ];
+
+        if stuffing > 0 {
+            #[allow(clippy::cast_possible_truncation)]
+            packet.push((stuffing - 1) as u8);
+            packet.extend(std::iter::repeat_n(0xFF, stuffing - 1));
+        }
+
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    #[test]
+    fn test_demux() {
+        let pat = [
+            0x00, // pointer
+            0x00, 0xB0, 0x0D, // table id, section length
+            0x00, 0x01, 0xC1, 0x00, 0x00, // ts id, version, section numbers
+            0x00, 0x01, 0xF0, 0x00, // program 1 -> pmt pid 0x1000
+            0x00, 0x00, 0x00, 0x00, // crc
+        ];
+        let pmt = [
+            0x00, // pointer
+            0x02, 0xB0, 0x17, // table id, section length
+            0x00, 0x01, 0xC1, 0x00, 0x00, // program, version, section numbers
+            0xE1, 0x00, 0xF0, 0x00, // pcr pid, program info length
+            0x1B, 0xE1, 0x00, 0xF0, 0x00, // video stream, skipped
+            0x0F, 0xE1, 0x01, 0xF0, 0x00, // adts aac stream at pid 0x101
+            0x00, 0x00, 0x00, 0x00, // crc
+        ];
+        let pes = [
+            0x00, 0x00, 0x01, 0xC0, 0x00, 0x00, // start code, stream id, length
+            0x80, 0x80, 0x05, 0x21, 0x00, 0x01, 0x00, 0x01, // flags, pts
+            0xFF, 0xF1, 0x50, // audio
+        ];
+
+        let mut data = vec![];
+        data.extend(packet(0, true, &pat));
+        data.extend(packet(0x1000, true, &pmt));
+        data.extend(packet(0x100, true, &[0xAA; 8]));
+        data.extend(packet(0x101, true, &pes));
+        data.extend(packet(0x101, false, &[0x80, 0x2E]));
+
+        assert!(is_ts(&data));
+
+        let mut out = vec![];
+        Demuxer::default().push(&data, &mut out);
+
+        assert_eq!(out, [0xFF, 0xF1, 0x50, 0x80, 0x2E]);
+    }
+}
@@ -1,15 +1,32 @@
-pub use self::rodio::Rodio;
+use std::path::PathBuf;
+use std::time::Duration;
 
+use tokio::sync::broadcast;
+
+use crate::models::{EqGains, Header};
+
+pub use self::output_config::{OutputConfig, SupportedConfig};
+pub use self::recorder::RecordOptions;
+pub use self::rodio::{
+    probe, BufferOptions, Capture, ReconnectPolicy, Rodio, TimeShiftOptions, VolumeCurve,
+};
+pub use self::sleep::SleepTimer;
+
+mod hls;
+mod output_config;
+mod playlist;
+mod recorder;
 mod rodio;
+mod sleep;
 
 pub trait Player: Send + Sync {
-    /// Starts playing given stream.
-    fn play(&self, stream_url: &str) -> anyhow::Result<()>;
+    /// Starts playing given stream in background, progress is reported by [`Player::state`].
+    /// Cancels connection to the previous stream if it isn't established yet.
+    /// `headers` are sent with the stream requests, e.g. the ones stored for the station.
+    /// Loudness normalization starts from `gain` in dB, e.g. remembered for the station.
+    fn play(&self, stream_url: &str, headers: &[Header], gain: f32) -> anyhow::Result<()>;
 
-    /// Waits until current track ended.
-    fn wait_end(&self);
-
-    /// Stops current track. No effect if queue is empty.
+    /// Stops current track or cancels pending connection. No effect if queue is empty.
     fn stop(&self);
 
     /// Pauses current track. No effect if already paused.
@@ -27,6 +44,12 @@ pub trait Player: Send + Sync {
     /// Set volume in percentage [0 - 100].
     fn set_volume(&self, volume: i8);
 
+    /// Gets if output is muted, volume is kept to be restored on unmute.
+    fn is_muted(&self) -> bool;
+
+    /// Mutes or unmutes output without changing volume.
+    fn set_muted(&self, is_muted: bool);
+
     /// Returns all [Device]s currently available to the system
     /// that support one or more output stream formats.
     fn devices(&self) -> anyhow::Result<Vec<Device>>;
@@ -36,6 +59,92 @@ pub trait Player: Send + Sync {
 
     /// Return active [Device] if exists.
     fn active_device(&self) -> Option<Device>;
+
+    /// Sets output format of the device by its id, the device default is used if `None`.
+    /// Format can be set for a device plugged in later. Output is restarted if the device is active.
+    fn set_output_config(
+        &self,
+        device_id: &str,
+        config: Option<OutputConfig>,
+    ) -> anyhow::Result<()>;
+
+    /// Returns receiver of the [`Event`]s published from now on.
+    /// Events are dropped for a subscriber lagging too far behind.
+    fn subscribe(&self) -> broadcast::Receiver<Event>;
+
+    /// Returns latest [Metadata] received from the current stream.
+    fn metadata(&self) -> Option<Metadata>;
+
+    /// Returns [State] of the current stream.
+    fn state(&self) -> State;
+
+    /// Returns number of the streams started by [`Player::play`],
+    /// so the same station played again can be told from the current one.
+    fn plays(&self) -> u64;
+
+    /// Returns names of codecs the player can decode, as reported by
+    /// station directories (e.g. `MP3`, `AAC`).
+    fn codecs(&self) -> Vec<String>;
+
+    /// Starts writing the current stream to a file as is, without re-encoding.
+    /// Only self-framing MP3 and ADTS AAC streams can be recorded this way.
+    /// Returns path of the created file.
+    fn start_recording(&self, station: &str) -> anyhow::Result<PathBuf>;
+
+    /// Finishes current recording. No effect if not recording.
+    fn stop_recording(&self);
+
+    /// Returns path of the file the current stream is recorded to.
+    fn recording(&self) -> Option<PathBuf>;
+
+    /// Returns [Position] within the retained part of the stream, `None` if time-shift is disabled.
+    fn position(&self) -> Option<Position>;
+
+    /// Skips back by the duration, at most to the oldest retained audio.
+    fn rewind(&self, by: Duration);
+
+    /// Skips forward by the duration, at most to the live stream.
+    fn fast_forward(&self, by: Duration);
+
+    /// Continues playback from the live stream, skipping audio buffered while paused.
+    fn jump_to_live(&self);
+
+    /// Gets if streams are adjusted toward the target loudness.
+    fn is_normalizing(&self) -> bool;
+
+    /// Enables or disables loudness normalization of all streams.
+    fn set_normalizing(&self, is_enabled: bool);
+
+    /// Gain in dB the current stream is adjusted by to reach the target loudness.
+    fn gain(&self) -> f32;
+
+    /// Returns gains of the equalizer bands applied to all streams.
+    fn equalizer(&self) -> EqGains;
+
+    /// Sets gains of the equalizer bands, applied to the playing stream at once.
+    fn set_equalizer(&self, gains: EqGains);
+
+    /// Returns [Levels] of the recently played audio.
+    fn levels(&self) -> Levels;
+
+    /// Returns [`BufferLevel`] of the stream being played, `None` if not connected.
+    fn buffer(&self) -> Option<BufferLevel>;
+}
+
+/// State of the current stream.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub enum State {
+    /// Nothing is playing.
+    #[default]
+    Stopped,
+    /// Connection to the stream is being established.
+    Connecting,
+    /// Stream is connected and playing.
+    Playing,
+    /// Stream was interrupted and is being re-opened.
+    Reconnecting { attempt: u32, max_retries: u32 },
+    /// Stream can't be opened or was interrupted and can't be re-opened.
+    Failed(String),
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +152,12 @@ pub struct Device {
     id: String,
     is_active: bool,
     is_default: bool,
+    /// Device isn't backed by the sound hardware, e.g. writes to a file.
+    is_virtual: bool,
+    /// Formats supported by the device, empty for virtual ones.
+    configs: Vec<SupportedConfig>,
+    /// Format chosen for the device, the device default is used if `None`.
+    config: Option<OutputConfig>,
 }
 
 impl Device {
@@ -57,6 +172,51 @@ impl Device {
     pub fn is_default(&self) -> bool {
         self.is_default
     }
+
+    pub fn is_virtual(&self) -> bool {
+        self.is_virtual
+    }
+
+    pub fn configs(&self) -> &[SupportedConfig] {
+        &self.configs
+    }
+
+    pub fn config(&self) -> Option<OutputConfig> {
+        self.config
+    }
+}
+
+/// Output device change made by the user, or by the player as devices are plugged and unplugged.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeviceChange {
+    /// Output moved to the device chosen by the user.
+    Chosen(Device),
+    /// Output format of the active device changed.
+    Reconfigured(Device),
+    /// Active device vanished, output moved to the default device if there is one.
+    Lost {
+        lost: Device,
+        fallback: Option<Device>,
+    },
+    /// Device chosen by the user reappeared and output returned to it.
+    Returned(Device),
+}
+
+/// Change of the playback status, published to the [`Player::subscribe`] receivers.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    StateChanged(State),
+    Metadata(Option<Metadata>),
+    /// Stream failed, published along with [`State::Failed`].
+    Error(String),
+    /// Buffer started or stopped re-buffering, or its level changed noticeably.
+    Buffer(Option<BufferLevel>),
+    Paused(bool),
+    Volume {
+        volume: i8,
+        is_muted: bool,
+    },
+    DeviceChanged(DeviceChange),
 }
 
 impl PartialEq<Self> for Device {
@@ -64,3 +224,128 @@ impl PartialEq<Self> for Device {
         self.id == other.id
     }
 }
+
+/// Playback position within the retained part of a live stream.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct Position {
+    position: Duration,
+    duration: Duration,
+}
+
+impl Position {
+    /// Time from the oldest retained audio to the live stream.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// How far playback is behind the live stream.
+    pub fn behind_live(&self) -> Duration {
+        self.duration.saturating_sub(self.position)
+    }
+}
+
+/// In-band stream metadata (e.g. ICY `StreamTitle`/`StreamUrl`).
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Metadata {
+    title: Option<String>,
+    url: Option<String>,
+}
+
+impl Metadata {
+    /// Current song title, usually formatted as "Artist - Title".
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Station or track web page announced by the stream.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+}
+
+/// Format of the stream found by [`probe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamInfo {
+    codec: Option<String>,
+    bitrate: Option<u32>,
+    sample_rate: u32,
+}
+
+impl StreamInfo {
+    /// Codec name as reported by station directories (e.g. `MP3`), `None` if unknown.
+    pub fn codec(&self) -> Option<&str> {
+        self.codec.as_deref()
+    }
+
+    /// Bitrate in kbps announced by the server.
+    pub fn bitrate(&self) -> Option<u32> {
+        self.bitrate
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+/// Part of the stream downloaded ahead of playback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferLevel {
+    buffered: Duration,
+    pre_roll: Duration,
+    is_buffering: bool,
+}
+
+impl BufferLevel {
+    /// Estimated playback time of the buffered stream.
+    pub fn buffered(&self) -> Duration {
+        self.buffered
+    }
+
+    /// Buffered before playback starts or resumes after underrun.
+    pub fn pre_roll(&self) -> Duration {
+        self.pre_roll
+    }
+
+    /// Returns `true` while playback waits for the pre-roll.
+    pub fn is_buffering(&self) -> bool {
+        self.is_buffering
+    }
+}
+
+/// Levels and spectrum of the played audio, before the volume is applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Levels {
+    rms: f32,
+    peak: f32,
+    bands: Vec<f32>,
+}
+
+impl Levels {
+    /// Level in dBFS treated as silence.
+    pub const FLOOR: f32 = -60.0;
+
+    /// Root mean square level in dBFS.
+    pub fn rms(&self) -> f32 {
+        self.rms
+    }
+
+    /// Peak level in dBFS.
+    pub fn peak(&self) -> f32 {
+        self.peak
+    }
+
+    /// Spectrum from the lowest frequency band, in range [0 - 1].
+    pub fn bands(&self) -> &[f32] {
+        &self.bands
+    }
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Self {
+            rms: Self::FLOOR,
+            peak: Self::FLOOR,
+            bands: vec![],
+        }
+    }
+}
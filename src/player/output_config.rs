@@ -0,0 +1,261 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::Context;
+
+/// Format of the output stream chosen for a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputConfig {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Frames per period of the device, chosen by the device if `None`.
+    pub buffer_size: Option<u32>,
+}
+
+impl OutputConfig {
+    /// Sample rates offered when a device supports a range of them.
+    const SAMPLE_RATES: [u32; 6] = [44_100, 48_000, 88_200, 96_000, 176_400, 192_000];
+
+    /// Buffer sizes offered when a device supports a range of them.
+    const BUFFER_SIZES: [u32; 6] = [128, 256, 512, 1024, 2048, 4096];
+
+    /// Returns configs with the common sample rates supported by the device.
+    pub fn options(supported: &[SupportedConfig]) -> Vec<Self> {
+        let mut options = vec![];
+
+        for range in supported {
+            for sample_rate in Self::SAMPLE_RATES {
+                let option = Self {
+                    sample_rate,
+                    channels: range.channels,
+                    buffer_size: None,
+                };
+
+                if range.supports(&option) && !options.contains(&option) {
+                    options.push(option);
+                }
+            }
+        }
+
+        options
+    }
+
+    /// Returns the config following `current` in [`Self::options`], stepping back if `backward`.
+    /// Device default is the first and the last option, it's `None`.
+    pub fn cycle(
+        current: Option<Self>,
+        supported: &[SupportedConfig],
+        backward: bool,
+    ) -> Option<Self> {
+        let mut options: Vec<Option<Self>> = vec![None];
+        options.extend(Self::options(supported).into_iter().map(Some));
+
+        if backward {
+            options.reverse();
+        }
+
+        let format = current.map(|c| (c.sample_rate, c.channels));
+        let idx = options
+            .iter()
+            .position(|o| o.map(|c| (c.sample_rate, c.channels)) == format);
+
+        let next = match idx {
+            Some(idx) => options[(idx + 1) % options.len()],
+            None => options.get(1).copied().flatten(),
+        };
+
+        // buffer size is kept while it's supported with the new format.
+        next.map(|c| {
+            let config = Self {
+                buffer_size: current.and_then(|c| c.buffer_size),
+                ..c
+            };
+
+            if supported.iter().any(|s| s.supports(&config)) {
+                config
+            } else {
+                c
+            }
+        })
+    }
+
+    /// Returns the config with the next supported buffer size, the device default follows the largest.
+    pub fn next_buffer_size(self, supported: &[SupportedConfig]) -> Self {
+        let mut sizes: Vec<Option<u32>> = vec![None];
+        sizes.extend(Self::BUFFER_SIZES.into_iter().map(Some));
+
+        let idx = sizes
+            .iter()
+            .position(|&s| s == self.buffer_size)
+            .unwrap_or(0);
+
+        sizes
+            .into_iter()
+            .cycle()
+            .skip(idx + 1)
+            .take(Self::BUFFER_SIZES.len() + 1)
+            .map(|buffer_size| Self {
+                buffer_size,
+                ..self
+            })
+            .find(|c| c.buffer_size.is_none() || supported.iter().any(|s| s.supports(c)))
+            .unwrap_or(self)
+    }
+}
+
+/// Stored as `<sample rate>:<channels>[:<buffer size>]`.
+impl fmt::Display for OutputConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.sample_rate, self.channels)?;
+
+        if let Some(buffer_size) = self.buffer_size {
+            write!(f, ":{buffer_size}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for OutputConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut parts = value.trim().split(':');
+
+        let sample_rate = parts.next().context("no sample rate")?.parse()?;
+        let channels = parts.next().context("no channels")?.parse()?;
+        let buffer_size = parts.next().map(str::parse).transpose()?;
+
+        anyhow::ensure!(parts.next().is_none(), "unexpected output config {value}");
+
+        Ok(Self {
+            sample_rate,
+            channels,
+            buffer_size,
+        })
+    }
+}
+
+/// Range of output formats supported by a device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupportedConfig {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    /// Range of the buffer sizes in frames, `None` if unknown.
+    pub buffer_sizes: Option<(u32, u32)>,
+}
+
+impl SupportedConfig {
+    pub fn supports(&self, config: &OutputConfig) -> bool {
+        let is_buffer_supported = match (config.buffer_size, self.buffer_sizes) {
+            (Some(size), Some((min, max))) => (min..=max).contains(&size),
+            // device doesn't tell its limits, so any size is tried.
+            (Some(_) | None, None) | (None, Some(_)) => true,
+        };
+
+        self.channels == config.channels
+            && (self.min_sample_rate..=self.max_sample_rate).contains(&config.sample_rate)
+            && is_buffer_supported
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OutputConfig, SupportedConfig};
+
+    fn config(sample_rate: u32, channels: u16, buffer_size: Option<u32>) -> OutputConfig {
+        OutputConfig {
+            sample_rate,
+            channels,
+            buffer_size,
+        }
+    }
+
+    fn supported() -> Vec<SupportedConfig> {
+        vec![
+            SupportedConfig {
+                channels: 2,
+                min_sample_rate: 44_100,
+                max_sample_rate: 48_000,
+                buffer_sizes: Some((256, 1024)),
+            },
+            SupportedConfig {
+                channels: 1,
+                min_sample_rate: 48_000,
+                max_sample_rate: 48_000,
+                buffer_sizes: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_parse() {
+        let test_data = [
+            ("48000:2", Some(config(48_000, 2, None))),
+            ("44100:1:512", Some(config(44_100, 1, Some(512)))),
+            ("48000", None),
+            ("48000:2:512:1", None),
+            ("a:2", None),
+        ];
+
+        for (value, want) in test_data {
+            let got = value.parse::<OutputConfig>().ok();
+            assert_eq!(got, want, "{value}");
+
+            if let Some(got) = got {
+                assert_eq!(got.to_string(), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cycle() {
+        let supported = supported();
+
+        // (current, backward, want).
+        let test_data = [
+            (None, false, Some(config(44_100, 2, None))),
+            (
+                Some(config(44_100, 2, Some(512))),
+                false,
+                Some(config(48_000, 2, Some(512))),
+            ),
+            (
+                Some(config(48_000, 2, Some(512))),
+                false,
+                Some(config(48_000, 1, Some(512))),
+            ),
+            (Some(config(48_000, 1, None)), false, None),
+            (None, true, Some(config(48_000, 1, None))),
+            (
+                Some(config(96_000, 2, None)),
+                false,
+                Some(config(44_100, 2, None)),
+            ),
+        ];
+
+        for (current, backward, want) in test_data {
+            let got = OutputConfig::cycle(current, &supported, backward);
+            assert_eq!(got, want, "{current:?} backward {backward}");
+        }
+    }
+
+    #[test]
+    fn test_next_buffer_size() {
+        let supported = supported();
+
+        // (buffer size, next one).
+        let test_data = [
+            (None, Some(256)),
+            (Some(256), Some(512)),
+            (Some(1024), None),
+            (Some(100), Some(256)),
+        ];
+
+        for (buffer_size, want) in test_data {
+            let got = config(48_000, 2, buffer_size).next_buffer_size(&supported);
+            assert_eq!(got.buffer_size, want, "{buffer_size:?}");
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use reqwest::Url;
+
+use super::hls;
+
+/// Playlist formats which can be used instead of a direct stream URL.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Playlist {
+    M3u,
+    Pls,
+    Xspf,
+    Asx,
+}
+
+impl Playlist {
+    /// Detects playlist by response `Content-Type`. Falls back to URL extension
+    /// only if the type is missing or generic, explicit audio type wins over it.
+    pub fn detect(content_type: Option<&str>, url: &str) -> Option<Self> {
+        let mime = content_type.map(|t| {
+            t.split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        });
+
+        match mime.as_deref() {
+            None | Some("" | "application/octet-stream" | "text/plain") => {
+                Self::from_extension(url)
+            }
+            Some(mime) => Self::from_mime(mime),
+        }
+    }
+
+    fn from_mime(mime: &str) -> Option<Self> {
+        match mime {
+            "audio/x-mpegurl"
+            | "audio/mpegurl"
+            | "application/x-mpegurl"
+            | "application/vnd.apple.mpegurl" => Some(Self::M3u),
+            "audio/x-scpls" | "audio/scpls" | "application/pls+xml" => Some(Self::Pls),
+            "application/xspf+xml" => Some(Self::Xspf),
+            "video/x-ms-asx" | "video/x-ms-wax" | "audio/x-ms-wax" | "video/x-ms-wvx" => {
+                Some(Self::Asx)
+            }
+            _ => None,
+        }
+    }
+
+    fn from_extension(url: &str) -> Option<Self> {
+        let path = Url::parse(url).map_or_else(|_| url.to_string(), |u| u.path().to_string());
+        let (_, extension) = path.rsplit_once('.')?;
+
+        match extension.to_ascii_lowercase().as_str() {
+            "m3u" | "m3u8" => Some(Self::M3u),
+            "pls" => Some(Self::Pls),
+            "xspf" => Some(Self::Xspf),
+            "asx" | "wax" | "wvx" => Some(Self::Asx),
+            _ => None,
+        }
+    }
+
+    /// Reports if the playlist is a HLS playlist, which lists segments of
+    /// a single stream instead of alternative streams.
+    pub fn is_hls(self, body: &str) -> bool {
+        self == Self::M3u && hls::is_hls(body)
+    }
+
+    /// Returns stream URLs listed in the playlist in order of appearance,
+    /// relative entries are resolved against `base`.
+    pub fn parse(self, body: &str, base: &str) -> Vec<String> {
+        let entries = match self {
+            Self::M3u => parse_m3u(body),
+            Self::Pls => parse_pls(body),
+            Self::Xspf => parse_xspf(body),
+            Self::Asx => parse_asx(body),
+        };
+
+        let base = Url::parse(base).ok();
+
+        entries
+            .into_iter()
+            .filter_map(|entry| match base {
+                Some(ref base) => base.join(entry.trim()).ok().map(String::from),
+                None => Url::parse(entry.trim()).ok().map(String::from),
+            })
+            .collect()
+    }
+}
+
+fn parse_m3u(body: &str) -> Vec<String> {
+    body.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn parse_pls(body: &str) -> Vec<String> {
+    let mut entries: Vec<(u32, String)> = body
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.trim().split_once('=')?;
+            let key = key.trim().to_ascii_lowercase();
+            let idx = key.strip_prefix("file")?.parse().ok()?;
+
+            Some((idx, value.trim().to_string()))
+        })
+        .collect();
+
+    entries.sort_by_key(|(idx, _)| *idx);
+    entries.into_iter().map(|(_, url)| url).collect()
+}
+
+fn parse_xspf(body: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut rest = body;
+
+    while let Some(start) = rest.find("<location>") {
+        rest = &rest[start + "<location>".len()..];
+
+        let Some(end) = rest.find("</location>") else {
+            break;
+        };
+
+        result.push(unescape(rest[..end].trim()));
+        rest = &rest[end..];
+    }
+
+    result
+}
+
+fn parse_asx(body: &str) -> Vec<String> {
+    let mut result = vec![];
+
+    for tag in body.split('<').skip(1) {
+        let tag = tag.split('>').next().unwrap_or_default();
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        if name == "ref" || name == "entryref" {
+            if let Some(href) = attribute(tag, "href") {
+                result.push(unescape(href));
+            }
+        }
+    }
+
+    result
+}
+
+/// Returns value of the quoted attribute of a XML tag, name is case-insensitive.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+
+    while let Some(pos) = lower[from..].find(name) {
+        let start = from + pos + name.len();
+        let rest = tag[start..].trim_start();
+
+        if let Some(value) = rest.strip_prefix('=') {
+            let value = value.trim_start();
+            let quote = value.chars().next()?;
+
+            if quote == '"' || quote == '\'' {
+                let value = &value[1..];
+                return value.find(quote).map(|end| &value[..end]);
+            }
+        }
+
+        from = start;
+    }
+
+    None
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Playlist;
+
+    #[test]
+    fn test_detect() {
+        let test_data = [
+            (
+                Some("audio/x-mpegurl"),
+                "http://a.b/live",
+                Some(Playlist::M3u),
+            ),
+            (
+                Some("audio/x-scpls; charset=utf-8"),
+                "http://a.b/live",
+                Some(Playlist::Pls),
+            ),
+            (
+                Some("application/xspf+xml"),
+                "http://a.b/live",
+                Some(Playlist::Xspf),
+            ),
+            (
+                Some("video/x-ms-asx"),
+                "http://a.b/live",
+                Some(Playlist::Asx),
+            ),
+            (Some("audio/mpeg"), "http://a.b/live", None),
+            (Some("audio/mpeg"), "http://a.b/stream.m3u", None),
+            (Some("audio/aacp"), "http://a.b/live.pls", None),
+            (
+                Some("application/octet-stream"),
+                "http://a.b/live.m3u",
+                Some(Playlist::M3u),
+            ),
+            (
+                Some("text/plain"),
+                "http://a.b/live.PLS?x=1",
+                Some(Playlist::Pls),
+            ),
+            (None, "http://a.b/live.m3u", Some(Playlist::M3u)),
+            (None, "http://a.b/live/index.m3u8", Some(Playlist::M3u)),
+            (None, "http://a.b/live.mp3", None),
+            (None, "http://a.b/live", None),
+        ];
+
+        for (content_type, url, want) in test_data {
+            assert_eq!(
+                Playlist::detect(content_type, url),
+                want,
+                "{content_type:?} {url}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let base = "http://radio.example/lists/main.pls";
+        let test_data = [
+            (
+                Playlist::M3u,
+                "#EXTM3U\n#EXTINF:-1,Radio\nhttp://a.example/stream\n\n  relative/stream.mp3  \n",
+                vec![
+                    "http://a.example/stream",
+                    "http://radio.example/lists/relative/stream.mp3",
+                ],
+            ),
+            (
+                Playlist::Pls,
+                "[playlist]\nNumberOfEntries=2\nFile2=http://b.example/2\nTitle1=Radio\nfile1=http://b.example/1\nVersion=2\n",
+                vec!["http://b.example/1", "http://b.example/2"],
+            ),
+            (
+                Playlist::Xspf,
+                r#"<?xml version="1.0"?><playlist><trackList>
+                    <track><location>http://c.example/1?a=1&amp;b=2</location></track>
+                    <track><location> http://c.example/2 </location></track>
+                </trackList></playlist>"#,
+                vec!["http://c.example/1?a=1&b=2", "http://c.example/2"],
+            ),
+            (
+                Playlist::Asx,
+                r#"<ASX version="3.0"><Entry><Ref HREF = "http://d.example/1" /></Entry>
+                    <entry><ref href='mms://d.example/2'/></entry>
+                    <EntryRef href="http://d.example/more.asx"/></ASX>"#,
+                vec![
+                    "http://d.example/1",
+                    "mms://d.example/2",
+                    "http://d.example/more.asx",
+                ],
+            ),
+        ];
+
+        for (playlist, body, want) in test_data {
+            assert_eq!(playlist.parse(body, base), want, "{playlist:?}");
+        }
+    }
+}
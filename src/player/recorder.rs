@@ -0,0 +1,220 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::Local;
+
+/// Where and how streams are recorded.
+#[derive(Debug, Clone)]
+pub struct RecordOptions {
+    /// Directory for recorded files, created if missing.
+    pub dir: PathBuf,
+    /// Start a new file each time the stream title changes.
+    pub split_by_track: bool,
+}
+
+/// Writes raw stream bytes to a file, shared between the player and stream readers.
+#[derive(Debug, Clone, Default)]
+pub struct Recorder(Arc<Mutex<Option<Recording>>>);
+
+#[derive(Debug)]
+struct Recording {
+    options: RecordOptions,
+    station: String,
+    extension: String,
+    path: PathBuf,
+    file: BufWriter<File>,
+}
+
+impl Recorder {
+    /// Starts a new recording, the previous one is finished.
+    pub fn start(
+        &self,
+        options: &RecordOptions,
+        station: &str,
+        title: Option<&str>,
+        extension: &str,
+    ) -> io::Result<PathBuf> {
+        self.stop();
+
+        fs::create_dir_all(&options.dir)?;
+
+        let title = title.filter(|_| options.split_by_track);
+        let (path, file) = create_file(&options.dir, station, title, extension)?;
+
+        log::info!("recording to {}", path.display());
+
+        *self.0.lock().unwrap() = Some(Recording {
+            options: options.clone(),
+            station: station.to_string(),
+            extension: extension.to_string(),
+            path: path.clone(),
+            file,
+        });
+
+        Ok(path)
+    }
+
+    /// Finishes current recording. No effect if not recording.
+    pub fn stop(&self) {
+        if let Some(mut recording) = self.0.lock().unwrap().take() {
+            if let Err(e) = recording.file.flush() {
+                log::error!("flush {}: {e:?}", recording.path.display());
+            }
+        }
+    }
+
+    /// Returns path of the file being recorded.
+    pub fn path(&self) -> Option<PathBuf> {
+        self.0.lock().unwrap().as_ref().map(|r| r.path.clone())
+    }
+
+    /// Appends bytes to the recording, recording is stopped on write errors.
+    pub fn write(&self, buf: &[u8]) {
+        let mut recording = self.0.lock().unwrap();
+
+        if let Some(ref mut r) = *recording {
+            if let Err(e) = r.file.write_all(buf) {
+                log::error!("write {}: {e:?}", r.path.display());
+                *recording = None;
+            }
+        }
+    }
+
+    /// Notifies about the stream title change, starts a new file if splitting by track.
+    pub fn set_title(&self, title: Option<&str>) {
+        let mut recording = self.0.lock().unwrap();
+
+        let Some(ref mut r) = *recording else {
+            return;
+        };
+
+        if !r.options.split_by_track {
+            return;
+        }
+
+        let result = r
+            .file
+            .flush()
+            .and_then(|()| create_file(&r.options.dir, &r.station, title, &r.extension));
+
+        match result {
+            Ok((path, file)) => {
+                log::info!("recording to {}", path.display());
+                r.path = path;
+                r.file = file;
+            }
+            Err(e) => {
+                log::error!("split {}: {e:?}", r.path.display());
+                *recording = None;
+            }
+        }
+    }
+}
+
+/// Creates a new file, numbered if the one of the same second exists, e.g. after a quick track change.
+fn create_file(
+    dir: &Path,
+    station: &str,
+    title: Option<&str>,
+    extension: &str,
+) -> io::Result<(PathBuf, BufWriter<File>)> {
+    let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S").to_string();
+
+    for n in 0_u32.. {
+        let timestamp = match n {
+            0 => timestamp.clone(),
+            n => format!("{timestamp}-{n}"),
+        };
+        let path = dir.join(file_name(station, title, &timestamp, extension));
+
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, BufWriter::new(file))),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(io::ErrorKind::AlreadyExists.into())
+}
+
+fn file_name(station: &str, title: Option<&str>, timestamp: &str, extension: &str) -> String {
+    let mut name = sanitize(station);
+
+    if let Some(title) = title.map(sanitize).filter(|t| !t.is_empty()) {
+        name.push_str(" - ");
+        name.push_str(&title);
+    }
+
+    format!("{name} - {timestamp}.{extension}")
+}
+
+/// Replaces characters which aren't allowed in file names on common systems.
+fn sanitize(value: &str) -> String {
+    value
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{file_name, RecordOptions, Recorder};
+
+    #[test]
+    fn test_file_name() {
+        let test_data = [
+            ("Radio", None, "Radio - ts.mp3"),
+            (" Radio/FM ", Some(""), "Radio_FM - ts.mp3"),
+            (
+                "Radio",
+                Some("AC/DC - T.N.T."),
+                "Radio - AC_DC - T.N.T. - ts.mp3",
+            ),
+        ];
+
+        for (station, title, want) in test_data {
+            assert_eq!(file_name(station, title, "ts", "mp3"), want);
+        }
+    }
+
+    #[test]
+    fn test_split_by_track() {
+        let dir = std::env::temp_dir().join(format!("tradio-recorder-{}", std::process::id()));
+        let options = RecordOptions {
+            dir: dir.clone(),
+            split_by_track: true,
+        };
+
+        let recorder = Recorder::default();
+        let first = recorder
+            .start(&options, "Radio", Some("One"), "mp3")
+            .unwrap();
+        recorder.write(b"abc");
+        recorder.set_title(Some("Two"));
+        recorder.write(b"def");
+        let second = recorder.path().unwrap();
+        // the same title within a second doesn't overwrite the previous file.
+        recorder.set_title(Some("Two"));
+        recorder.write(b"ghi");
+        let third = recorder.path().unwrap();
+        recorder.stop();
+
+        assert!(recorder.path().is_none());
+        assert_ne!(second, third);
+        assert_eq!(fs::read(&first).unwrap(), b"abc");
+        assert_eq!(fs::read(&second).unwrap(), b"def");
+        assert_eq!(fs::read(&third).unwrap(), b"ghi");
+        assert!(second.to_str().unwrap().contains("Radio - Two - "));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
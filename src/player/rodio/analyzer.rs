@@ -0,0 +1,370 @@
+use std::f32::consts::PI;
+use std::mem;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError};
+use std::thread;
+use std::time::Duration;
+
+use rodio::Source;
+
+use super::super::Levels;
+use super::Shared;
+
+/// Frames sent to the analyzer at once, about 20 ms at 48 kHz.
+const CHUNK_FRAMES: usize = 1024;
+
+/// Chunks waiting for the analyzer, new ones are dropped if it falls behind.
+const QUEUE_LEN: usize = 8;
+
+/// Samples of the spectrum window, 43 ms at 48 kHz.
+const FFT_SIZE: usize = 2048;
+
+/// Spectrum bands spread logarithmically between these frequencies.
+const BANDS: usize = 16;
+const MIN_FREQUENCY: f32 = 40.0;
+const MAX_FREQUENCY: f32 = 16_000.0;
+
+/// Fall of the levels in dB per analyzed chunk, so meters drop smoothly.
+const DECAY: f32 = 1.5;
+
+/// Levels fall without new chunks, e.g. if no output device is used.
+const IDLE_TIMEOUT: Duration = Duration::from_millis(100);
+
+struct Chunk {
+    samples: Vec<f32>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+/// Source passing samples through while copying them to the analyzer thread,
+/// which never blocks the audio thread.
+pub struct Tap<S> {
+    source: S,
+    chunk: Vec<f32>,
+    chunks: SyncSender<Chunk>,
+    /// Analyzed chunks returned for reuse.
+    spare: Receiver<Vec<f32>>,
+}
+
+impl<S: Source<Item = f32>> Tap<S> {
+    /// Wraps the source, levels of its samples are published to `levels`.
+    pub fn new(source: S, levels: Shared<Levels>) -> Self {
+        let (chunks_tx, chunks_rx) = mpsc::sync_channel(QUEUE_LEN);
+        let (spare_tx, spare_rx) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("tradio-analyzer".to_string())
+            .spawn(move || Analyzer::new(levels).run(&chunks_rx, &spare_tx))
+            .expect("can't spawn analyzer");
+
+        Self {
+            source,
+            chunk: Vec::with_capacity(CHUNK_FRAMES * 2),
+            chunks: chunks_tx,
+            spare: spare_rx,
+        }
+    }
+
+    fn flush(&mut self) {
+        let next = self.spare.try_recv().unwrap_or_default();
+        let chunk = Chunk {
+            samples: mem::replace(&mut self.chunk, next),
+            channels: self.source.channels(),
+            sample_rate: self.source.sample_rate(),
+        };
+
+        if let Err(TrySendError::Full(chunk) | TrySendError::Disconnected(chunk)) =
+            self.chunks.try_send(chunk)
+        {
+            self.chunk = chunk.samples;
+        }
+
+        self.chunk.clear();
+    }
+}
+
+impl<S: Source<Item = f32>> Source for Tap<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for Tap<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = self.source.next()?;
+
+        self.chunk.push(sample);
+
+        if self.chunk.len() >= CHUNK_FRAMES * usize::from(self.source.channels()) {
+            self.flush();
+        }
+
+        Some(sample)
+    }
+}
+
+/// Computes levels and spectrum of the tapped samples.
+struct Analyzer {
+    levels: Shared<Levels>,
+    /// Recent mono samples, the oldest first.
+    window: Vec<f32>,
+    hann: Vec<f32>,
+    rms: f32,
+    peak: f32,
+    /// Band levels in dB.
+    bands: [f32; BANDS],
+}
+
+impl Analyzer {
+    fn new(levels: Shared<Levels>) -> Self {
+        #[allow(clippy::cast_precision_loss)]
+        let hann = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+
+        Self {
+            levels,
+            window: vec![0.0; FFT_SIZE],
+            hann,
+            rms: Levels::FLOOR,
+            peak: Levels::FLOOR,
+            bands: [Levels::FLOOR; BANDS],
+        }
+    }
+
+    fn run(&mut self, chunks: &Receiver<Chunk>, spare: &Sender<Vec<f32>>) {
+        loop {
+            match chunks.recv_timeout(IDLE_TIMEOUT) {
+                Ok(chunk) => {
+                    self.analyze(&chunk);
+                    // tap is dropped with the output, buffer isn't needed then.
+                    let _ = spare.send(chunk.samples);
+                }
+                Err(RecvTimeoutError::Timeout) => self.decay(),
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+
+            self.publish();
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn analyze(&mut self, chunk: &Chunk) {
+        let channels = usize::from(chunk.channels.max(1));
+        let frames = chunk.samples.len() / channels;
+
+        if frames == 0 {
+            return;
+        }
+
+        let square_sum: f32 = chunk.samples.iter().map(|s| s * s).sum();
+        let peak = chunk.samples.iter().fold(0.0_f32, |p, s| p.max(s.abs()));
+
+        self.rms = rise_or_decay(
+            self.rms,
+            to_db((square_sum / chunk.samples.len() as f32).sqrt()),
+        );
+        self.peak = rise_or_decay(self.peak, to_db(peak));
+
+        // only the latest window is analyzed, older samples are shifted out.
+        let mono = chunk
+            .samples
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32);
+
+        let shift = frames.min(FFT_SIZE);
+        self.window.rotate_left(shift);
+        let start = FFT_SIZE - shift;
+        for (slot, sample) in self.window[start..]
+            .iter_mut()
+            .zip(mono.skip(frames - shift))
+        {
+            *slot = sample;
+        }
+
+        let bands = spectrum(&self.window, &self.hann, chunk.sample_rate);
+
+        for (band, level) in self.bands.iter_mut().zip(bands) {
+            *band = rise_or_decay(*band, level);
+        }
+    }
+
+    fn decay(&mut self) {
+        self.rms = rise_or_decay(self.rms, Levels::FLOOR);
+        self.peak = rise_or_decay(self.peak, Levels::FLOOR);
+
+        for band in &mut self.bands {
+            *band = rise_or_decay(*band, Levels::FLOOR);
+        }
+    }
+
+    fn publish(&self) {
+        self.levels.set(Levels {
+            rms: self.rms,
+            peak: self.peak,
+            bands: self
+                .bands
+                .iter()
+                .map(|&db| ((db - Levels::FLOOR) / -Levels::FLOOR).clamp(0.0, 1.0))
+                .collect(),
+        });
+    }
+}
+
+/// Rises to the new level at once, falls slowly.
+fn rise_or_decay(current: f32, new: f32) -> f32 {
+    new.max(current - DECAY).max(Levels::FLOOR)
+}
+
+fn to_db(amplitude: f32) -> f32 {
+    (20.0 * amplitude.log10()).max(Levels::FLOOR)
+}
+
+/// Returns levels in dB of the bands of the windowed samples.
+#[allow(clippy::cast_precision_loss)]
+fn spectrum(samples: &[f32], hann: &[f32], sample_rate: u32) -> [f32; BANDS] {
+    let mut re: Vec<f32> = samples.iter().zip(hann).map(|(s, w)| s * w).collect();
+    let mut im = vec![0.0; re.len()];
+
+    fft(&mut re, &mut im);
+
+    // full scale sine gives magnitude of the half of the window sum.
+    let scale = 2.0 / hann.iter().sum::<f32>();
+    let bin_width = sample_rate as f32 / re.len() as f32;
+    let ratio = (MAX_FREQUENCY / MIN_FREQUENCY).powf(1.0 / BANDS as f32);
+
+    let mut bands = [Levels::FLOOR; BANDS];
+    let mut low = MIN_FREQUENCY;
+
+    for band in &mut bands {
+        let high = low * ratio;
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let (first, last) = (
+            (low / bin_width).floor() as usize,
+            ((high / bin_width).ceil() as usize).min(re.len() / 2),
+        );
+
+        let magnitude = (first..last.max(first + 1))
+            .filter_map(|bin| Some(re.get(bin)?.hypot(*im.get(bin)?)))
+            .fold(0.0_f32, f32::max);
+
+        *band = to_db(magnitude * scale);
+        low = high;
+    }
+
+    bands
+}
+
+/// In-place radix-2 Cooley-Tukey FFT, the length must be a power of two.
+#[allow(clippy::cast_precision_loss)]
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let mut j = 0;
+
+    for i in 1..n {
+        let mut bit = n >> 1;
+
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+
+        j |= bit;
+
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+
+    while len <= n {
+        let angle = -2.0 * PI / len as f32;
+
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::{spectrum, Analyzer, Chunk, Levels, Shared, BANDS, FFT_SIZE};
+
+    #[allow(clippy::cast_precision_loss)]
+    fn sine(frequency: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * PI * frequency * i as f32 / 48_000.0).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_spectrum() {
+        let hann = Analyzer::new(Shared::default()).hann;
+
+        // (frequency, band with the peak).
+        let test_data = [(100.0, 2), (1000.0, 8), (10_000.0, 14)];
+
+        for (frequency, want) in test_data {
+            let bands = spectrum(&sine(frequency, 1.0, FFT_SIZE), &hann, 48_000);
+            let loudest = (0..BANDS)
+                .max_by(|&a, &b| bands[a].total_cmp(&bands[b]))
+                .unwrap();
+
+            assert_eq!(loudest, want, "{frequency} Hz: {bands:?}");
+            assert!(bands[want] > -1.5, "{frequency} Hz: {}", bands[want]);
+        }
+    }
+
+    #[test]
+    fn test_levels() {
+        let levels = Shared::default();
+        let mut analyzer = Analyzer::new(levels.clone());
+
+        analyzer.analyze(&Chunk {
+            samples: sine(1000.0, 0.5, 4800),
+            channels: 1,
+            sample_rate: 48_000,
+        });
+        analyzer.publish();
+
+        let got: Levels = levels.get();
+        assert!((got.rms() + 9.0).abs() < 0.1, "rms {}", got.rms());
+        assert!((got.peak() + 6.0).abs() < 0.1, "peak {}", got.peak());
+
+        // levels fall without samples.
+        analyzer.decay();
+        analyzer.publish();
+        assert!(levels.get().peak() < got.peak());
+    }
+}
@@ -0,0 +1,59 @@
+use std::f64::consts::PI;
+
+/// Second order IIR filter in direct form I, the state holds only previous
+/// samples, so coefficients can be replaced while playing.
+#[derive(Debug, Clone, Copy)]
+pub struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    /// Previous inputs and outputs.
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    /// Builds filter from coefficients normalized by `a0`.
+    pub fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self {
+            b,
+            a,
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    /// Builds peaking filter boosting or cutting frequencies around `frequency`,
+    /// as in the Audio EQ Cookbook by R. Bristow-Johnson.
+    pub fn peaking(frequency: f64, gain: f64, q: f64, sample_rate: u32) -> Self {
+        let a = 10_f64.powf(gain / 40.0);
+        let w0 = 2.0 * PI * frequency / f64::from(sample_rate);
+        let alpha = w0.sin() / (2.0 * q);
+        let a0 = 1.0 + alpha / a;
+
+        Self::new(
+            [
+                (1.0 + alpha * a) / a0,
+                -2.0 * w0.cos() / a0,
+                (1.0 - alpha * a) / a0,
+            ],
+            [-2.0 * w0.cos() / a0, (1.0 - alpha / a) / a0],
+        )
+    }
+
+    /// Replaces coefficients keeping the state, so the output stays continuous.
+    pub fn update(&mut self, other: &Self) {
+        self.b = other.b;
+        self.a = other.a;
+    }
+
+    pub fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+
+        y
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::VecDeque;
+use std::io::{self, Read};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+use std::{fmt, thread};
+
+use super::super::BufferLevel;
+use super::events::{is_buffer_changed, Announced};
+
+/// Bytes read from the network at once.
+const CHUNK_LEN: usize = 16 * 1024;
+
+/// Reads return at most this, so little of the stream is held by the decoder's read-ahead
+/// (symphonia's `MediaSourceStream` reads blocks of up to 32 KB) where it isn't counted.
+const MAX_READ_LEN: usize = 4 * 1024;
+
+/// Bitrate in kbps assumed until the byte rate of the stream is measured.
+const DEFAULT_BITRATE: u32 = 128;
+
+/// How often waiting for the pre-roll checks if it's cancelled.
+const CANCEL_CHECK_PERIOD: Duration = Duration::from_millis(100);
+
+/// Playback stops to re-buffer once less than this is left,
+/// so reads of the decoder rarely wait for the network.
+const LOW_WATERMARK: Duration = Duration::from_millis(500);
+
+/// How much of the stream is downloaded ahead of playback.
+#[derive(Debug, Clone, Copy)]
+pub struct BufferOptions {
+    /// Downloaded before playback starts and before it resumes after underrun.
+    pub pre_roll: Duration,
+    /// Download is paused once this much is buffered.
+    pub capacity: Duration,
+}
+
+impl Default for BufferOptions {
+    fn default() -> Self {
+        Self {
+            pre_roll: Duration::from_secs(2),
+            capacity: Duration::from_secs(15),
+        }
+    }
+}
+
+/// Reader of the stream downloaded by a background thread into a ring buffer.
+pub struct BufferedReader {
+    ring: Arc<Ring>,
+}
+
+impl BufferedReader {
+    /// Starts downloading the stream. Sizes in bytes are estimated from the typical bitrate
+    /// until the byte rate is measured by [`BufferStatus::set_byte_rate`].
+    /// Noticeable changes of the fill level are announced by `level`.
+    pub fn new<R>(
+        reader: R,
+        options: BufferOptions,
+        level: Announced<Option<BufferLevel>>,
+    ) -> io::Result<Self>
+    where
+        R: Read + Send + 'static,
+    {
+        let sizes = Sizes::new(options, f64::from(DEFAULT_BITRATE) * 1000.0 / 8.0);
+        let ring = Arc::new(Ring {
+            state: Mutex::new(RingState {
+                data: VecDeque::new(),
+                is_buffering: sizes.pre_roll > 0,
+                is_finished: false,
+                is_closed: false,
+                error: None,
+                sizes,
+            }),
+            changed: Condvar::new(),
+            options,
+            level,
+        });
+
+        {
+            let ring = ring.clone();
+
+            thread::Builder::new()
+                .name("tradio-download".to_string())
+                .spawn(move || download(reader, &ring))?;
+        }
+
+        Ok(Self { ring })
+    }
+
+    pub fn status(&self) -> BufferStatus {
+        BufferStatus(self.ring.clone())
+    }
+}
+
+impl Read for BufferedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut state = self.ring.lock();
+
+        while state.data.is_empty() && !state.is_finished {
+            state = self.ring.changed.wait(state).unwrap();
+        }
+
+        if state.data.is_empty() {
+            return match state.error.take() {
+                Some(e) => Err(e),
+                None => Ok(0),
+            };
+        }
+
+        let len = buf.len().min(state.data.len()).min(MAX_READ_LEN);
+
+        for (dst, src) in buf.iter_mut().zip(state.data.drain(..len)) {
+            *dst = src;
+        }
+
+        if !state.is_buffering && !state.is_finished && state.data.len() < state.sizes.low_watermark
+        {
+            log::warn!("stream buffer underrun, re-buffering");
+            state.is_buffering = true;
+        }
+
+        self.ring.changed.notify_all();
+        self.ring.announce(&state);
+
+        Ok(len)
+    }
+}
+
+impl Drop for BufferedReader {
+    fn drop(&mut self) {
+        self.ring.lock().is_closed = true;
+        self.ring.changed.notify_all();
+    }
+}
+
+/// Fill level of the [`BufferedReader`], shared with the player.
+#[derive(Clone)]
+pub struct BufferStatus(Arc<Ring>);
+
+impl BufferStatus {
+    /// Returns `true` while the pre-roll is downloaded, playback should wait for it.
+    pub fn is_buffering(&self) -> bool {
+        self.0.lock().is_buffering
+    }
+
+    pub fn level(&self) -> BufferLevel {
+        self.0.lock().level()
+    }
+
+    /// Sets bytes per second of the stream, measured from the decoded audio.
+    pub fn set_byte_rate(&self, byte_rate: f64) {
+        let mut state = self.0.lock();
+        state.sizes = Sizes::new(self.0.options, byte_rate);
+
+        if state.is_buffering && state.data.len() >= state.sizes.pre_roll {
+            state.is_buffering = false;
+        }
+
+        self.0.changed.notify_all();
+        self.0.announce(&state);
+    }
+
+    /// Blocks until the pre-roll is downloaded or the stream is finished.
+    /// Returns `false` if cancelled or the reader is dropped meanwhile.
+    pub fn wait_ready(&self, is_cancelled: impl Fn() -> bool) -> bool {
+        let mut state = self.0.lock();
+
+        while state.is_buffering && !state.is_finished {
+            if state.is_closed || is_cancelled() {
+                return false;
+            }
+
+            state = self
+                .0
+                .changed
+                .wait_timeout(state, CANCEL_CHECK_PERIOD)
+                .unwrap()
+                .0;
+        }
+
+        true
+    }
+}
+
+impl fmt::Debug for BufferStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BufferStatus").field(&self.level()).finish()
+    }
+}
+
+struct Ring {
+    state: Mutex<RingState>,
+    /// Notified on each change of the state.
+    changed: Condvar,
+    options: BufferOptions,
+    level: Announced<Option<BufferLevel>>,
+}
+
+impl Ring {
+    fn lock(&self) -> MutexGuard<'_, RingState> {
+        self.state.lock().unwrap()
+    }
+
+    fn announce(&self, state: &RingState) {
+        self.level.update(Some(state.level()), |announced, level| {
+            is_buffer_changed(*announced, *level)
+        });
+    }
+}
+
+struct RingState {
+    data: VecDeque<u8>,
+    is_buffering: bool,
+    /// Stream ended or failed, no more data is downloaded.
+    is_finished: bool,
+    /// Reader is dropped, download must stop.
+    is_closed: bool,
+    /// Error of the stream, returned once the buffered data is read.
+    error: Option<io::Error>,
+    sizes: Sizes,
+}
+
+impl RingState {
+    fn level(&self) -> BufferLevel {
+        #[allow(clippy::cast_precision_loss)]
+        let to_duration =
+            |bytes: usize| Duration::from_secs_f64(bytes as f64 / self.sizes.byte_rate);
+
+        BufferLevel {
+            buffered: to_duration(self.data.len()),
+            pre_roll: to_duration(self.sizes.pre_roll),
+            is_buffering: self.is_buffering,
+        }
+    }
+}
+
+/// Buffer options converted to bytes by the byte rate of the stream.
+#[derive(Debug, Clone, Copy)]
+struct Sizes {
+    byte_rate: f64,
+    pre_roll: usize,
+    low_watermark: usize,
+    capacity: usize,
+}
+
+impl Sizes {
+    fn new(options: BufferOptions, byte_rate: f64) -> Self {
+        let to_bytes = |d: Duration| {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let bytes = (d.as_secs_f64() * byte_rate) as usize;
+            bytes
+        };
+
+        let pre_roll = to_bytes(options.pre_roll);
+
+        Self {
+            byte_rate,
+            pre_roll,
+            low_watermark: to_bytes(LOW_WATERMARK).min(pre_roll / 2),
+            capacity: to_bytes(options.capacity).max(pre_roll + 2 * CHUNK_LEN),
+        }
+    }
+}
+
+fn download<R: Read>(mut reader: R, ring: &Ring) {
+    let mut chunk = vec![0; CHUNK_LEN];
+
+    loop {
+        {
+            let mut state = ring.lock();
+
+            while state.data.len() + CHUNK_LEN > state.sizes.capacity && !state.is_closed {
+                state = ring.changed.wait(state).unwrap();
+            }
+
+            if state.is_closed {
+                return;
+            }
+        }
+
+        let result = reader.read(&mut chunk);
+        let mut state = ring.lock();
+
+        match result {
+            Ok(0) => state.is_finished = true,
+            Ok(len) => state.data.extend(&chunk[..len]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                state.error = Some(e);
+                state.is_finished = true;
+            }
+        }
+
+        if state.is_buffering && (state.is_finished || state.data.len() >= state.sizes.pre_roll) {
+            state.is_buffering = false;
+        }
+
+        ring.changed.notify_all();
+        ring.announce(&state);
+
+        if state.is_finished {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+    use std::sync::mpsc::{self, Receiver};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::super::events::Announced;
+    use super::{BufferOptions, BufferedReader};
+
+    /// Reader returning chunks sent by the test, ends once the sender is dropped.
+    struct ChannelReader(Receiver<Vec<u8>>);
+
+    impl Read for ChannelReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Ok(chunk) = self.0.recv() else {
+                return Ok(0);
+            };
+
+            buf[..chunk.len()].copy_from_slice(&chunk);
+
+            Ok(chunk.len())
+        }
+    }
+
+    fn wait_until(condition: impl Fn() -> bool) {
+        let started = Instant::now();
+
+        while !condition() {
+            assert!(started.elapsed() < Duration::from_secs(1), "timed out");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_buffered_reader() {
+        let (tx, rx) = mpsc::channel();
+        let options = BufferOptions {
+            pre_roll: Duration::from_secs(1),
+            capacity: Duration::from_secs(10),
+        };
+
+        // 1000 bytes of pre-roll and 500 of low watermark.
+        let mut reader =
+            BufferedReader::new(ChannelReader(rx), options, Announced::default()).unwrap();
+        let status = reader.status();
+        status.set_byte_rate(1000.0);
+
+        tx.send(vec![1; 600]).unwrap();
+        wait_until(|| status.level().buffered() >= Duration::from_millis(599));
+        assert!(status.is_buffering());
+
+        tx.send(vec![2; 600]).unwrap();
+        assert!(status.wait_ready(|| false));
+        assert!(!status.is_buffering());
+
+        let mut buf = vec![0; 800];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf[599..601], [1, 2]);
+        assert!(status.is_buffering(), "underrun below low watermark");
+
+        drop(tx);
+        wait_until(|| !status.is_buffering());
+
+        let mut rest = vec![];
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, vec![2; 400]);
+    }
+
+    #[test]
+    fn test_wait_ready_cancelled() {
+        let (_tx, rx) = mpsc::channel();
+        let reader = BufferedReader::new(
+            ChannelReader(rx),
+            BufferOptions::default(),
+            Announced::default(),
+        )
+        .unwrap();
+        let status = reader.status();
+
+        assert!(!status.wait_ready(|| true));
+
+        drop(reader);
+        assert!(!status.wait_ready(|| false));
+    }
+}
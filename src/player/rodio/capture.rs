@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use anyhow::Context;
+use rodio::Source;
+
+use crate::http::HttpConfig;
+
+use super::super::recorder::RecordOptions;
+use super::super::State;
+use super::reconnect::{ReconnectPolicy, Reconnecting, StreamOptions};
+use super::virtual_output::{VirtualDevice, VirtualOutput};
+use super::Session;
+
+/// Stream recorded apart from the playback over its own connection, e.g. by a schedule.
+/// Decoded audio is discarded at real-time rate, so the stream is pulled as fast as it's sent.
+/// Recording is finished on drop.
+pub struct Capture {
+    session: Session,
+    output: Arc<Mutex<Option<VirtualOutput>>>,
+}
+
+impl Capture {
+    /// Starts connecting to the stream in background, progress is reported by [`Capture::state`].
+    pub fn start(
+        url: &str,
+        http: HttpConfig,
+        reconnect: ReconnectPolicy,
+        record: RecordOptions,
+        station: &str,
+    ) -> anyhow::Result<Self> {
+        let session = Session::default();
+        session.state.set(State::Connecting);
+
+        let output = Arc::<Mutex<Option<VirtualOutput>>>::default();
+        let options = StreamOptions {
+            http,
+            reconnect,
+            ..StreamOptions::default()
+        };
+
+        {
+            let url = url.to_string();
+            let station = station.to_string();
+            let session = session.clone();
+            let output = output.clone();
+
+            thread::Builder::new()
+                .name("tradio-capture".to_string())
+                .spawn(move || {
+                    if let Err(e) = run(&url, options, &record, &station, &session, &output) {
+                        log::error!("capture {url}: {e:?}");
+                        session.state.set(State::Failed(format!("{e:#}")));
+                    }
+                })
+                .context("spawn capture")?;
+        }
+
+        Ok(Self { session, output })
+    }
+
+    pub fn state(&self) -> State {
+        self.session.state.get()
+    }
+
+    /// Returns path of the file the stream is recorded to, `None` until connected.
+    pub fn path(&self) -> Option<PathBuf> {
+        self.session.recorder.path()
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        self.session.cancel();
+
+        // taken under the lock, so the recording can't be started after it's stopped.
+        let output = self.output.lock().unwrap().take();
+        self.session.recorder.stop();
+        drop(output);
+    }
+}
+
+fn run(
+    url: &str,
+    options: StreamOptions,
+    record: &RecordOptions,
+    station: &str,
+    session: &Session,
+    output: &Mutex<Option<VirtualOutput>>,
+) -> anyhow::Result<()> {
+    let source = Reconnecting::new(url, options, session.clone())?;
+    let mut output = output.lock().unwrap();
+
+    if session.is_cancelled() {
+        return Ok(());
+    }
+
+    // other formats need their headers at the start of the file.
+    let extension = session
+        .extension
+        .get()
+        .context("stream format can't be recorded without re-encoding")?;
+    let title = session.metadata.get().and_then(|m| m.title);
+
+    let path = session
+        .recorder
+        .start(record, station, title.as_deref(), extension)
+        .context("start recording")?;
+    log::info!("capturing {url} to {}", path.display());
+
+    *output = Some(VirtualDevice::Null.start(source.convert_samples())?);
+    session.state.set(State::Playing);
+
+    Ok(())
+}
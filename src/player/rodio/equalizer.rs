@@ -0,0 +1,258 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::Source;
+
+use crate::models::EqGains;
+
+use super::biquad::Biquad;
+use super::mixer::Ramp;
+use super::Shared;
+
+/// Bandwidth of the bands, an octave.
+const Q: f64 = 1.41;
+
+/// Bands this close to the Nyquist frequency are skipped, the filter is unstable there.
+const MAX_FREQUENCY_RATIO: f64 = 0.45;
+
+/// Time the preamp takes to follow changed gains, the level jumps audibly otherwise.
+const PREAMP_FADE: Duration = Duration::from_millis(15);
+
+/// Band gains shared by the player and the playing streams.
+#[derive(Debug, Clone, Default)]
+pub struct EqSettings {
+    gains: Shared<EqGains>,
+    /// Incremented on each change, so streams notice it without locking.
+    version: Arc<AtomicU64>,
+}
+
+impl EqSettings {
+    pub fn gains(&self) -> EqGains {
+        self.gains.get()
+    }
+
+    pub fn set_gains(&self, gains: EqGains) {
+        self.gains.set(gains);
+        self.version.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Source applying graphic equalizer, changes of the gains are picked up on the fly.
+pub struct Equalizer<S> {
+    source: S,
+    settings: EqSettings,
+    /// Version of the settings the filters are built for.
+    version: u64,
+    gains: EqGains,
+    /// Filters of every channel, one per band.
+    filters: Vec<Vec<Biquad>>,
+    /// Attenuation keeping boosted bands from clipping.
+    preamp: Ramp,
+    channels: u16,
+    sample_rate: u32,
+    /// Channel of the next sample in the current frame.
+    channel: u16,
+}
+
+impl<S: Source<Item = i16>> Equalizer<S> {
+    pub fn new(source: S, settings: EqSettings) -> Self {
+        let mut equalizer = Self {
+            channels: source.channels(),
+            sample_rate: source.sample_rate(),
+            source,
+            settings,
+            version: 0,
+            gains: EqGains::default(),
+            filters: vec![],
+            preamp: Ramp::new(1.0),
+            channel: 0,
+        };
+
+        equalizer.rebuild(Duration::ZERO);
+        equalizer
+    }
+
+    /// Builds filters for the current gains and stream spec, the state is kept
+    /// if only gains changed to avoid clicks.
+    fn rebuild(&mut self, fade: Duration) {
+        self.version = self.settings.version.load(Ordering::SeqCst);
+        self.gains = self.settings.gains();
+
+        let max_frequency = f64::from(self.sample_rate) * MAX_FREQUENCY_RATIO;
+
+        let filters: Vec<Biquad> = self
+            .gains
+            .bands()
+            .map(|(frequency, gain)| (f64::from(frequency), f64::from(gain)))
+            .filter(|(frequency, _)| *frequency < max_frequency)
+            .map(|(frequency, gain)| Biquad::peaking(frequency, gain, Q, self.sample_rate))
+            .collect();
+
+        if self.filters.len() == usize::from(self.channels)
+            && self.filters.iter().all(|f| f.len() == filters.len())
+        {
+            for channel in &mut self.filters {
+                for (filter, updated) in channel.iter_mut().zip(&filters) {
+                    filter.update(updated);
+                }
+            }
+        } else {
+            self.filters = vec![filters; usize::from(self.channels)];
+        }
+
+        let max_boost = self.gains.bands().map(|(_, g)| g).max().unwrap_or(0).max(0);
+        let preamp = 10_f32.powf(-f32::from(max_boost) / 20.0);
+
+        self.preamp.set(preamp, self.frames(fade));
+    }
+
+    fn frames(&self, duration: Duration) -> u64 {
+        let frames = duration.as_millis() * u128::from(self.sample_rate) / 1000;
+
+        u64::try_from(frames).unwrap_or(u64::MAX)
+    }
+}
+
+impl<S: Source<Item = i16>> Source for Equalizer<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for Equalizer<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.channel == 0 {
+            let channels = self.source.channels();
+            let sample_rate = self.source.sample_rate();
+
+            if channels != self.channels || sample_rate != self.sample_rate {
+                self.channels = channels;
+                self.sample_rate = sample_rate;
+                self.filters.clear();
+                self.rebuild(PREAMP_FADE);
+            } else if self.settings.version.load(Ordering::Relaxed) != self.version {
+                self.rebuild(PREAMP_FADE);
+            }
+
+            self.preamp.advance();
+        }
+
+        let sample = self.source.next()?;
+        let channel = usize::from(self.channel);
+
+        self.channel = (self.channel + 1) % self.channels.max(1);
+
+        let mut value = f64::from(sample) * f64::from(self.preamp.value());
+
+        // filters run while bypassed too, their state would be stale on a change otherwise.
+        if let Some(filters) = self.filters.get_mut(channel) {
+            for filter in filters {
+                value = filter.process(value);
+            }
+        }
+
+        if self.gains.is_flat() && !self.preamp.is_moving() {
+            return Some(sample);
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let equalized = value
+            .round()
+            .clamp(f64::from(i16::MIN), f64::from(i16::MAX)) as i16;
+
+        Some(equalized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use rodio::buffer::SamplesBuffer;
+
+    use crate::models::EqGains;
+
+    use super::{EqSettings, Equalizer};
+
+    /// Mono sine of the frequency at -6 dBFS, one second long.
+    fn sine(frequency: f64) -> Vec<i16> {
+        (0..48_000)
+            .map(|i| {
+                let t = f64::from(i) / 48_000.0;
+
+                #[allow(clippy::cast_possible_truncation)]
+                let sample = (16_384.0 * (2.0 * PI * frequency * t).sin()) as i16;
+
+                sample
+            })
+            .collect()
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn rms(samples: &[i16]) -> f64 {
+        let sum: f64 = samples.iter().map(|&s| f64::from(s).powi(2)).sum();
+
+        (sum / samples.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn test_equalizer() {
+        let boost = "0,0,0,0,0,6,0,0,0,0".parse::<EqGains>().unwrap();
+        let cut = "0,0,0,0,0,-6,0,0,0,0".parse::<EqGains>().unwrap();
+
+        // (gains, frequency, wanted change of the level in dB).
+        let test_data = [
+            (EqGains::default(), 1000.0, 0.0),
+            (boost, 1000.0, 0.0),
+            (boost, 8000.0, -6.0),
+            (cut, 1000.0, -6.0),
+            (cut, 8000.0, 0.0),
+        ];
+
+        for (gains, frequency, want) in test_data {
+            let settings = EqSettings::default();
+            settings.set_gains(gains);
+
+            let input = sine(frequency);
+            let source = SamplesBuffer::new(1, 48_000, input.clone());
+            let output: Vec<i16> = Equalizer::new(source, settings).collect();
+
+            // filters settle in the first tenth of a second.
+            let got = 20.0 * (rms(&output[4800..]) / rms(&input[4800..])).log10();
+            assert!((got - want).abs() < 0.3, "{gains} at {frequency} Hz: {got}");
+        }
+    }
+
+    #[test]
+    fn test_equalizer_preamp_fade() {
+        let settings = EqSettings::default();
+
+        let source = SamplesBuffer::new(1, 48_000, vec![10_000_i16; 4800]);
+        let mut equalizer = Equalizer::new(source, settings.clone());
+
+        let mut output: Vec<i16> = equalizer.by_ref().take(1000).collect();
+        settings.set_gains("0,0,0,0,0,6,0,0,0,0".parse::<EqGains>().unwrap());
+        output.extend(equalizer);
+
+        // level goes down to -6 dB over 15 ms instead of jumping.
+        let jump = output.windows(2).map(|w| (w[0] - w[1]).abs()).max();
+        assert!(jump < Some(50), "{jump:?}");
+        assert!(output[1000 + 240] > 8000, "{}", output[1000 + 240]);
+        assert_eq!(output.last(), Some(&5012));
+    }
+}
@@ -0,0 +1,197 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+use super::super::{BufferLevel, Event, Metadata, State};
+use super::Shared;
+
+/// Events kept for a slow subscriber, older ones are dropped.
+const CAPACITY: usize = 64;
+
+/// Smaller changes of the buffered stream aren't announced, it changes on each read.
+const BUFFER_STEP: Duration = Duration::from_millis(500);
+
+/// Sender of the player events, they are dropped if nobody is subscribed.
+#[derive(Debug, Clone)]
+pub struct Events(broadcast::Sender<Event>);
+
+impl Events {
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.0.subscribe()
+    }
+
+    pub fn send(&self, event: Event) {
+        log::debug!("player event {event:?}");
+
+        // no subscribers isn't an error.
+        let _ = self.0.send(event);
+    }
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self(broadcast::channel(CAPACITY).0)
+    }
+}
+
+/// Value of the session announced to the player subscribers as soon as it's changed.
+#[derive(Debug, Clone, Default)]
+pub struct Announced<T> {
+    value: Shared<T>,
+    events: Events,
+    /// Only the current session is announced, the replaced one may be finishing its connection.
+    is_current: Arc<AtomicBool>,
+}
+
+impl<T: Announce> Announced<T> {
+    pub fn new(events: Events, is_current: Arc<AtomicBool>) -> Self {
+        Self {
+            value: Shared::default(),
+            events,
+            is_current,
+        }
+    }
+
+    pub fn get(&self) -> T {
+        self.value.get()
+    }
+
+    pub fn set(&self, value: T) {
+        self.update(value, |announced, value| announced != value);
+    }
+
+    /// Replaces the value if `is_changed` from the previous one and announces it.
+    pub fn update(&self, value: T, is_changed: fn(&T, &T) -> bool) {
+        let mut announced = self.value.0.lock().unwrap();
+
+        if !is_changed(&announced, &value) {
+            return;
+        }
+
+        // sent under the lock, so concurrent changes are announced in order.
+        if self.is_current.load(Ordering::SeqCst) {
+            value.announce(&self.events);
+        }
+
+        *announced = value;
+    }
+
+    /// Announces the value if it differs from the one of the replaced session.
+    pub fn announce_replacing(&self, previous: &Self) {
+        let value = self.get();
+
+        if value != previous.get() {
+            value.announce(&self.events);
+        }
+    }
+}
+
+/// Value announced by [`Announced`].
+pub trait Announce: Clone + PartialEq + Default {
+    fn announce(&self, events: &Events);
+}
+
+impl Announce for State {
+    fn announce(&self, events: &Events) {
+        if let Self::Failed(ref e) = self {
+            events.send(Event::Error(e.clone()));
+        }
+
+        events.send(Event::StateChanged(self.clone()));
+    }
+}
+
+impl Announce for Option<Metadata> {
+    fn announce(&self, events: &Events) {
+        events.send(Event::Metadata(self.clone()));
+    }
+}
+
+impl Announce for Option<BufferLevel> {
+    fn announce(&self, events: &Events) {
+        events.send(Event::Buffer(*self));
+    }
+}
+
+/// Reports if the buffer level should be announced, see [`Announced::update`].
+pub fn is_buffer_changed(announced: Option<BufferLevel>, current: Option<BufferLevel>) -> bool {
+    match (announced, current) {
+        (Some(announced), Some(current)) => {
+            announced.is_buffering() != current.is_buffering()
+                || announced.buffered().abs_diff(current.buffered()) >= BUFFER_STEP
+        }
+        (None, None) => false,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::super::super::{BufferLevel, Event, State};
+    use super::{is_buffer_changed, Announced, Events};
+
+    fn level(buffered_ms: u64, is_buffering: bool) -> BufferLevel {
+        BufferLevel {
+            buffered: Duration::from_millis(buffered_ms),
+            pre_roll: Duration::from_secs(2),
+            is_buffering,
+        }
+    }
+
+    #[test]
+    fn test_is_buffer_changed() {
+        // (announced, current, want).
+        let test_data = [
+            (None, None, false),
+            (None, Some(level(0, true)), true),
+            (Some(level(1000, false)), None, true),
+            (Some(level(1000, false)), Some(level(1400, false)), false),
+            (Some(level(1000, false)), Some(level(1500, false)), true),
+            (Some(level(1000, false)), Some(level(600, false)), false),
+            (Some(level(1000, false)), Some(level(1000, true)), true),
+        ];
+
+        for (announced, current, want) in test_data {
+            let got = is_buffer_changed(announced, current);
+            assert_eq!(got, want, "{announced:?} -> {current:?}");
+        }
+    }
+
+    #[test]
+    fn test_announced() {
+        let events = Events::default();
+        let mut rx = events.subscribe();
+        let is_current = Arc::<AtomicBool>::default();
+        let state = Announced::new(events, is_current.clone());
+
+        // session isn't current yet.
+        state.set(State::Connecting);
+        assert!(rx.try_recv().is_err());
+
+        is_current.store(true, Ordering::SeqCst);
+        state.set(State::Playing);
+        state.set(State::Playing);
+        state.set(State::Failed("eof".into()));
+
+        let mut got = vec![];
+
+        while let Ok(event) = rx.try_recv() {
+            got.push(event);
+        }
+
+        assert_eq!(
+            got,
+            [
+                Event::StateChanged(State::Playing),
+                Event::Error("eof".into()),
+                Event::StateChanged(State::Failed("eof".into())),
+            ]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use std::io;
+use std::io::Read;
+
+use super::super::Metadata;
+use super::events::Announced;
+
+/// Reader which strips ICY metadata blocks out of the audio bytes.
+///
+/// Every `metaint` audio bytes the server inserts one length byte followed
+/// by `length * 16` bytes of metadata (`StreamTitle='...';StreamUrl='...';`).
+pub struct IcyReader<R> {
+    inner: R,
+    metaint: usize,
+    remaining: usize,
+    metadata: Announced<Option<Metadata>>,
+}
+
+impl<R: Read> IcyReader<R> {
+    pub fn new(inner: R, metaint: usize, metadata: Announced<Option<Metadata>>) -> Self {
+        Self {
+            inner,
+            metaint,
+            remaining: metaint,
+            metadata,
+        }
+    }
+
+    /// Reads the metadata block, returns `false` if the stream is ended.
+    fn read_metadata(&mut self) -> io::Result<bool> {
+        let mut len = [0; 1];
+
+        if self.inner.read(&mut len)? == 0 {
+            return Ok(false);
+        }
+
+        let mut block = vec![0; usize::from(len[0]) * 16];
+        self.inner.read_exact(&mut block)?;
+
+        if let Some(metadata) = parse(&block) {
+            log::debug!("icy metadata: {metadata:?}");
+            self.metadata.set(Some(metadata));
+        }
+
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for IcyReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            if !self.read_metadata()? {
+                return Ok(0);
+            }
+
+            self.remaining = self.metaint;
+        }
+
+        let len = buf.len().min(self.remaining);
+        let n = self.inner.read(&mut buf[..len])?;
+        self.remaining -= n;
+
+        Ok(n)
+    }
+}
+
+/// Parses metadata block, returns `None` if block is empty or has no known fields.
+/// Empty fields are parsed as `None`, so e.g. the title of the previous song is cleared.
+fn parse(block: &[u8]) -> Option<Metadata> {
+    let end = block.iter().position(|&b| b == 0).unwrap_or(block.len());
+    let text = decode(&block[..end]);
+
+    let mut metadata = Metadata::default();
+    let mut is_known = false;
+    let mut rest = text.as_str();
+
+    while let Some((key, tail)) = rest.split_once("='") {
+        let (value, tail) = tail
+            .split_once("';")
+            .unwrap_or((tail.trim_end_matches('\''), ""));
+        let value = value.trim();
+
+        let field = match key.trim() {
+            "StreamTitle" => Some(&mut metadata.title),
+            "StreamUrl" => Some(&mut metadata.url),
+            _ => None,
+        };
+
+        if let Some(field) = field {
+            *field = Some(value.to_string()).filter(|v| !v.is_empty());
+            is_known = true;
+        }
+
+        rest = tail;
+    }
+
+    is_known.then_some(metadata)
+}
+
+/// Decodes metadata as UTF-8, falling back to Latin-1 used by older servers.
+fn decode(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        Err(_) => bytes.iter().map(|&b| char::from(b)).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use super::{parse, Announced, IcyReader, Metadata};
+
+    #[test]
+    fn test_parse() {
+        let metadata = |title: Option<&str>, url: Option<&str>| Metadata {
+            title: title.map(ToString::to_string),
+            url: url.map(ToString::to_string),
+        };
+
+        let test_data: [(&[u8], Option<Metadata>); 7] = [
+            (b"", None),
+            (b"Unknown='value';", None),
+            (b"StreamTitle='';\0\0\0", Some(metadata(None, None))),
+            (
+                b"StreamTitle='Artist - Title';\0\0",
+                Some(metadata(Some("Artist - Title"), None)),
+            ),
+            (
+                b"StreamTitle='Rock'n'Roll; live';StreamUrl='http://a.b/c';",
+                Some(metadata(Some("Rock'n'Roll; live"), Some("http://a.b/c"))),
+            ),
+            (
+                b"StreamUrl='http://a.b/c';",
+                Some(metadata(None, Some("http://a.b/c"))),
+            ),
+            (
+                b"StreamTitle='Caf\xe9';",
+                Some(metadata(Some("Café"), None)),
+            ),
+        ];
+
+        for (block, want) in test_data {
+            assert_eq!(parse(block), want, "{:?}", String::from_utf8_lossy(block));
+        }
+    }
+
+    #[test]
+    fn test_reader_strips_metadata() {
+        let meta = b"StreamTitle='Song';\0\0\0\0\0\0\0\0\0\0\0\0\0";
+        let mut data = b"abcd".to_vec();
+        data.push(2);
+        data.extend_from_slice(meta);
+        data.extend_from_slice(b"efgh");
+        data.push(0);
+        data.extend_from_slice(b"ij");
+
+        let metadata = Announced::default();
+        let mut reader = IcyReader::new(Cursor::new(data), 4, metadata.clone());
+
+        let mut audio = vec![];
+        reader.read_to_end(&mut audio).unwrap();
+
+        assert_eq!(audio, b"abcdefghij");
+        assert_eq!(metadata.get().unwrap().title(), Some("Song"));
+    }
+}
@@ -0,0 +1,462 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::Source;
+
+use super::biquad::Biquad;
+use super::Shared;
+
+/// Loudness is measured over blocks of four steps, i.e. 400 ms blocks overlapping by 75%.
+const STEP: Duration = Duration::from_millis(100);
+const STEPS_PER_BLOCK: usize = 4;
+
+/// Blocks the loudness is integrated over, the recent ten seconds.
+const WINDOW_BLOCKS: usize = 100;
+
+/// Gain isn't adapted until this many blocks are measured.
+const MIN_BLOCKS: usize = 30;
+
+/// Gates of ITU-R BS.1770, dropping silence and quiet parts from the measurement.
+const ABSOLUTE_GATE: f64 = -70.0;
+const RELATIVE_GATE: f64 = -10.0;
+
+/// Limit of the applied gain in dB.
+const MAX_GAIN: f32 = 12.0;
+
+/// Gain change per step in dB, keeps adaptation inaudible.
+const GAIN_STEP: f32 = 0.1;
+
+/// Part of the distance to the wanted amplitude passed per sample, avoids clicks on gain change.
+const SMOOTHING: f32 = 0.001;
+
+/// Target loudness and switch of the normalization, shared by all streams.
+#[derive(Debug, Clone)]
+pub struct Loudness {
+    /// Target loudness in LUFS.
+    target: f32,
+    is_enabled: Arc<AtomicBool>,
+}
+
+impl Loudness {
+    /// Loudness recommended by `ReplayGain` 2.0.
+    pub const DEFAULT_TARGET: f32 = -18.0;
+
+    pub fn set_target(&mut self, target: f32) {
+        self.target = target;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.is_enabled.load(Ordering::SeqCst)
+    }
+
+    pub fn set_enabled(&self, is_enabled: bool) {
+        self.is_enabled.store(is_enabled, Ordering::SeqCst);
+    }
+}
+
+impl Default for Loudness {
+    fn default() -> Self {
+        Self {
+            target: Self::DEFAULT_TARGET,
+            is_enabled: Arc::default(),
+        }
+    }
+}
+
+/// Source adapting gain of the stream toward the target loudness.
+/// Gain in dB is shared with the player, so it can be preset and remembered per station.
+pub struct Normalizer<S> {
+    source: S,
+    loudness: Loudness,
+    gain: Shared<f32>,
+    meter: Meter,
+    channels: u16,
+    sample_rate: u32,
+    /// Channel of the next sample in the current frame.
+    channel: u16,
+    /// Applied amplitude, moves smoothly to the wanted one.
+    amplitude: f32,
+    wanted_amplitude: f32,
+}
+
+impl<S: Source<Item = i16>> Normalizer<S> {
+    pub fn new(source: S, loudness: Loudness, gain: Shared<f32>) -> Self {
+        let channels = source.channels();
+        let sample_rate = source.sample_rate();
+        let wanted_amplitude = if loudness.is_enabled() {
+            db_to_amplitude(gain.get())
+        } else {
+            1.0
+        };
+
+        Self {
+            source,
+            loudness,
+            gain,
+            meter: Meter::new(channels, sample_rate),
+            channels,
+            sample_rate,
+            channel: 0,
+            amplitude: wanted_amplitude,
+            wanted_amplitude,
+        }
+    }
+
+    /// Moves gain toward the target by the latest measurement, called every step.
+    fn adapt(&mut self) {
+        if !self.loudness.is_enabled() {
+            self.wanted_amplitude = 1.0;
+            return;
+        }
+
+        let mut gain = self.gain.get();
+
+        if let Some(measured) = self.meter.loudness() {
+            #[allow(clippy::cast_possible_truncation)]
+            let wanted = (self.loudness.target - measured as f32).clamp(-MAX_GAIN, MAX_GAIN);
+
+            gain += (wanted - gain).clamp(-GAIN_STEP, GAIN_STEP);
+        }
+
+        // loud transients of quiet streams would be clipped otherwise.
+        if let Some(peak) = self.meter.peak() {
+            gain = gain.min(-peak);
+        }
+
+        self.gain.set(gain);
+
+        self.wanted_amplitude = db_to_amplitude(gain);
+    }
+}
+
+impl<S: Source<Item = i16>> Source for Normalizer<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for Normalizer<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.channel == 0 {
+            let channels = self.source.channels();
+            let sample_rate = self.source.sample_rate();
+
+            // filters depend on the stream spec, measured blocks don't.
+            if channels != self.channels || sample_rate != self.sample_rate {
+                self.meter.reset(channels, sample_rate);
+                self.channels = channels;
+                self.sample_rate = sample_rate;
+            }
+        }
+
+        let sample = self.source.next()?;
+
+        self.meter
+            .push(usize::from(self.channel), f32::from(sample) / 32768.0);
+        self.channel += 1;
+
+        if self.channel >= self.channels {
+            self.channel = 0;
+
+            if self.meter.end_frame() {
+                self.adapt();
+            }
+        }
+
+        self.amplitude += (self.wanted_amplitude - self.amplitude) * SMOOTHING;
+
+        #[allow(clippy::cast_possible_truncation)]
+        let normalized = (f32::from(sample) * self.amplitude)
+            .round()
+            .clamp(f32::from(i16::MIN), f32::from(i16::MAX)) as i16;
+
+        Some(normalized)
+    }
+}
+
+/// Gated loudness of the recent audio per ITU-R BS.1770, all channels weighted equally.
+struct Meter {
+    filters: Vec<KWeighting>,
+    /// Frames in a step.
+    step_len: usize,
+    /// Frames pushed to the current step.
+    frames: usize,
+    /// Sum of the squared weighted samples of the current step.
+    energy: f64,
+    /// Mean square of the recent steps.
+    steps: VecDeque<f64>,
+    /// Mean square of the recent blocks.
+    blocks: VecDeque<f64>,
+    /// Peak of the current step and of the recent ones, as long as the blocks.
+    peak: f32,
+    peaks: VecDeque<f32>,
+}
+
+impl Meter {
+    fn new(channels: u16, sample_rate: u32) -> Self {
+        let mut meter = Self {
+            filters: vec![],
+            step_len: 0,
+            frames: 0,
+            energy: 0.0,
+            steps: VecDeque::with_capacity(STEPS_PER_BLOCK),
+            blocks: VecDeque::with_capacity(WINDOW_BLOCKS),
+            peak: 0.0,
+            peaks: VecDeque::with_capacity(WINDOW_BLOCKS),
+        };
+
+        meter.reset(channels, sample_rate);
+        meter
+    }
+
+    /// Rebuilds filters for the new stream spec, the current step is dropped.
+    fn reset(&mut self, channels: u16, sample_rate: u32) {
+        let step_len = STEP.as_millis() * u128::from(sample_rate) / 1000;
+
+        self.filters = vec![KWeighting::new(sample_rate); usize::from(channels)];
+        self.step_len = usize::try_from(step_len).unwrap_or(usize::MAX).max(1);
+        self.frames = 0;
+        self.energy = 0.0;
+        self.peak = 0.0;
+        self.steps.clear();
+    }
+
+    fn push(&mut self, channel: usize, sample: f32) {
+        self.peak = self.peak.max(sample.abs());
+
+        if let Some(filter) = self.filters.get_mut(channel) {
+            let weighted = filter.process(f64::from(sample));
+            self.energy += weighted * weighted;
+        }
+    }
+
+    /// Finishes the frame, returns `true` if a new block is measured.
+    #[allow(clippy::cast_precision_loss)]
+    fn end_frame(&mut self) -> bool {
+        self.frames += 1;
+
+        if self.frames < self.step_len {
+            return false;
+        }
+
+        if self.steps.len() == STEPS_PER_BLOCK {
+            self.steps.pop_front();
+        }
+
+        self.steps.push_back(self.energy / self.frames as f64);
+        self.frames = 0;
+        self.energy = 0.0;
+
+        if self.peaks.len() == WINDOW_BLOCKS {
+            self.peaks.pop_front();
+        }
+
+        self.peaks.push_back(self.peak);
+        self.peak = 0.0;
+
+        if self.steps.len() < STEPS_PER_BLOCK {
+            return false;
+        }
+
+        if self.blocks.len() == WINDOW_BLOCKS {
+            self.blocks.pop_front();
+        }
+
+        self.blocks
+            .push_back(self.steps.iter().sum::<f64>() / STEPS_PER_BLOCK as f64);
+
+        true
+    }
+
+    /// Returns sample peak of the recent steps in dBFS, `None` if none is measured or it's silent.
+    fn peak(&self) -> Option<f32> {
+        let peak = self.peaks.iter().copied().reduce(f32::max)?;
+
+        (peak > 0.0).then(|| 20.0 * peak.log10())
+    }
+
+    /// Returns loudness in LUFS, `None` if not enough audio is measured or it's silent.
+    fn loudness(&self) -> Option<f64> {
+        if self.blocks.len() < MIN_BLOCKS {
+            return None;
+        }
+
+        let audible = self
+            .blocks
+            .iter()
+            .copied()
+            .filter(|&e| to_lufs(e) > ABSOLUTE_GATE);
+
+        let relative_gate = to_lufs(mean(audible.clone())?) + RELATIVE_GATE;
+
+        mean(audible.filter(|&e| to_lufs(e) > relative_gate)).map(to_lufs)
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0_u32), |(sum, count), v| (sum + v, count + 1));
+
+    (count > 0).then(|| sum / f64::from(count))
+}
+
+fn to_lufs(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.log10()
+}
+
+fn db_to_amplitude(db: f32) -> f32 {
+    10_f32.powf(db / 20.0)
+}
+
+/// Pre-filter of ITU-R BS.1770: high shelf modelling the head, followed by high pass.
+#[derive(Debug, Clone, Copy)]
+struct KWeighting {
+    shelf: Biquad,
+    high_pass: Biquad,
+}
+
+impl KWeighting {
+    fn new(sample_rate: u32) -> Self {
+        let rate = f64::from(sample_rate);
+
+        // coefficients are derived for any rate the same way as libebur128 does.
+        let shelf = {
+            let (f0, gain, q) = (
+                1_681.974_450_955_533,
+                3.999_843_853_973_347,
+                0.707_175_236_955_419_6,
+            );
+            let k = (PI * f0 / rate).tan();
+            let vh = 10_f64.powf(gain / 20.0);
+            let vb = vh.powf(0.499_666_774_154_541_6);
+            let a0 = 1.0 + k / q + k * k;
+
+            Biquad::new(
+                [
+                    (vh + vb * k / q + k * k) / a0,
+                    2.0 * (k * k - vh) / a0,
+                    (vh - vb * k / q + k * k) / a0,
+                ],
+                [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            )
+        };
+
+        let high_pass = {
+            let (f0, q) = (38.135_470_876_024_44, 0.500_327_037_323_877_3);
+            let k = (PI * f0 / rate).tan();
+            let a0 = 1.0 + k / q + k * k;
+
+            Biquad::new(
+                [1.0, -2.0, 1.0],
+                [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            )
+        };
+
+        Self { shelf, high_pass }
+    }
+
+    fn process(&mut self, sample: f64) -> f64 {
+        self.high_pass.process(self.shelf.process(sample))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use rodio::buffer::SamplesBuffer;
+
+    use super::{Loudness, Meter, Normalizer, Shared};
+
+    /// Stereo 1 kHz sine with peak at the given level in dBFS.
+    fn sine(level: f32, secs: u32) -> Vec<i16> {
+        let amplitude = 10_f32.powf(level / 20.0) * f32::from(i16::MAX);
+
+        (0..48_000 * secs)
+            .flat_map(|i| {
+                #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+                let sample = (amplitude * (2.0 * PI * 1000.0 * i as f32 / 48_000.0).sin()) as i16;
+
+                [sample, sample]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_meter() {
+        let test_data = [(-23.0, Some(-23.0)), (-10.0, Some(-10.0)), (-80.0, None)];
+
+        for (level, want) in test_data {
+            let mut meter = Meter::new(2, 48_000);
+
+            for frame in sine(level, 5).chunks(2) {
+                for (channel, &sample) in frame.iter().enumerate() {
+                    meter.push(channel, f32::from(sample) / 32768.0);
+                }
+
+                meter.end_frame();
+            }
+
+            let got = meter.loudness().map(|l| (l * 10.0).round() / 10.0);
+            assert_eq!(got, want, "{level} dBFS");
+        }
+    }
+
+    #[test]
+    fn test_normalizer() {
+        // (enabled, initial gain, wanted gain).
+        let test_data = [(true, 0.0, 5.0), (true, 8.0, 5.0), (false, 3.0, 3.0)];
+
+        for (is_enabled, initial, want) in test_data {
+            let loudness = Loudness::default();
+            loudness.set_enabled(is_enabled);
+
+            let gain = Shared::default();
+            gain.set(initial);
+
+            let source = SamplesBuffer::new(2, 48_000, sine(-23.0, 20));
+            let output: Vec<i16> = Normalizer::new(source, loudness, gain.clone()).collect();
+
+            assert_eq!(output.len(), 48_000 * 2 * 20);
+            assert!((gain.get() - want).abs() < 0.05, "got {}", gain.get());
+        }
+    }
+
+    #[test]
+    fn test_normalizer_peak() {
+        let loudness = Loudness::default();
+        loudness.set_enabled(true);
+
+        // quiet stream with a loud click every 100 ms.
+        let mut data = sine(-30.0, 20);
+        let click = 29_204; // -1 dBFS.
+
+        for frame in data.chunks_mut(9600) {
+            frame[0] = click;
+        }
+
+        let gain = Shared::default();
+        let source = SamplesBuffer::new(2, 48_000, data);
+        let output: Vec<i16> = Normalizer::new(source, loudness, gain.clone()).collect();
+
+        assert!(gain.get() < 1.01, "got {}", gain.get());
+        assert!(output.iter().all(|&s| s < i16::MAX && s > i16::MIN));
+    }
+}
@@ -0,0 +1,345 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::source::UniformSourceIterator;
+use rodio::Source;
+
+/// Output format of the mixer, sources are converted to it.
+const CHANNELS: u16 = 2;
+const SAMPLE_RATE: u32 = 48_000;
+
+/// Fade applied on pause, resume and stop to avoid clicks.
+pub const SHORT_FADE: Duration = Duration::from_millis(150);
+
+type BoxedSource = Box<dyn Source<Item = i16> + Send>;
+
+enum Command {
+    /// Fades in the new source while fading out the playing ones.
+    Play(BoxedSource, Duration),
+    /// Fades out all playing sources.
+    Stop(Duration),
+    Pause,
+    Resume,
+}
+
+/// Controls the [Mixer] from other threads.
+#[derive(Debug)]
+pub struct MixerControl {
+    commands: Sender<Command>,
+    is_paused: Arc<AtomicBool>,
+}
+
+impl MixerControl {
+    /// Starts playing the source with fade in, playing sources are faded out simultaneously.
+    pub fn play<S>(&self, source: S, crossfade: Duration)
+    where
+        S: Source<Item = i16> + Send + 'static,
+    {
+        self.send(Command::Play(Box::new(source), crossfade));
+    }
+
+    /// Fades out and drops all sources.
+    pub fn stop(&self) {
+        self.send(Command::Stop(SHORT_FADE));
+    }
+
+    /// Fades out and stops pulling sources, so they are resumed from the same sample.
+    pub fn pause(&self) {
+        self.is_paused.store(true, Ordering::SeqCst);
+        self.send(Command::Pause);
+    }
+
+    pub fn resume(&self) {
+        self.is_paused.store(false, Ordering::SeqCst);
+        self.send(Command::Resume);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused.load(Ordering::SeqCst)
+    }
+
+    fn send(&self, command: Command) {
+        // mixer is dropped with the output, nothing to control then.
+        let _ = self.commands.send(command);
+    }
+}
+
+/// Never ending source mixing streams of the player, which lets
+/// the stream being switched to overlap the previous one.
+pub struct Mixer {
+    commands: Receiver<Command>,
+
+    tracks: Vec<Track>,
+    /// Gain of the whole output, faded on pause and resume.
+    master: Ramp,
+    is_paused: bool,
+    channels: u16,
+    sample_rate: u32,
+    /// Channel of the next sample in the current frame.
+    channel: u16,
+}
+
+struct Track {
+    source: UniformSourceIterator<BoxedSource, f32>,
+    gain: Ramp,
+    is_fading_out: bool,
+    is_ended: bool,
+}
+
+impl Track {
+    /// Reports if the source is ended or faded out completely.
+    fn is_finished(&self) -> bool {
+        self.is_ended || (self.is_fading_out && self.gain.value() == 0.0)
+    }
+}
+
+impl Mixer {
+    pub fn new() -> (Self, MixerControl) {
+        Self::with_format(CHANNELS, SAMPLE_RATE)
+    }
+
+    fn with_format(channels: u16, sample_rate: u32) -> (Self, MixerControl) {
+        let (tx, rx) = mpsc::channel();
+        let is_paused = Arc::<AtomicBool>::default();
+
+        let mixer = Self {
+            commands: rx,
+            tracks: vec![],
+            master: Ramp::new(1.0),
+            is_paused: false,
+            channels,
+            sample_rate,
+            channel: 0,
+        };
+
+        let control = MixerControl {
+            commands: tx,
+            is_paused,
+        };
+
+        (mixer, control)
+    }
+
+    /// Applies pending commands and moves fades to the next frame.
+    fn start_frame(&mut self) {
+        while let Ok(command) = self.commands.try_recv() {
+            self.apply(command);
+        }
+
+        self.master.advance();
+
+        for track in &mut self.tracks {
+            track.gain.advance();
+        }
+
+        self.tracks.retain(|t| !t.is_finished());
+    }
+
+    fn apply(&mut self, command: Command) {
+        match command {
+            Command::Play(source, fade) => {
+                self.fade_out(fade);
+
+                let mut gain = Ramp::new(0.0);
+                gain.set(1.0, self.frames(fade));
+
+                self.tracks.push(Track {
+                    source: UniformSourceIterator::new(source, self.channels, self.sample_rate),
+                    gain,
+                    is_fading_out: false,
+                    is_ended: false,
+                });
+            }
+            Command::Stop(fade) => self.fade_out(fade),
+            Command::Pause => {
+                self.is_paused = true;
+                self.master.set(0.0, self.frames(SHORT_FADE));
+            }
+            Command::Resume => {
+                self.is_paused = false;
+                self.master.set(1.0, self.frames(SHORT_FADE));
+            }
+        }
+    }
+
+    fn fade_out(&mut self, fade: Duration) {
+        // paused sources aren't pulled, so they can't fade.
+        let frames = if self.is_silent() {
+            0
+        } else {
+            self.frames(fade)
+        };
+
+        for track in &mut self.tracks {
+            track.gain.set(0.0, frames);
+            track.is_fading_out = true;
+        }
+    }
+
+    fn is_silent(&self) -> bool {
+        self.is_paused && self.master.value() == 0.0
+    }
+
+    fn frames(&self, duration: Duration) -> u64 {
+        let frames = duration.as_millis() * u128::from(self.sample_rate) / 1000;
+
+        u64::try_from(frames).unwrap_or(u64::MAX)
+    }
+}
+
+impl Source for Mixer {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Iterator for Mixer {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.channel == 0 {
+            self.start_frame();
+        }
+
+        self.channel = (self.channel + 1) % self.channels;
+
+        if self.is_silent() {
+            return Some(0.0);
+        }
+
+        let mut sum = 0.0;
+
+        for track in &mut self.tracks {
+            match track.source.next() {
+                Some(sample) => sum += sample * track.gain.value(),
+                None => track.is_ended = true,
+            }
+        }
+
+        Some(sum * self.master.value())
+    }
+}
+
+/// Gain moving linearly to the target value.
+#[derive(Debug, Clone, Copy)]
+pub struct Ramp {
+    value: f32,
+    target: f32,
+    step: f32,
+}
+
+impl Ramp {
+    pub fn new(value: f32) -> Self {
+        Self {
+            value,
+            target: value,
+            step: 0.0,
+        }
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Reports if the target is not reached yet, it's set exactly at the end.
+    #[allow(clippy::float_cmp)]
+    pub fn is_moving(&self) -> bool {
+        self.value != self.target
+    }
+
+    /// Starts moving to the target, reaching it after the number of steps.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn set(&mut self, target: f32, steps: u64) {
+        self.target = target;
+
+        if steps == 0 {
+            self.value = target;
+        } else {
+            self.step = (target - self.value).abs() / steps as f32;
+        }
+    }
+
+    pub fn advance(&mut self) {
+        if self.value < self.target {
+            self.value = (self.value + self.step).min(self.target);
+        } else if self.value > self.target {
+            self.value = (self.value - self.step).max(self.target);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rodio::buffer::SamplesBuffer;
+
+    use super::Mixer;
+
+    /// Mono source of the given length and constant value.
+    fn source(value: i16, len: usize) -> SamplesBuffer<i16> {
+        SamplesBuffer::new(1, 1000, vec![value; len])
+    }
+
+    fn take(mixer: &mut Mixer, n: usize) -> Vec<f32> {
+        mixer
+            .by_ref()
+            .take(n)
+            .map(|s| (s * 100.0).round() / 100.0)
+            .collect()
+    }
+
+    #[test]
+    fn test_crossfade() {
+        let (mut mixer, control) = Mixer::with_format(1, 1000);
+        let half = i16::MAX / 2 + 1;
+
+        assert_eq!(take(&mut mixer, 2), [0.0, 0.0]);
+
+        control.play(source(half, 100), Duration::from_millis(4));
+        assert_eq!(take(&mut mixer, 6), [0.13, 0.25, 0.38, 0.5, 0.5, 0.5]);
+
+        // crossfade of equal sources keeps the level.
+        control.play(source(half, 100), Duration::from_millis(4));
+        assert_eq!(take(&mut mixer, 6), [0.5, 0.5, 0.5, 0.5, 0.5, 0.5]);
+        assert!(!mixer.tracks.is_empty());
+
+        control.stop();
+        assert_eq!(take(&mut mixer, 200).last(), Some(&0.0));
+        assert!(mixer.tracks.is_empty());
+    }
+
+    #[test]
+    fn test_pause() {
+        let (mut mixer, control) = Mixer::with_format(1, 1000);
+
+        control.play(source(i16::MAX / 2 + 1, 2000), Duration::ZERO);
+        control.pause();
+        assert!(control.is_paused());
+
+        let paused = take(&mut mixer, 200);
+        assert_eq!(paused.first(), Some(&0.5));
+        assert_eq!(paused.last(), Some(&0.0));
+
+        // source isn't pulled while paused, so it's still playing after the silence.
+        assert_eq!(take(&mut mixer, 1000), [0.0; 1000]);
+
+        control.resume();
+        assert_eq!(take(&mut mixer, 1000).last(), Some(&0.5));
+        assert!(!mixer.tracks.is_empty());
+    }
+}
@@ -1,242 +1,559 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::Sender;
 use std::sync::{Arc, Mutex};
 use std::{fmt, time::Duration};
 
 use anyhow::Context;
-use rodio::cpal::traits::HostTrait;
-use rodio::queue::SourcesQueueOutput;
-use rodio::source::Stoppable;
-use rodio::{cpal, DeviceTrait, OutputStream, Sink, Source};
-
-use super::{Device, Player};
-
+use rodio::Sink;
+use tokio::sync::broadcast;
+
+use crate::http::HttpConfig;
+use crate::models::{EqGains, Header};
+
+use super::recorder::{RecordOptions, Recorder};
+use super::{
+    BufferLevel, Device, Event, Levels, Metadata, OutputConfig, Player, Position, State, StreamInfo,
+};
+
+pub use buffer::BufferOptions;
+pub use capture::Capture;
+pub use reconnect::ReconnectPolicy;
+pub use timeshift::TimeShiftOptions;
+pub use volume::VolumeCurve;
+
+use analyzer::Tap;
+use buffer::BufferStatus;
+use events::{Announced, Events};
+use mixer::{Mixer, MixerControl};
+use output::Output;
+use reconnect::StreamOptions;
+use timeshift::Timeline;
+use volume::Volume;
+use worker::{Command, Effects, Worker};
+
+mod analyzer;
+mod biquad;
+mod buffer;
+mod capture;
+mod equalizer;
+mod events;
+mod icy;
+mod loudness;
+mod mixer;
+mod opus;
+mod output;
+mod reconnect;
 mod source;
-
-#[derive(Debug, Default)]
-struct Controls {
-    stop: AtomicBool,
+mod tee;
+mod timeshift;
+mod virtual_output;
+mod volume;
+mod worker;
+
+/// Stream downloaded ahead by [`probe`], only the first packet is decoded.
+const PROBE_BUFFERING: BufferOptions = BufferOptions {
+    pre_roll: Duration::ZERO,
+    capacity: Duration::from_secs(1),
+};
+
+/// Opens the stream and decodes its first packet to find its format, it isn't played.
+/// Blocks until done, like the stream requests it must not be called within the async runtime.
+pub fn probe(url: &str, http: &HttpConfig) -> anyhow::Result<StreamInfo> {
+    let source = source::Symphonia::from_http(url, http, PROBE_BUFFERING, &Session::default())?;
+
+    Ok(source.info())
 }
 
-#[derive(Default)]
-struct ActiveOutput {
-    device: Option<Device>,
-    stream: Option<OutputStream>,
-}
-
-impl fmt::Debug for ActiveOutput {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("ActiveOutput")
-            .field("device", &self.device)
-            .field("with_stream", &self.stream.is_some())
-            .finish()
-    }
-}
-
-// Safety: we guarantee that `OutputStream` cannot be `Send` on Android's AAudio API.
-#[cfg(not(target_os = "android"))]
-unsafe impl Send for ActiveOutput {}
-
 pub struct Rodio {
-    sink: Sink,
-    queue_rx: SharedSourcesQueue,
-
-    controls: Arc<Controls>,
-    active_out: Mutex<ActiveOutput>,
+    sink: Arc<Sink>,
+
+    mixer: Arc<MixerControl>,
+    output: Arc<Output>,
+    session: Arc<Mutex<Session>>,
+    /// Number of the streams started by `play`.
+    plays: AtomicU64,
+    events: Events,
+    stream: StreamOptions,
+    record: Option<RecordOptions>,
+    time_shift: Option<TimeShiftOptions>,
+    effects: Effects,
+    levels: Shared<Levels>,
+    volume: Mutex<Volume>,
+
+    commands: Sender<Command>,
 }
 
 impl Rodio {
-    const ACCESS_PERIOD: Duration = Duration::from_millis(15);
-
     /// Builds new `RodioPlayer` without output stream.
     pub fn new_idle() -> Self {
         let (sink, queue_rx) = Sink::new_idle();
-        let queue_rx = SharedSourcesQueue::from(queue_rx);
+        let sink = Arc::new(sink);
+        let events = Events::default();
+        let output = Arc::new(Output::new(queue_rx, events.clone()));
+        output.watch();
+
+        let session = Arc::new(Mutex::new(Session::new(&events)));
+
+        let (mixer, control) = Mixer::new();
+        let mixer_control = Arc::new(control);
+        let levels = Shared::<Levels>::default();
+        sink.append(Tap::new(mixer, levels.clone()));
+
+        let commands = Worker::new(mixer_control.clone(), session.clone()).spawn();
 
         Self {
             sink,
-            queue_rx,
-            controls: Arc::default(),
-            active_out: Mutex::default(),
+            mixer: mixer_control,
+            output,
+            session,
+            plays: AtomicU64::default(),
+            events,
+            stream: StreamOptions::default(),
+            record: None,
+            time_shift: None,
+            effects: Effects::default(),
+            levels,
+            volume: Mutex::default(),
+            commands,
         }
     }
 
-    /// Builds new `RodioPlayer` beginning playback on a default output stream.
+    /// Sets how interrupted streams are re-opened.
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.stream.reconnect = policy;
+
+        self
+    }
+
+    /// Sets how much of the streams is downloaded ahead of playback.
+    pub fn with_buffering(mut self, options: BufferOptions) -> Self {
+        self.stream.buffering = options;
+
+        self
+    }
+
+    /// Sets proxy, headers, timeouts and certificates of the stream requests.
+    pub fn with_http(mut self, config: HttpConfig) -> Self {
+        self.stream.http = config;
+
+        self
+    }
+
+    /// Enables recording of the played streams.
+    pub fn with_recording(mut self, options: RecordOptions) -> Self {
+        self.record = Some(options);
+
+        self
+    }
+
+    /// Keeps downloading paused streams, so playback is resumed from the pause point.
+    pub fn with_time_shift(mut self, options: TimeShiftOptions) -> Self {
+        self.time_shift = Some(options);
+
+        self
+    }
+
+    /// Overlaps the previous stream with the next one for the given time on switch.
+    pub fn with_crossfade(mut self, crossfade: Duration) -> Self {
+        self.effects.crossfade = crossfade;
+
+        self
+    }
+
+    /// Sets loudness in LUFS the streams are normalized to.
+    pub fn with_loudness_target(mut self, target: f32) -> Self {
+        self.effects.loudness.set_target(target);
+
+        self
+    }
+
+    /// Moves output back to the device chosen by the user once it reappears,
+    /// after playback fell back to the default device.
+    pub fn with_return_to_device(self, value: bool) -> Self {
+        self.output.set_return_to_preferred(value);
+
+        self
+    }
+
+    /// Lists virtual device writing played audio to the WAV file.
+    pub fn with_wav_output(self, path: PathBuf) -> Self {
+        self.output.set_wav_path(path);
+
+        self
+    }
+
+    /// Sets how volume percentage maps to the output amplitude.
+    pub fn with_volume_curve(self, curve: VolumeCurve) -> Self {
+        self.update_volume(|v| v.curve = curve);
+
+        self
+    }
+
+    /// Changes volume settings and applies them to the output.
+    fn update_volume(&self, update: impl FnOnce(&mut Volume)) {
+        let mut volume = self.volume.lock().unwrap();
+        let previous = (volume.level, volume.is_muted);
+
+        update(&mut volume);
+        self.sink.set_volume(volume.amplitude());
+
+        if (volume.level, volume.is_muted) != previous {
+            self.events.send(Event::Volume {
+                volume: volume.level,
+                is_muted: volume.is_muted,
+            });
+        }
+    }
+
+    /// Pauses or resumes the output and announces the change.
+    fn set_paused(&self, is_paused: bool) {
+        if self.mixer.is_paused() == is_paused {
+            return;
+        }
+
+        if is_paused {
+            self.mixer.pause();
+        } else {
+            self.mixer.resume();
+        }
+
+        self.events.send(Event::Paused(is_paused));
+    }
+
+    /// Replaces current session, connection of the previous stream is cancelled
+    /// and its recording is finished.
+    fn replace_session(&self, session: Session) {
+        Session::replace(&self.session, session).recorder.stop();
+    }
+
+    /// Builds new `RodioPlayer` beginning playback on a default output stream,
+    /// or on the null device if the system has none.
     pub fn default() -> anyhow::Result<Self> {
         let player = Self::new_idle();
-        let device = player
-            .devices()?
-            .into_iter()
-            .find(|d| d.is_default)
-            .context("can't find default device")?;
+        let devices = player.devices()?;
+
+        let device = if let Some(device) = devices.iter().find(|d| d.is_default) {
+            device
+        } else {
+            log::warn!("can't find default device, using null output");
 
-        player.use_device(&device)?;
+            devices
+                .iter()
+                .find(|d| d.is_virtual)
+                .context("can't find null device")?
+        };
+
+        player.use_device(device)?;
 
         Ok(player)
     }
 }
 
 impl Player for Rodio {
-    fn play(&self, track_url: &str) -> anyhow::Result<()> {
-        let source = source::Symphonia::from_http(track_url)?;
-
-        let controls = self.controls.clone();
+    fn play(&self, track_url: &str, headers: &[Header], gain: f32) -> anyhow::Result<()> {
+        let timeline = match self.time_shift {
+            Some(ref options) => Some(Timeline::new(options).context("time-shift buffer")?),
+            None => None,
+        };
 
-        let access = move |src: &mut Stoppable<_>| {
-            if controls.stop.load(Ordering::SeqCst) {
-                src.stop();
-                controls.stop.store(false, Ordering::SeqCst);
-            }
+        let session = Session {
+            timeline,
+            ..Session::new(&self.events)
         };
+        session.state.set(State::Connecting);
+        session.gain.set(gain);
 
-        let source = source
-            .stoppable()
-            .periodic_access(Self::ACCESS_PERIOD, access);
+        self.replace_session(session.clone());
+        self.plays.fetch_add(1, Ordering::Relaxed);
 
-        // clean source queue.
-        while self.sink.len() > 0 {
-            self.controls.stop.store(true, Ordering::SeqCst);
-            self.sink.sleep_until_end();
+        // paused stream can't fade out, so it's dropped at once.
+        if self.mixer.is_paused() {
+            self.mixer.stop();
+            self.set_paused(false);
         }
 
-        self.controls.stop.store(false, Ordering::SeqCst);
-
-        self.sink.append(source);
-
-        Ok(())
-    }
-
-    fn wait_end(&self) {
-        self.sink.sleep_until_end();
+        self.commands
+            .send(Command::Play {
+                url: track_url.to_string(),
+                session,
+                options: StreamOptions {
+                    http: self.stream.http.with_headers(headers),
+                    ..self.stream.clone()
+                },
+                effects: self.effects.clone(),
+            })
+            .context("playback worker is stopped")
     }
 
     fn stop(&self) {
-        self.controls.stop.store(true, Ordering::SeqCst);
+        // stream started before the replacement is stopped, one connecting is cancelled.
+        self.replace_session(Session::new(&self.events));
+        self.mixer.stop();
+
+        // paused stream is dropped at once, it would keep downloading otherwise.
+        self.set_paused(false);
     }
 
     fn pause(&self) {
-        self.sink.pause();
+        self.set_paused(true);
     }
 
     fn resume(&self) {
-        self.sink.play();
+        self.set_paused(false);
     }
 
     fn is_paused(&self) -> bool {
-        self.sink.is_paused()
+        self.mixer.is_paused()
     }
 
-    #[allow(clippy::cast_possible_truncation)]
     fn volume(&self) -> i8 {
-        (self.sink.volume() * 100.0).round() as i8
+        self.volume.lock().unwrap().level
     }
 
     fn set_volume(&self, volume: i8) {
-        self.sink
-            .set_volume(f32::from(volume.clamp(0, 100)) / 100.0);
+        self.update_volume(|v| v.level = volume.clamp(0, 100));
     }
 
-    fn devices(&self) -> anyhow::Result<Vec<Device>> {
-        let host = cpal::default_host();
-        let devices = host.output_devices().context("output devices")?;
-        let default_device = host.default_output_device();
-        let active_out = self.active_out.lock().unwrap();
-
-        let mut result = vec![];
-
-        for device in devices {
-            let id = device.name()?;
-            let is_active = active_out.device.as_ref().map(Device::id) == Some(&id);
-
-            let is_default = if let Some(ref default) = default_device {
-                id == default.name()?
-            } else {
-                false
-            };
-
-            result.push(Device {
-                id,
-                is_active,
-                is_default,
-            });
-        }
+    fn is_muted(&self) -> bool {
+        self.volume.lock().unwrap().is_muted
+    }
+
+    fn set_muted(&self, is_muted: bool) {
+        self.update_volume(|v| v.is_muted = is_muted);
+    }
 
-        Ok(result)
+    fn devices(&self) -> anyhow::Result<Vec<Device>> {
+        Ok(self.output.devices())
     }
 
     fn use_device(&self, device: &Device) -> anyhow::Result<()> {
-        let mut active_out = self.active_out.lock().unwrap();
+        self.output.use_device(device)
+    }
+
+    fn active_device(&self) -> Option<Device> {
+        self.output.active_device()
+    }
+
+    fn set_output_config(
+        &self,
+        device_id: &str,
+        config: Option<OutputConfig>,
+    ) -> anyhow::Result<()> {
+        self.output.set_config(device_id, config)
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.session.lock().unwrap().metadata.get()
+    }
+
+    fn state(&self) -> State {
+        self.session.lock().unwrap().state.get()
+    }
+
+    fn plays(&self) -> u64 {
+        self.plays.load(Ordering::Relaxed)
+    }
+
+    fn codecs(&self) -> Vec<String> {
+        source::supported_codecs()
+    }
+
+    fn start_recording(&self, station: &str) -> anyhow::Result<PathBuf> {
+        let options = self.record.as_ref().context("recording is disabled")?;
+        let session = self.session.lock().unwrap();
+
+        anyhow::ensure!(
+            session.state.get() == State::Playing,
+            "stream isn't playing"
+        );
+
+        // other formats need their headers at the start of the file.
+        let extension = session
+            .extension
+            .get()
+            .context("stream format can't be recorded without re-encoding")?;
+        let title = session.metadata.get().and_then(|m| m.title);
+
+        session
+            .recorder
+            .start(options, station, title.as_deref(), extension)
+            .context("start recording")
+    }
+
+    fn stop_recording(&self) {
+        self.session.lock().unwrap().recorder.stop();
+    }
 
-        if Some(device) == active_out.device.as_ref() {
-            return Ok(());
+    fn recording(&self) -> Option<PathBuf> {
+        self.session.lock().unwrap().recorder.path()
+    }
+
+    fn position(&self) -> Option<Position> {
+        let session = self.session.lock().unwrap();
+
+        session.timeline.as_ref().map(Timeline::position)
+    }
+
+    fn rewind(&self, by: Duration) {
+        if let Some(ref timeline) = self.session.lock().unwrap().timeline {
+            timeline.rewind(by);
         }
+    }
 
-        let mut devices = cpal::default_host().devices()?;
+    fn fast_forward(&self, by: Duration) {
+        if let Some(ref timeline) = self.session.lock().unwrap().timeline {
+            timeline.fast_forward(by);
+        }
+    }
 
-        let rodio_device = loop {
-            if let Some(target) = devices.next() {
-                if target.name()? == device.id {
-                    break target;
-                }
-            } else {
-                return Err(anyhow::Error::msg("device not found"));
-            }
-        };
+    fn jump_to_live(&self) {
+        if let Some(ref timeline) = self.session.lock().unwrap().timeline {
+            timeline.jump_to_live();
+        }
+    }
 
-        let (stream, handler) = OutputStream::try_from_device(&rodio_device)?;
+    fn is_normalizing(&self) -> bool {
+        self.effects.loudness.is_enabled()
+    }
 
-        active_out.stream = Some(stream);
-        active_out.device = Some(device.clone());
+    fn set_normalizing(&self, is_enabled: bool) {
+        self.effects.loudness.set_enabled(is_enabled);
+    }
 
-        handler.play_raw(self.queue_rx.clone())?;
+    fn gain(&self) -> f32 {
+        self.session.lock().unwrap().gain.get()
+    }
 
-        Ok(())
+    fn equalizer(&self) -> EqGains {
+        self.effects.equalizer.gains()
     }
 
-    fn active_device(&self) -> Option<Device> {
-        self.active_out.lock().unwrap().device.clone()
+    fn set_equalizer(&self, gains: EqGains) {
+        self.effects.equalizer.set_gains(gains);
+    }
+
+    fn levels(&self) -> Levels {
+        self.levels.get()
+    }
+
+    fn buffer(&self) -> Option<BufferLevel> {
+        let buffer = self.session.lock().unwrap().buffer.get();
+
+        buffer.as_ref().map(BufferStatus::level)
     }
 }
 
 impl fmt::Debug for Rodio {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RodioPlayer")
-            .field("controls", &self.controls)
-            .field("active_out", &self.active_out)
+            .field("mixer", &self.mixer)
+            .field("output", &self.output)
+            .field("session", &self.session)
+            .field("stream", &self.stream)
+            .field("record", &self.record)
+            .field("time_shift", &self.time_shift)
+            .field("effects", &self.effects)
+            .field("volume", &self.volume)
             .finish_non_exhaustive()
     }
 }
 
-#[derive(Clone)]
-struct SharedSourcesQueue(Arc<Mutex<SourcesQueueOutput<f32>>>);
+/// Value shared between the player and the audio thread.
+#[derive(Debug, Default)]
+struct Shared<T>(Arc<Mutex<T>>);
 
-impl Source for SharedSourcesQueue {
-    fn current_frame_len(&self) -> Option<usize> {
-        self.0.lock().unwrap().current_frame_len()
+impl<T: Clone> Shared<T> {
+    fn get(&self) -> T {
+        self.0.lock().unwrap().clone()
     }
 
-    fn channels(&self) -> u16 {
-        self.0.lock().unwrap().channels()
+    fn set(&self, value: T) {
+        *self.0.lock().unwrap() = value;
     }
+}
 
-    fn sample_rate(&self) -> u32 {
-        self.0.lock().unwrap().sample_rate()
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
     }
+}
 
-    fn total_duration(&self) -> Option<Duration> {
-        self.0.lock().unwrap().total_duration()
-    }
+/// State of the stream started by a single [`Player::play`] call.
+#[derive(Debug, Clone)]
+struct Session {
+    metadata: Announced<Option<Metadata>>,
+    state: Announced<State>,
+    /// File extension of the raw stream if it can be recorded as is, known once connected.
+    extension: Shared<Option<&'static str>>,
+    recorder: Recorder,
+    /// Buffer of the decoded stream, `None` if time-shift is disabled.
+    timeline: Option<Timeline>,
+    /// Loudness normalization gain in dB.
+    gain: Shared<f32>,
+    /// Buffer of the connected stream, replaced on reconnect.
+    buffer: Shared<Option<BufferStatus>>,
+    /// Level of the buffer announced last.
+    buffer_level: Announced<Option<BufferLevel>>,
+    /// Changes are announced while the session is the player's current one.
+    is_current: Arc<AtomicBool>,
+    /// Set once the session is replaced, its pending connection is abandoned then.
+    cancelled: Arc<AtomicBool>,
 }
 
-impl Iterator for SharedSourcesQueue {
-    type Item = f32;
+impl Session {
+    /// Builds session announcing its changes by the events once it's made current.
+    fn new(events: &Events) -> Self {
+        let is_current = Arc::<AtomicBool>::default();
+
+        Self {
+            metadata: Announced::new(events.clone(), is_current.clone()),
+            state: Announced::new(events.clone(), is_current.clone()),
+            extension: Shared::default(),
+            recorder: Recorder::default(),
+            timeline: None,
+            gain: Shared::default(),
+            buffer: Shared::default(),
+            buffer_level: Announced::new(events.clone(), is_current.clone()),
+            is_current,
+            cancelled: Arc::default(),
+        }
+    }
+
+    /// Makes the session current and returns the cancelled previous one.
+    /// Cancellation happens under the lock the worker starts streams under.
+    fn replace(current: &Mutex<Self>, session: Self) -> Self {
+        let mut current = current.lock().unwrap();
+        current.is_current.store(false, Ordering::SeqCst);
+        current.cancel();
+
+        // new session isn't connected yet, so only its initial values are announced.
+        session.state.announce_replacing(&current.state);
+        session.metadata.announce_replacing(&current.metadata);
+        session
+            .buffer_level
+            .announce_replacing(&current.buffer_level);
+        session.is_current.store(true, Ordering::SeqCst);
+
+        std::mem::replace(&mut *current, session)
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.0.lock().unwrap().next()
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
     }
 }
 
-impl From<SourcesQueueOutput<f32>> for SharedSourcesQueue {
-    fn from(value: SourcesQueueOutput<f32>) -> Self {
-        Self(Arc::new(Mutex::new(value)))
+impl Default for Session {
+    /// Builds session which changes aren't announced, e.g. of a probe.
+    fn default() -> Self {
+        Self::new(&Events::default())
     }
 }
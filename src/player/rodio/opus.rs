@@ -0,0 +1,92 @@
+use opus_decoder::OpusDecoder;
+use symphonia::core::audio::{AsAudioBufferRef, AudioBuffer, AudioBufferRef, Signal, SignalSpec};
+use symphonia::core::codecs::{
+    CodecDescriptor, CodecParameters, Decoder, DecoderOptions, FinalizeResult, CODEC_TYPE_OPUS,
+};
+use symphonia::core::errors::{decode_error, unsupported_error, Result};
+use symphonia::core::formats::Packet;
+use symphonia::core::support_codec;
+
+/// Opus always decodes at 48 kHz, regardless of the input sample rate.
+const SAMPLE_RATE: u32 = 48_000;
+
+/// Symphonia adapter for the pure Rust Opus decoder, symphonia 0.5 can
+/// demux Ogg Opus but has no decoder for it.
+pub struct Opus {
+    params: CodecParameters,
+    decoder: OpusDecoder,
+    channels: usize,
+    pcm: Vec<f32>,
+    buffer: AudioBuffer<f32>,
+}
+
+impl Decoder for Opus {
+    fn try_new(params: &CodecParameters, _options: &DecoderOptions) -> Result<Self> {
+        let Some(layout) = params.channels else {
+            return unsupported_error("opus: channels are unknown");
+        };
+
+        let channels = layout.count();
+
+        if channels > 2 {
+            return unsupported_error("opus: multistream is not supported");
+        }
+
+        let Ok(decoder) = OpusDecoder::new(SAMPLE_RATE, channels) else {
+            return unsupported_error("opus: invalid decoder parameters");
+        };
+
+        let frames = OpusDecoder::MAX_FRAME_SIZE_48K;
+        let spec = SignalSpec::new(SAMPLE_RATE, layout);
+
+        Ok(Self {
+            params: params.clone(),
+            decoder,
+            channels,
+            pcm: vec![0.0; frames * channels],
+            buffer: AudioBuffer::new(frames as u64, spec),
+        })
+    }
+
+    fn supported_codecs() -> &'static [CodecDescriptor] {
+        &[support_codec!(CODEC_TYPE_OPUS, "opus", "Opus")]
+    }
+
+    fn reset(&mut self) {
+        self.decoder.reset();
+    }
+
+    fn codec_params(&self) -> &CodecParameters {
+        &self.params
+    }
+
+    fn decode(&mut self, packet: &Packet) -> Result<AudioBufferRef<'_>> {
+        let Ok(frames) = self
+            .decoder
+            .decode_float(&packet.data, &mut self.pcm, false)
+        else {
+            return decode_error("opus: invalid packet");
+        };
+
+        self.buffer.clear();
+        self.buffer.render_reserved(Some(frames));
+
+        for ch in 0..self.channels {
+            let samples = self.pcm.iter().skip(ch).step_by(self.channels);
+
+            for (dst, src) in self.buffer.chan_mut(ch).iter_mut().zip(samples) {
+                *dst = *src;
+            }
+        }
+
+        Ok(self.buffer.as_audio_buffer_ref())
+    }
+
+    fn finalize(&mut self) -> FinalizeResult {
+        FinalizeResult::default()
+    }
+
+    fn last_decoded(&self) -> AudioBufferRef<'_> {
+        self.buffer.as_audio_buffer_ref()
+    }
+}
@@ -0,0 +1,852 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Context;
+use rodio::cpal::traits::{HostTrait, StreamTrait};
+use rodio::cpal::{FromSample, SampleFormat, SizedSample, SupportedBufferSize};
+use rodio::queue::SourcesQueueOutput;
+use rodio::source::UniformSourceIterator;
+use rodio::{cpal, DeviceTrait, Source};
+
+use super::super::{Device, DeviceChange, Event, OutputConfig, SupportedConfig};
+use super::events::Events;
+use super::virtual_output::{VirtualDevice, VirtualOutput};
+
+/// How often the device list is checked for plugged and unplugged devices.
+const WATCH_PERIOD: Duration = Duration::from_secs(2);
+
+/// Output stream of the player, moved between devices as they come and go.
+pub struct Output {
+    host: Box<dyn Host>,
+    active: Mutex<ActiveOutput>,
+    queue: SharedSourcesQueue,
+    /// Device chosen by the user.
+    preferred: Mutex<Option<Device>>,
+    /// Output is moved back to the preferred device once it reappears.
+    return_to_preferred: AtomicBool,
+    /// File of the WAV virtual device, it isn't listed if not set.
+    wav_path: Mutex<Option<PathBuf>>,
+    /// Formats chosen for the devices by their ids.
+    configs: Mutex<HashMap<String, OutputConfig>>,
+    /// Changes of the active device are announced to the player subscribers.
+    events: Events,
+}
+
+#[derive(Default)]
+struct ActiveOutput {
+    device: Option<Device>,
+    stream: Option<Stream>,
+}
+
+// streams aren't read, they are kept to play until dropped.
+#[allow(dead_code)]
+enum Stream {
+    Hardware(Box<dyn StreamTrait>),
+    Virtual(VirtualOutput),
+}
+
+/// Stream about to replace the active one.
+enum Pending {
+    Hardware(Box<dyn StreamTrait>),
+    Virtual(VirtualDevice),
+}
+
+impl Pending {
+    fn play(self, queue: SharedSourcesQueue) -> anyhow::Result<Stream> {
+        let stream = match self {
+            Self::Virtual(target) => Stream::Virtual(target.start(queue)?),
+            Self::Hardware(stream) => {
+                stream.play()?;
+                Stream::Hardware(stream)
+            }
+        };
+
+        Ok(stream)
+    }
+}
+
+/// Sound hardware the output is played on.
+trait Host: Send + Sync {
+    /// Lists output devices, their supported formats are queried if `with_configs`.
+    /// Devices are returned inactive and without the chosen format.
+    fn devices(&self, with_configs: bool) -> anyhow::Result<Vec<Device>>;
+
+    /// Builds stream of the device pulling the source, it's paused until played.
+    fn build_stream(
+        &self,
+        device_id: &str,
+        config: Option<OutputConfig>,
+        source: SharedSourcesQueue,
+    ) -> anyhow::Result<Box<dyn StreamTrait>>;
+}
+
+impl fmt::Debug for ActiveOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ActiveOutput")
+            .field("device", &self.device)
+            .field("with_stream", &self.stream.is_some())
+            .finish()
+    }
+}
+
+// Safety: we guarantee that `cpal::Stream` cannot be `Send` on Android's AAudio API.
+#[cfg(not(target_os = "android"))]
+unsafe impl Send for ActiveOutput {}
+
+impl Output {
+    pub fn new(queue: SourcesQueueOutput<f32>, events: Events) -> Self {
+        Self::with_host(Box::new(CpalHost), queue, events)
+    }
+
+    fn with_host(host: Box<dyn Host>, queue: SourcesQueueOutput<f32>, events: Events) -> Self {
+        Self {
+            host,
+            active: Mutex::default(),
+            queue: SharedSourcesQueue::from(queue),
+            preferred: Mutex::default(),
+            return_to_preferred: AtomicBool::default(),
+            wav_path: Mutex::default(),
+            configs: Mutex::default(),
+            events,
+        }
+    }
+
+    /// Starts thread moving output to the default device when the active one vanishes,
+    /// it stops when the output is dropped.
+    pub fn watch(self: &Arc<Self>) {
+        let output = Arc::downgrade(self);
+
+        thread::Builder::new()
+            .name("tradio-devices".to_string())
+            .spawn(move || watch(&output))
+            .expect("can't spawn device watcher");
+    }
+
+    pub fn set_return_to_preferred(&self, value: bool) {
+        self.return_to_preferred.store(value, Ordering::SeqCst);
+    }
+
+    pub fn set_wav_path(&self, path: PathBuf) {
+        *self.wav_path.lock().unwrap() = Some(path);
+    }
+
+    /// Returns all output devices available to the system, followed by virtual ones.
+    /// Virtual devices are listed even if the sound hardware can't be queried.
+    pub fn devices(&self) -> Vec<Device> {
+        let active = self.active_device();
+        let hardware = self
+            .hardware_devices(active.as_ref(), true)
+            .unwrap_or_else(|e| {
+                log::warn!("list hardware devices: {e:?}");
+                vec![]
+            });
+
+        self.append_virtual(hardware, active.as_ref())
+    }
+
+    /// Lists devices of the sound hardware, their supported formats are queried if `with_configs`.
+    /// Querying them opens the devices on some hosts, e.g. ALSA, so it's skipped by the watcher.
+    fn hardware_devices(
+        &self,
+        active: Option<&Device>,
+        with_configs: bool,
+    ) -> anyhow::Result<Vec<Device>> {
+        let configs = self.configs.lock().unwrap().clone();
+        let mut devices = self.host.devices(with_configs)?;
+
+        for device in &mut devices {
+            device.is_active = active.map(Device::id) == Some(&device.id);
+            device.config = configs.get(&device.id).copied();
+        }
+
+        Ok(devices)
+    }
+
+    fn append_virtual(&self, mut devices: Vec<Device>, active: Option<&Device>) -> Vec<Device> {
+        for device in self.virtual_devices() {
+            let id = device.name();
+
+            devices.push(Device {
+                is_active: active.map(Device::id) == Some(&id),
+                id,
+                is_default: false,
+                is_virtual: true,
+                configs: vec![],
+                config: None,
+            });
+        }
+
+        devices
+    }
+
+    fn virtual_devices(&self) -> Vec<VirtualDevice> {
+        let mut devices = vec![VirtualDevice::Null];
+        devices.extend(
+            self.wav_path
+                .lock()
+                .unwrap()
+                .clone()
+                .map(VirtualDevice::Wav),
+        );
+
+        devices
+    }
+
+    /// Starts playing on the device chosen by the user.
+    pub fn use_device(&self, device: &Device) -> anyhow::Result<()> {
+        *self.preferred.lock().unwrap() = Some(device.clone());
+
+        self.open(device)?;
+
+        if let Some(active) = self.active_device() {
+            self.notify(DeviceChange::Chosen(active));
+        }
+
+        Ok(())
+    }
+
+    pub fn active_device(&self) -> Option<Device> {
+        self.active.lock().unwrap().device.clone()
+    }
+
+    /// Sets format of the device, it's restarted if active.
+    /// Device may be absent, the format is applied once it's plugged in.
+    pub fn set_config(&self, device_id: &str, config: Option<OutputConfig>) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            !self.virtual_devices().iter().any(|d| d.name() == device_id),
+            "format of virtual device can't be changed"
+        );
+
+        let previous = self.store_config(device_id, config);
+        let mut active = self.active.lock().unwrap();
+
+        if let Some(device) = active.device.clone().filter(|d| d.id == device_id) {
+            if let Err(e) = self.start(&mut active, &device) {
+                // failed format isn't applied later either.
+                self.store_config(device_id, previous);
+                return Err(e);
+            }
+
+            let restarted = active.device.clone().expect("started above");
+            drop(active);
+            self.notify(DeviceChange::Reconfigured(restarted));
+        }
+
+        Ok(())
+    }
+
+    /// Replaces format of the device, returns the previous one.
+    fn store_config(&self, device_id: &str, config: Option<OutputConfig>) -> Option<OutputConfig> {
+        let mut configs = self.configs.lock().unwrap();
+
+        match config {
+            Some(config) => configs.insert(device_id.to_string(), config),
+            None => configs.remove(device_id),
+        }
+    }
+
+    fn open(&self, device: &Device) -> anyhow::Result<()> {
+        let mut active = self.active.lock().unwrap();
+
+        if Some(device) == active.device.as_ref() {
+            return Ok(());
+        }
+
+        self.start(&mut active, device)
+    }
+
+    fn start(&self, active: &mut ActiveOutput, device: &Device) -> anyhow::Result<()> {
+        let config = if device.is_virtual {
+            None
+        } else {
+            self.configs.lock().unwrap().get(&device.id).copied()
+        };
+
+        let restarted = active
+            .device
+            .clone()
+            .filter(|d| d.id == device.id && !d.is_virtual);
+
+        if let Some(previous) = restarted {
+            return self.restart(active, device, config, previous);
+        }
+
+        // prepared first, so the previous stream keeps playing if the device can't be opened.
+        let pending = self.prepare(device, config)?;
+
+        // previous stream would keep pulling the queue along with the new one.
+        active.stream = None;
+        // forgotten until the new stream plays, so choosing the device again retries it.
+        active.device = None;
+
+        active.stream = Some(pending.play(self.queue.clone())?);
+        active.device = Some(Device {
+            config,
+            ..device.clone()
+        });
+
+        Ok(())
+    }
+
+    /// Reopens the active hardware device with the new format. Exclusive devices, e.g. ALSA `hw:`,
+    /// can't be opened twice, so its stream is closed first and reopened with the previous format
+    /// if the new one fails.
+    fn restart(
+        &self,
+        active: &mut ActiveOutput,
+        device: &Device,
+        config: Option<OutputConfig>,
+        previous: Device,
+    ) -> anyhow::Result<()> {
+        active.stream = None;
+        active.device = None;
+
+        let e = match self
+            .prepare(device, config)
+            .and_then(|p| p.play(self.queue.clone()))
+        {
+            Ok(stream) => {
+                active.stream = Some(stream);
+                active.device = Some(Device {
+                    config,
+                    ..device.clone()
+                });
+
+                return Ok(());
+            }
+            Err(e) => e,
+        };
+
+        let stream = self
+            .prepare(&previous, previous.config)
+            .and_then(|p| p.play(self.queue.clone()))
+            .with_context(|| format!("reopen previous format after: {e:#}"))?;
+
+        active.stream = Some(stream);
+        active.device = Some(previous);
+
+        Err(e)
+    }
+
+    fn prepare(&self, device: &Device, config: Option<OutputConfig>) -> anyhow::Result<Pending> {
+        if device.is_virtual {
+            let target = self
+                .virtual_devices()
+                .into_iter()
+                .find(|d| d.name() == device.id)
+                .context("device not found")?;
+
+            return Ok(Pending::Virtual(target));
+        }
+
+        let stream = self
+            .host
+            .build_stream(&device.id, config, self.queue.clone())?;
+
+        Ok(Pending::Hardware(stream))
+    }
+
+    /// Moves output if the active device vanished or the preferred one reappeared.
+    fn check(&self) -> anyhow::Result<()> {
+        let return_to_preferred = self.return_to_preferred.load(Ordering::SeqCst);
+        let active = self.active_device();
+        // unlike the listing, failed query must not be taken as all devices vanished.
+        let hardware = self.hardware_devices(active.as_ref(), false)?;
+        let devices = self.append_virtual(hardware, active.as_ref());
+        let preferred = self.preferred.lock().unwrap().clone();
+        let is_available = |device: &Device| devices.iter().any(|d| d == device);
+
+        if let Some(lost) = active.filter(|d| !is_available(d)) {
+            log::warn!("output device {} vanished", lost.id);
+
+            let fallback = devices.into_iter().find(|d| d.is_default);
+
+            if let Some(ref device) = fallback {
+                self.open(device).context("open default device")?;
+            } else {
+                // nothing to play on, stream of the lost device is dead anyway.
+                *self.active.lock().unwrap() = ActiveOutput::default();
+            }
+
+            self.notify(DeviceChange::Lost { lost, fallback });
+        } else if let Some(preferred) = preferred.filter(|d| {
+            return_to_preferred && self.active_device().as_ref() != Some(d) && is_available(d)
+        }) {
+            log::info!("output device {} reappeared", preferred.id);
+
+            self.open(&preferred).context("open preferred device")?;
+            self.notify(DeviceChange::Returned(preferred));
+        }
+
+        Ok(())
+    }
+
+    fn notify(&self, change: DeviceChange) {
+        self.events.send(Event::DeviceChanged(change));
+    }
+}
+
+impl fmt::Debug for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Output")
+            .field("active", &self.active)
+            .field("preferred", &self.preferred)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Sound hardware of the system.
+struct CpalHost;
+
+impl Host for CpalHost {
+    fn devices(&self, with_configs: bool) -> anyhow::Result<Vec<Device>> {
+        let host = cpal::default_host();
+        let devices = host.output_devices().context("output devices")?;
+        let default_device = host.default_output_device();
+
+        let mut result = vec![];
+
+        for device in devices {
+            let id = device.name()?;
+
+            let is_default = if let Some(ref default) = default_device {
+                id == default.name()?
+            } else {
+                false
+            };
+
+            result.push(Device {
+                configs: if with_configs {
+                    supported_configs(&device)
+                } else {
+                    vec![]
+                },
+                id,
+                is_active: false,
+                is_default,
+                is_virtual: false,
+                config: None,
+            });
+        }
+
+        Ok(result)
+    }
+
+    fn build_stream(
+        &self,
+        device_id: &str,
+        config: Option<OutputConfig>,
+        source: SharedSourcesQueue,
+    ) -> anyhow::Result<Box<dyn StreamTrait>> {
+        let mut devices = cpal::default_host().devices()?;
+
+        let cpal_device = loop {
+            if let Some(target) = devices.next() {
+                if target.name()? == device_id {
+                    break target;
+                }
+            } else {
+                return Err(anyhow::Error::msg("device not found"));
+            }
+        };
+
+        let supported = match config {
+            Some(config) => find_stream_config(&cpal_device, config)?,
+            None => cpal_device.default_output_config()?,
+        };
+
+        let mut stream_config = supported.config();
+
+        if let Some(buffer_size) = config.and_then(|c| c.buffer_size) {
+            stream_config.buffer_size = cpal::BufferSize::Fixed(buffer_size);
+        }
+
+        log::info!(
+            "output to {} at {} Hz, {} channels, {:?} buffer",
+            device_id,
+            stream_config.sample_rate.0,
+            stream_config.channels,
+            stream_config.buffer_size
+        );
+
+        let source = UniformSourceIterator::<_, f32>::new(
+            source,
+            stream_config.channels,
+            stream_config.sample_rate.0,
+        );
+
+        let stream = match supported.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&cpal_device, &stream_config, source),
+            SampleFormat::I16 => build_stream::<i16>(&cpal_device, &stream_config, source),
+            SampleFormat::U16 => build_stream::<u16>(&cpal_device, &stream_config, source),
+            format => anyhow::bail!("unsupported sample format {format}"),
+        }?;
+
+        Ok(Box::new(stream))
+    }
+}
+
+/// Sample formats the output stream can be built with, the preferred first.
+const SAMPLE_FORMATS: [SampleFormat; 3] = [SampleFormat::F32, SampleFormat::I16, SampleFormat::U16];
+
+fn supported_configs(device: &cpal::Device) -> Vec<SupportedConfig> {
+    let Ok(ranges) = device.supported_output_configs() else {
+        return vec![];
+    };
+
+    let mut configs: Vec<SupportedConfig> = vec![];
+
+    for range in ranges.filter(|r| SAMPLE_FORMATS.contains(&r.sample_format())) {
+        let config = SupportedConfig {
+            channels: range.channels(),
+            min_sample_rate: range.min_sample_rate().0,
+            max_sample_rate: range.max_sample_rate().0,
+            buffer_sizes: match *range.buffer_size() {
+                SupportedBufferSize::Range { min, max } => Some((min, max)),
+                SupportedBufferSize::Unknown => None,
+            },
+        };
+
+        // the same ranges are listed for each sample format.
+        if !configs.contains(&config) {
+            configs.push(config);
+        }
+    }
+
+    configs
+}
+
+fn find_stream_config(
+    device: &cpal::Device,
+    config: OutputConfig,
+) -> anyhow::Result<cpal::SupportedStreamConfig> {
+    let rate = cpal::SampleRate(config.sample_rate);
+
+    device
+        .supported_output_configs()?
+        .filter(|r| r.channels() == config.channels)
+        .filter(|r| r.min_sample_rate() <= rate && rate <= r.max_sample_rate())
+        .filter_map(|r| {
+            let preference = SAMPLE_FORMATS
+                .iter()
+                .position(|&f| f == r.sample_format())?;
+            Some((preference, r))
+        })
+        .min_by_key(|(preference, _)| *preference)
+        .map(|(_, range)| range.with_sample_rate(rate))
+        .with_context(|| format!("device doesn't support {config}"))
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut source: impl Iterator<Item = f32> + Send + 'static,
+) -> anyhow::Result<cpal::Stream>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let stream = device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            for sample in data.iter_mut() {
+                *sample = source.next().map_or(T::EQUILIBRIUM, T::from_sample);
+            }
+        },
+        |e| log::error!("output stream: {e}"),
+        None,
+    )?;
+
+    Ok(stream)
+}
+
+fn watch(output: &Weak<Output>) {
+    loop {
+        thread::sleep(WATCH_PERIOD);
+
+        let Some(output) = output.upgrade() else {
+            return;
+        };
+
+        if let Err(e) = output.check() {
+            log::error!("check output devices: {e:?}");
+        }
+    }
+}
+
+#[derive(Clone)]
+struct SharedSourcesQueue(Arc<Mutex<SourcesQueueOutput<f32>>>);
+
+impl Source for SharedSourcesQueue {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.0.lock().unwrap().current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.0.lock().unwrap().channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.0.lock().unwrap().sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.0.lock().unwrap().total_duration()
+    }
+}
+
+impl Iterator for SharedSourcesQueue {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.lock().unwrap().next()
+    }
+}
+
+impl From<SourcesQueueOutput<f32>> for SharedSourcesQueue {
+    fn from(value: SourcesQueueOutput<f32>) -> Self {
+        Self(Arc::new(Mutex::new(value)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::{Arc, Mutex};
+
+    use rodio::cpal::traits::StreamTrait;
+    use rodio::cpal::{PauseStreamError, PlayStreamError};
+
+    use super::super::super::{Device, DeviceChange, Event, OutputConfig};
+    use super::super::events::Events;
+    use super::super::virtual_output::VirtualDevice;
+    use super::{Host, Output, SharedSourcesQueue};
+
+    /// Devices of the fake host by ids with their defaultness.
+    #[derive(Default)]
+    struct Hardware {
+        devices: Vec<(&'static str, bool)>,
+        unsupported: Vec<OutputConfig>,
+        /// Devices with a built stream, they can't be opened again like ALSA `hw:` ones.
+        opened: HashSet<String>,
+    }
+
+    #[derive(Clone, Default)]
+    struct FakeHost(Arc<Mutex<Hardware>>);
+
+    impl FakeHost {
+        fn set_devices(&self, devices: &[(&'static str, bool)]) {
+            self.0.lock().unwrap().devices = devices.to_vec();
+        }
+
+        fn opened(&self) -> Vec<String> {
+            let mut opened: Vec<String> = self.0.lock().unwrap().opened.iter().cloned().collect();
+            opened.sort();
+
+            opened
+        }
+    }
+
+    impl Host for FakeHost {
+        fn devices(&self, _with_configs: bool) -> anyhow::Result<Vec<Device>> {
+            let devices = self.0.lock().unwrap().devices.clone();
+
+            Ok(devices
+                .into_iter()
+                .map(|(id, is_default)| Device {
+                    is_default,
+                    ..device(id)
+                })
+                .collect())
+        }
+
+        fn build_stream(
+            &self,
+            device_id: &str,
+            config: Option<OutputConfig>,
+            _source: SharedSourcesQueue,
+        ) -> anyhow::Result<Box<dyn StreamTrait>> {
+            let mut hardware = self.0.lock().unwrap();
+
+            anyhow::ensure!(
+                hardware.devices.iter().any(|(id, _)| *id == device_id),
+                "device not found"
+            );
+            anyhow::ensure!(
+                !config.is_some_and(|c| hardware.unsupported.contains(&c)),
+                "format isn't supported"
+            );
+            anyhow::ensure!(hardware.opened.insert(device_id.to_string()), "device busy");
+
+            Ok(Box::new(FakeStream {
+                host: self.clone(),
+                device_id: device_id.to_string(),
+            }))
+        }
+    }
+
+    struct FakeStream {
+        host: FakeHost,
+        device_id: String,
+    }
+
+    impl StreamTrait for FakeStream {
+        fn play(&self) -> Result<(), PlayStreamError> {
+            Ok(())
+        }
+
+        fn pause(&self) -> Result<(), PauseStreamError> {
+            Ok(())
+        }
+    }
+
+    impl Drop for FakeStream {
+        fn drop(&mut self) {
+            self.host.0.lock().unwrap().opened.remove(&self.device_id);
+        }
+    }
+
+    fn device(id: &str) -> Device {
+        Device {
+            id: id.to_string(),
+            is_active: false,
+            is_default: false,
+            is_virtual: false,
+            configs: vec![],
+            config: None,
+        }
+    }
+
+    fn new_output(host: &FakeHost) -> (Output, Events) {
+        let events = Events::default();
+        let (_, queue) = rodio::queue::queue(true);
+
+        (
+            Output::with_host(Box::new(host.clone()), queue, events.clone()),
+            events,
+        )
+    }
+
+    fn config(sample_rate: u32) -> OutputConfig {
+        OutputConfig {
+            sample_rate,
+            channels: 2,
+            buffer_size: None,
+        }
+    }
+
+    #[test]
+    fn test_set_config() {
+        let host = FakeHost::default();
+        host.set_devices(&[("hw:0", true), ("hw:1", false)]);
+        host.0.lock().unwrap().unsupported.push(config(96_000));
+
+        let (output, events) = new_output(&host);
+        let mut rx = events.subscribe();
+        output.use_device(&device("hw:0")).unwrap();
+
+        // exclusive device is closed before it's reopened with the new format.
+        output.set_config("hw:0", Some(config(48_000))).unwrap();
+        assert_eq!(output.active_device().unwrap().config, Some(config(48_000)));
+        assert_eq!(host.opened(), ["hw:0"]);
+
+        // unsupported format falls back to the previous one and isn't stored.
+        assert!(output.set_config("hw:0", Some(config(96_000))).is_err());
+        assert_eq!(output.active_device().unwrap().config, Some(config(48_000)));
+        assert_eq!(host.opened(), ["hw:0"]);
+
+        let devices = output.devices();
+        let configs: Vec<(&str, Option<OutputConfig>)> =
+            devices.iter().map(|d| (d.id(), d.config)).collect();
+        assert_eq!(
+            configs,
+            [
+                ("hw:0", Some(config(48_000))),
+                ("hw:1", None),
+                (VirtualDevice::Null.name().as_str(), None),
+            ]
+        );
+
+        // inactive and absent devices only store the format.
+        output.set_config("hw:1", Some(config(44_100))).unwrap();
+        output.set_config("hw:2", Some(config(44_100))).unwrap();
+        assert_eq!(output.active_device().unwrap().id, "hw:0");
+        assert_eq!(host.opened(), ["hw:0"]);
+
+        let null = VirtualDevice::Null.name();
+        assert!(output.set_config(&null, Some(config(44_100))).is_err());
+
+        let mut got = vec![];
+
+        while let Ok(Event::DeviceChanged(change)) = rx.try_recv() {
+            got.push(change);
+        }
+
+        assert_eq!(
+            got,
+            [
+                DeviceChange::Chosen(device("hw:0")),
+                DeviceChange::Reconfigured(device("hw:0")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let host = FakeHost::default();
+        host.set_devices(&[("hw:0", true), ("hw:1", false)]);
+
+        let (output, events) = new_output(&host);
+        output.set_return_to_preferred(true);
+        output.use_device(&device("hw:1")).unwrap();
+
+        let mut rx = events.subscribe();
+        let active = || output.active_device().map(|d| d.id);
+
+        // nothing changed.
+        output.check().unwrap();
+        assert_eq!(active().as_deref(), Some("hw:1"));
+
+        // unplugged device is replaced with the default one.
+        host.set_devices(&[("hw:0", true)]);
+        output.check().unwrap();
+        assert_eq!(active().as_deref(), Some("hw:0"));
+
+        // preferred device is plugged in again.
+        host.set_devices(&[("hw:0", true), ("hw:1", false)]);
+        output.check().unwrap();
+        assert_eq!(active().as_deref(), Some("hw:1"));
+        assert_eq!(host.opened(), ["hw:1"]);
+
+        // no device to fall back to.
+        host.set_devices(&[]);
+        output.check().unwrap();
+        assert_eq!(active(), None);
+        assert!(host.opened().is_empty());
+
+        let mut got = vec![];
+
+        while let Ok(Event::DeviceChanged(change)) = rx.try_recv() {
+            got.push(change);
+        }
+
+        assert_eq!(
+            got,
+            [
+                DeviceChange::Lost {
+                    lost: device("hw:1"),
+                    fallback: Some(device("hw:0")),
+                },
+                DeviceChange::Returned(device("hw:1")),
+                DeviceChange::Lost {
+                    lost: device("hw:1"),
+                    fallback: None,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rodio::Source;
+
+use crate::http::HttpConfig;
+
+use super::super::State;
+use super::buffer::BufferOptions;
+use super::source::Symphonia;
+use super::Session;
+
+/// Controls how a dropped stream is re-opened.
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    /// Maximum number of attempts before giving up, `0` disables reconnect.
+    pub max_retries: u32,
+    /// Delay before the first attempt, doubled on each next one.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between attempts.
+    pub max_backoff: Duration,
+}
+
+impl ReconnectPolicy {
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(attempt.saturating_sub(1));
+
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+/// How the streams are opened and re-opened.
+#[derive(Debug, Clone, Default)]
+pub struct StreamOptions {
+    pub http: HttpConfig,
+    pub reconnect: ReconnectPolicy,
+    pub buffering: BufferOptions,
+}
+
+/// Source which re-opens the stream when it is interrupted.
+///
+/// While reconnecting it yields silence, so the output keeps running and
+/// the audio thread is never blocked by the network.
+pub struct Reconnecting {
+    url: String,
+    options: StreamOptions,
+    session: Session,
+
+    source: Option<Symphonia>,
+    pending: Option<Receiver<anyhow::Result<Symphonia>>>,
+    cancel: Arc<AtomicBool>,
+
+    channels: u16,
+    sample_rate: u32,
+    silence: usize,
+}
+
+impl Reconnecting {
+    const SILENCE_PERIOD: Duration = Duration::from_millis(50);
+
+    pub fn new(url: &str, options: StreamOptions, session: Session) -> anyhow::Result<Self> {
+        let source = Symphonia::from_http(url, &options.http, options.buffering, &session)?;
+
+        Ok(Self {
+            url: url.to_string(),
+            options,
+            session,
+            channels: source.channels(),
+            sample_rate: source.sample_rate(),
+            source: Some(source),
+            pending: None,
+            cancel: Arc::default(),
+            silence: 0,
+        })
+    }
+
+    fn interrupt(&mut self) {
+        let Some(source) = self.source.take() else {
+            return;
+        };
+
+        self.channels = source.channels();
+        self.sample_rate = source.sample_rate();
+
+        if self.options.reconnect.max_retries == 0 {
+            log::warn!("stream {} interrupted", self.url);
+            self.session
+                .state
+                .set(State::Failed("stream interrupted".into()));
+            return;
+        }
+
+        log::warn!("stream {} interrupted, reconnecting", self.url);
+
+        let (tx, rx) = mpsc::channel();
+        let url = self.url.clone();
+        let options = self.options.clone();
+        let session = self.session.clone();
+        let cancel = self.cancel.clone();
+
+        let spawned = thread::Builder::new()
+            .name("tradio-reconnect".to_string())
+            .spawn(move || {
+                // receiver is gone if the source was dropped.
+                let _ = tx.send(reconnect(&url, &options, &session, &cancel));
+            });
+
+        if let Err(e) = spawned {
+            self.session.state.set(State::Failed(e.to_string()));
+            return;
+        }
+
+        self.pending = Some(rx);
+        self.silence = self.silence_len();
+    }
+
+    fn poll(&mut self) {
+        let Some(ref pending) = self.pending else {
+            return;
+        };
+
+        match pending.try_recv() {
+            Ok(Ok(source)) => {
+                log::info!("stream {} reconnected", self.url);
+                self.session.state.set(State::Playing);
+                self.source = Some(source);
+                self.pending = None;
+            }
+            Ok(Err(e)) => {
+                log::error!("reconnect {}: {e:?}", self.url);
+                self.session.state.set(State::Failed(e.to_string()));
+                self.pending = None;
+            }
+            Err(TryRecvError::Disconnected) => self.pending = None,
+            Err(TryRecvError::Empty) => self.silence = self.silence_len(),
+        }
+    }
+
+    fn silence_len(&self) -> usize {
+        silence_len(self.channels, self.sample_rate)
+    }
+}
+
+impl Source for Reconnecting {
+    fn current_frame_len(&self) -> Option<usize> {
+        match self.source {
+            // ended source is followed by silence while reconnecting.
+            Some(ref source) => match source.current_frame_len() {
+                Some(0) => Some(silence_len(source.channels(), source.sample_rate())),
+                len => len,
+            },
+            None => Some(self.silence),
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.as_ref().map_or(self.channels, Source::channels)
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source
+            .as_ref()
+            .map_or(self.sample_rate, Source::sample_rate)
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Iterator for Reconnecting {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(ref mut source) = self.source {
+            if let Some(sample) = source.next() {
+                return Some(sample);
+            }
+
+            self.interrupt();
+        }
+
+        if self.silence == 0 {
+            return None;
+        }
+
+        self.silence -= 1;
+
+        if self.silence == 0 {
+            self.poll();
+        }
+
+        Some(0)
+    }
+}
+
+impl Drop for Reconnecting {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::SeqCst);
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn silence_len(channels: u16, sample_rate: u32) -> usize {
+    let frames = u128::from(sample_rate) * Reconnecting::SILENCE_PERIOD.as_millis() / 1000;
+
+    (frames as usize * usize::from(channels)).max(1)
+}
+
+fn reconnect(
+    url: &str,
+    options: &StreamOptions,
+    session: &Session,
+    cancel: &AtomicBool,
+) -> anyhow::Result<Symphonia> {
+    let policy = options.reconnect;
+    let mut attempt = 0;
+
+    loop {
+        attempt += 1;
+
+        session.state.set(State::Reconnecting {
+            attempt,
+            max_retries: policy.max_retries,
+        });
+
+        let is_cancelled = || cancel.load(Ordering::SeqCst) || session.is_cancelled();
+
+        if sleep_unless(policy.backoff(attempt), is_cancelled) {
+            anyhow::bail!("reconnect cancelled");
+        }
+
+        match Symphonia::from_http(url, &options.http, options.buffering, session) {
+            Ok(source) => return Ok(source),
+            Err(e) if attempt >= policy.max_retries => return Err(e),
+            Err(e) => log::warn!("reconnect {url} attempt {attempt}: {e:?}"),
+        }
+    }
+}
+
+/// Sleeps for the duration unless cancelled meanwhile, reports if it was.
+/// Superseded stream must not hold its thread and connection for the whole backoff.
+fn sleep_unless(duration: Duration, is_cancelled: impl Fn() -> bool) -> bool {
+    const CHECK_PERIOD: Duration = Duration::from_millis(50);
+
+    let deadline = Instant::now() + duration;
+
+    loop {
+        if is_cancelled() {
+            return true;
+        }
+
+        let left = deadline.saturating_duration_since(Instant::now());
+
+        if left.is_zero() {
+            return false;
+        }
+
+        thread::sleep(left.min(CHECK_PERIOD));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::{sleep_unless, ReconnectPolicy};
+
+    #[test]
+    fn test_backoff() {
+        let policy = ReconnectPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(5),
+        };
+
+        let test_data = [
+            (1, 500),
+            (2, 1000),
+            (3, 2000),
+            (4, 4000),
+            (5, 5000),
+            (40, 5000),
+        ];
+
+        for (attempt, want) in test_data {
+            assert_eq!(policy.backoff(attempt), Duration::from_millis(want));
+        }
+    }
+
+    #[test]
+    fn test_sleep_unless() {
+        let started = Instant::now();
+        assert!(!sleep_unless(Duration::from_millis(120), || false));
+        assert!(started.elapsed() >= Duration::from_millis(120));
+
+        let cancelled = Arc::<AtomicBool>::default();
+
+        {
+            let cancelled = cancelled.clone();
+
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(100));
+                cancelled.store(true, Ordering::SeqCst);
+            });
+        }
+
+        let started = Instant::now();
+        assert!(sleep_unless(Duration::from_secs(30), || cancelled.load(Ordering::SeqCst)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
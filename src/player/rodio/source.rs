@@ -1,36 +1,233 @@
 use std::fmt::Formatter;
+use std::io::Read;
+use std::sync::OnceLock;
 use std::time::Duration;
 use std::{fmt, io};
 
 use anyhow::Context;
+use reqwest::blocking::Client;
+use reqwest::header::CONTENT_TYPE;
 use rodio::Source;
-use symphonia::core::audio::{SampleBuffer, SignalSpec};
-use symphonia::core::codecs::{Decoder, DecoderOptions};
+use symphonia::core::audio::{Channels, SampleBuffer, SignalSpec};
+use symphonia::core::codecs::{
+    CodecParameters, CodecRegistry, CodecType, Decoder, DecoderOptions, CODEC_TYPE_AAC,
+    CODEC_TYPE_FLAC, CODEC_TYPE_MP3, CODEC_TYPE_OPUS, CODEC_TYPE_VORBIS,
+};
+use symphonia::core::errors::Error;
 use symphonia::core::formats::{FormatOptions, FormatReader};
 use symphonia::core::io::{MediaSourceStream, MediaSourceStreamOptions, ReadOnlySource};
 use symphonia::core::meta::MetadataOptions;
 use symphonia::core::probe::Hint;
-use symphonia::default::{get_codecs, get_probe};
+use symphonia::default::{get_probe, register_enabled_codecs};
+
+use crate::http::HttpConfig;
+
+use super::super::hls::HlsReader;
+use super::super::playlist::Playlist;
+use super::super::StreamInfo;
+use super::buffer::{BufferOptions, BufferStatus, BufferedReader};
+use super::icy::IcyReader;
+use super::opus::Opus;
+use super::tee::TeeReader;
+use super::Session;
+
+/// Playlists may refer to other playlists, limit nesting to avoid loops.
+const MAX_PLAYLIST_DEPTH: usize = 3;
+const MAX_PLAYLIST_LEN: u64 = 1024 * 1024;
+
+/// Codec names used by station directories and their symphonia codec types.
+/// `AAC+` (HE-AAC) isn't listed, the AAC decoder supports only the LC profile.
+const CODEC_NAMES: [(&str, CodecType); 5] = [
+    ("MP3", CODEC_TYPE_MP3),
+    ("AAC", CODEC_TYPE_AAC),
+    ("FLAC", CODEC_TYPE_FLAC),
+    ("OGG", CODEC_TYPE_VORBIS),
+    ("OPUS", CODEC_TYPE_OPUS),
+];
+
+/// Returns registry of all enabled symphonia codecs and codecs implemented here.
+fn codecs() -> &'static CodecRegistry {
+    static CODECS: OnceLock<CodecRegistry> = OnceLock::new();
+
+    CODECS.get_or_init(|| {
+        let mut registry = CodecRegistry::new();
+        register_enabled_codecs(&mut registry);
+        registry.register_all::<Opus>();
+
+        registry
+    })
+}
+
+/// Returns names of codecs which can be decoded, as reported by station directories.
+pub fn supported_codecs() -> Vec<String> {
+    codec_names(codecs())
+}
+
+/// Returns names of codecs which have decoder in the registry.
+fn codec_names(registry: &CodecRegistry) -> Vec<String> {
+    CODEC_NAMES
+        .iter()
+        .filter(|(_, codec)| registry.get_codec(*codec).is_some())
+        .map(|(name, _)| (*name).to_string())
+        .collect()
+}
+
+/// Returns file extension of the raw stream if it can be recorded from any byte as is.
+/// Only self-framing formats qualify, others need their headers at the start of each file.
+fn raw_extension(params: &CodecParameters) -> Option<&'static str> {
+    match params.codec {
+        // codec config comes with the container (e.g. fMP4 of HLS) instead of each frame.
+        _ if params.extra_data.is_some() => None,
+        CODEC_TYPE_MP3 => Some("mp3"),
+        // ADTS, MP4 would have the config.
+        CODEC_TYPE_AAC => Some("aac"),
+        _ => None,
+    }
+}
 
 pub struct Symphonia {
     reader: Box<dyn FormatReader>,
     decoder: Box<dyn Decoder>,
+    codec: CodecType,
+    track_id: u32,
+    errors: DecodeErrors,
 
     offset: usize,
     buffer: SampleBuffer<i16>,
     spec: SignalSpec,
+    /// Bitrate in kbps announced by the server.
+    bitrate: Option<u32>,
+
+    /// Status of the downloaded stream, `None` if it's read directly.
+    status: Option<BufferStatus>,
+    /// Bytes and frames of the decoded packets, the byte rate of the stream is measured by them.
+    /// Container overhead isn't counted, e.g. a few percents of the HLS segments.
+    decoded: (u64, u64),
+    /// Samples of silence left to yield while the stream is re-buffered.
+    silence: usize,
 }
 
 impl Symphonia {
-    pub fn from_http(url: &str) -> anyhow::Result<Self> {
-        let resp = reqwest::blocking::Client::builder()
-            .connect_timeout(Duration::from_secs(5))
-            .build()?
+    /// Silence yielded at once while the stream is re-buffered.
+    const SILENCE_PERIOD: Duration = Duration::from_millis(50);
+
+    pub fn from_http(
+        url: &str,
+        http: &HttpConfig,
+        buffering: BufferOptions,
+        session: &Session,
+    ) -> anyhow::Result<Self> {
+        let client = http.blocking_client()?;
+
+        Self::open(&client, url, buffering, session, 0)
+    }
+
+    fn open(
+        client: &Client,
+        url: &str,
+        buffering: BufferOptions,
+        session: &Session,
+        depth: usize,
+    ) -> anyhow::Result<Self> {
+        // play request was superseded while the playlist was resolved.
+        anyhow::ensure!(!session.is_cancelled(), "connection cancelled");
+
+        let resp = client
             .get(url)
+            .header("Icy-MetaData", "1")
             .send()
             .context("get http response")?;
 
-        Self::from_reader(resp)
+        let content_type = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok());
+
+        if let Some(playlist) = Playlist::detect(content_type, resp.url().as_str()) {
+            anyhow::ensure!(depth < MAX_PLAYLIST_DEPTH, "too deeply nested playlist");
+
+            let base = resp.url().to_string();
+            let mut body = vec![];
+            resp.take(MAX_PLAYLIST_LEN).read_to_end(&mut body)?;
+
+            let body = String::from_utf8_lossy(&body);
+
+            if playlist.is_hls(&body) {
+                let reader = HlsReader::new(client.clone(), &base, &body)?;
+                return Self::from_tee(reader, buffering, None, session);
+            }
+
+            let mut result = Err(anyhow::anyhow!("playlist {url} has no streams"));
+
+            for entry in playlist.parse(&body, &base) {
+                result = Self::open(client, &entry, buffering, session, depth + 1);
+
+                match result {
+                    Ok(_) => break,
+                    Err(ref e) => log::warn!("playlist {url} entry {entry}: {e:?}"),
+                }
+            }
+
+            return result;
+        }
+
+        let bitrate = resp
+            .headers()
+            .get("icy-br")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.split(',').next()?.trim().parse::<u32>().ok())
+            .filter(|&v| v > 0);
+        let metaint = resp
+            .headers()
+            .get("icy-metaint")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|&v| v > 0);
+
+        match metaint {
+            Some(metaint) => {
+                let reader = IcyReader::new(resp, metaint, session.metadata.clone());
+                Self::from_tee(reader, buffering, bitrate, session)
+            }
+            None => Self::from_tee(resp, buffering, bitrate, session),
+        }
+    }
+
+    /// Builds source downloading the stream in background and copying its bytes
+    /// to the session recorder. Blocks until the pre-roll is downloaded.
+    fn from_tee<R>(
+        reader: R,
+        buffering: BufferOptions,
+        bitrate: Option<u32>,
+        session: &Session,
+    ) -> anyhow::Result<Self>
+    where
+        R: io::Read + Send + Sync + 'static,
+    {
+        let reader = TeeReader::new(reader, session.recorder.clone(), session.metadata.clone());
+        let reader = BufferedReader::new(reader, buffering, session.buffer_level.clone())
+            .context("start download")?;
+        let status = reader.status();
+
+        session.buffer.set(Some(status.clone()));
+
+        // probed as soon as the first bytes arrive, the pre-roll is measured by its packets.
+        let mut source = Self::from_reader(reader)?;
+        source.set_status(status.clone());
+        source.bitrate = bitrate;
+
+        anyhow::ensure!(
+            status.wait_ready(|| session.is_cancelled()),
+            "connection cancelled"
+        );
+
+        let extension = source
+            .reader
+            .default_track()
+            .and_then(|t| raw_extension(&t.codec_params));
+        session.extension.set(extension);
+
+        Ok(source)
     }
 
     pub fn from_reader<R>(reader: R) -> anyhow::Result<Self>
@@ -47,30 +244,213 @@ impl Symphonia {
             &MetadataOptions::default(),
         )?;
 
-        let mut reader = probe.format;
+        let reader = probe.format;
         let track = reader.default_track().context("track must by found")?;
-        let mut decoder = get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
+        let decoder = codecs().make(&track.codec_params, &DecoderOptions::default())?;
 
-        let packet = reader.next_packet().context("packet must by found")?;
-        let decoded_buf = decoder.decode(&packet).context("decode packet")?;
-        let spec = *decoded_buf.spec();
+        Self::new(reader, decoder)
+    }
 
-        let mut buffer = SampleBuffer::new(decoded_buf.capacity() as u64, spec);
-        buffer.copy_interleaved_ref(decoded_buf);
+    fn new(reader: Box<dyn FormatReader>, decoder: Box<dyn Decoder>) -> anyhow::Result<Self> {
+        let track = reader.default_track().context("track must by found")?;
 
-        Ok(Self {
+        let mut source = Self {
+            codec: track.codec_params.codec,
+            track_id: track.id,
             reader,
             decoder,
+            errors: DecodeErrors::default(),
             offset: 0,
-            buffer,
-            spec,
-        })
+            // replaced by the first decoded packet.
+            buffer: SampleBuffer::new(0, SignalSpec::new(0, Channels::FRONT_LEFT)),
+            spec: SignalSpec::new(0, Channels::FRONT_LEFT),
+            bitrate: None,
+            status: None,
+            decoded: (0, 0),
+            silence: 0,
+        };
+
+        anyhow::ensure!(source.decode_next(), "packet must by found");
+
+        Ok(source)
+    }
+
+    fn set_status(&mut self, status: BufferStatus) {
+        if let Some(byte_rate) = self.byte_rate() {
+            status.set_byte_rate(byte_rate);
+        }
+
+        self.status = Some(status);
+    }
+
+    /// Returns bytes per second of the packets decoded so far.
+    fn byte_rate(&self) -> Option<f64> {
+        let (bytes, frames) = self.decoded;
+
+        #[allow(clippy::cast_precision_loss)]
+        let byte_rate = (frames > 0 && self.spec.rate > 0)
+            .then(|| bytes as f64 * f64::from(self.spec.rate) / frames as f64);
+
+        byte_rate
+    }
+
+    /// Returns format of the stream, as of the last decoded packet.
+    pub fn info(&self) -> StreamInfo {
+        let codec = CODEC_NAMES
+            .iter()
+            .find(|(_, codec)| *codec == self.codec)
+            .map(|(name, _)| (*name).to_string())
+            .or_else(|| {
+                codecs()
+                    .get_codec(self.codec)
+                    .map(|d| d.short_name.to_uppercase())
+            });
+
+        StreamInfo {
+            codec,
+            bitrate: self.bitrate,
+            sample_rate: self.spec.rate,
+        }
+    }
+
+    /// Decodes the next packet into the buffer, skipping corrupt ones.
+    /// Returns `false` if the stream is ended.
+    fn decode_next(&mut self) -> bool {
+        loop {
+            let packet = match self.reader.next_packet() {
+                Ok(packet) => packet,
+                Err(Error::ResetRequired) => {
+                    if let Err(e) = self.reset_track() {
+                        log::warn!("reset decoder: {e:?}");
+                        return false;
+                    }
+
+                    continue;
+                }
+                Err(Error::DecodeError(e)) if self.errors.skip(e) => continue,
+                Err(e) => {
+                    log::debug!("read packet: {e}");
+                    return false;
+                }
+            };
+
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                Err(Error::ResetRequired) => {
+                    self.decoder.reset();
+                    continue;
+                }
+                Err(Error::DecodeError(e)) if self.errors.skip(e) => continue,
+                Err(e) => {
+                    log::warn!("decode packet: {e}");
+                    return false;
+                }
+            };
+
+            self.errors.in_row = 0;
+
+            if decoded.frames() == 0 {
+                continue;
+            }
+
+            self.decoded.0 += packet.data.len() as u64;
+            self.decoded.1 += decoded.frames() as u64;
+
+            let spec = *decoded.spec();
+
+            if spec != self.spec
+                || self.buffer.capacity() < decoded.capacity() * spec.channels.count()
+            {
+                if self.spec.rate > 0 && spec != self.spec {
+                    log::info!(
+                        "stream format changed to {} Hz, {} channels",
+                        spec.rate,
+                        spec.channels.count()
+                    );
+                }
+
+                self.buffer = SampleBuffer::new(decoded.capacity() as u64, spec);
+            }
+
+            // new format is reported once the samples of the previous one are played.
+            self.buffer.copy_interleaved_ref(decoded);
+            self.spec = spec;
+            self.offset = 0;
+
+            if let (Some(status), Some(byte_rate)) = (&self.status, self.byte_rate()) {
+                status.set_byte_rate(byte_rate);
+            }
+
+            return true;
+        }
+    }
+
+    /// Re-creates decoder for the new default track, e.g. after the stream was chained.
+    fn reset_track(&mut self) -> anyhow::Result<()> {
+        let track = self.reader.default_track().context("track must by found")?;
+
+        self.decoder = codecs().make(&track.codec_params, &DecoderOptions::default())?;
+        self.codec = track.codec_params.codec;
+        self.track_id = track.id;
+
+        Ok(())
+    }
+
+    /// Called once all samples of the buffer are read.
+    fn advance(&mut self) {
+        // reading the packet would block the output until the network catches up.
+        if self.status.as_ref().is_some_and(BufferStatus::is_buffering) {
+            self.silence = self.silence_len();
+            return;
+        }
+
+        self.decode_next();
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn silence_len(&self) -> usize {
+        let frames = u128::from(self.spec.rate) * Self::SILENCE_PERIOD.as_millis() / 1000;
+
+        (frames as usize * self.spec.channels.count()).max(1)
+    }
+}
+
+/// Counter of the corrupt packets skipped by [`Symphonia`].
+#[derive(Debug, Default)]
+struct DecodeErrors {
+    /// Packets skipped since the start.
+    skipped: u64,
+    /// Packets skipped in a row, the stream is ended if there are too many.
+    in_row: u32,
+}
+
+impl DecodeErrors {
+    /// Corrupt packets skipped in a row before the stream is treated as broken.
+    const MAX_IN_ROW: u32 = 20;
+
+    /// Counts corrupt packet, returns `false` if the stream should be ended.
+    fn skip(&mut self, error: &str) -> bool {
+        self.skipped += 1;
+        self.in_row += 1;
+
+        log::warn!("skip corrupt packet ({} in total): {error}", self.skipped);
+
+        self.in_row <= Self::MAX_IN_ROW
     }
 }
 
 impl Source for Symphonia {
     fn current_frame_len(&self) -> Option<usize> {
-        Some(self.buffer.samples().len())
+        if self.silence > 0 {
+            return Some(self.silence);
+        }
+
+        // frame ends with the buffer, the next one may have another format.
+        Some(self.buffer.len() - self.offset)
     }
 
     fn channels(&self) -> u16 {
@@ -94,24 +474,24 @@ impl Iterator for Symphonia {
     type Item = i16;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.offset == self.buffer.len() {
-            let Ok(packet) = self.reader.next_packet() else {
-                return None;
-            };
-            let Ok(decoded) = self.decoder.decode(&packet) else {
-                return None;
-            };
+        if self.silence > 0 {
+            self.silence -= 1;
 
-            let mut buffer = SampleBuffer::new(decoded.capacity() as u64, *decoded.spec());
-            buffer.copy_interleaved_ref(decoded);
+            if self.silence == 0 {
+                self.advance();
+            }
 
-            self.buffer = buffer;
-            self.offset = 0;
+            return Some(0);
         }
 
-        let sample = self.buffer.samples()[self.offset];
+        // buffer isn't refilled only if the stream is ended.
+        let sample = *self.buffer.samples().get(self.offset)?;
         self.offset += 1;
 
+        if self.offset == self.buffer.len() {
+            self.advance();
+        }
+
         Some(sample)
     }
 }
@@ -122,6 +502,291 @@ impl fmt::Debug for Symphonia {
         formatter.field("offset", &self.offset);
         formatter.field("buffer", &self.buffer.len());
         formatter.field("spec", &self.spec);
+        formatter.field("skipped", &self.errors.skipped);
         formatter.finish_non_exhaustive()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::io::{self, Cursor, Read};
+    use std::sync::mpsc::{self, Receiver};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use rodio::Source;
+    use symphonia::core::audio::{
+        AsAudioBufferRef, AudioBuffer, AudioBufferRef, Channels, Signal, SignalSpec,
+    };
+    use symphonia::core::codecs::{
+        CodecDescriptor, CodecParameters, CodecRegistry, Decoder, DecoderOptions, FinalizeResult,
+        CODEC_TYPE_AAC, CODEC_TYPE_FLAC, CODEC_TYPE_MP3, CODEC_TYPE_OPUS, CODEC_TYPE_VORBIS,
+    };
+    use symphonia::core::errors::{decode_error, unsupported_error, Error, Result};
+    use symphonia::core::formats::{
+        Cue, FormatOptions, FormatReader, Packet, SeekMode, SeekTo, SeekedTo, Track,
+    };
+    use symphonia::core::io::{MediaSourceStream, MediaSourceStreamOptions};
+    use symphonia::core::meta::{Metadata, MetadataLog};
+
+    use super::super::buffer::BufferOptions;
+    use super::super::opus::Opus;
+    use super::super::Session;
+    use super::{codec_names, raw_extension, supported_codecs, Symphonia};
+
+    #[test]
+    fn test_codec_names() {
+        let mut registry = CodecRegistry::new();
+        assert!(codec_names(&registry).is_empty());
+
+        registry.register_all::<Opus>();
+        assert_eq!(codec_names(&registry), ["OPUS"]);
+
+        // HE-AAC stations can't be decoded, so they aren't searched for.
+        let supported = supported_codecs();
+        assert!(supported.contains(&"MP3".to_string()));
+        assert!(supported.contains(&"AAC".to_string()));
+        assert!(!supported.contains(&"AAC+".to_string()));
+    }
+
+    #[test]
+    fn test_raw_extension() {
+        let test_data = [
+            (CODEC_TYPE_MP3, false, Some("mp3")),
+            (CODEC_TYPE_AAC, false, Some("aac")),
+            (CODEC_TYPE_AAC, true, None),
+            (CODEC_TYPE_FLAC, true, None),
+            (CODEC_TYPE_VORBIS, true, None),
+            (CODEC_TYPE_OPUS, false, None),
+        ];
+
+        for (codec, has_config, want) in test_data {
+            let mut params = CodecParameters::new();
+            params.for_codec(codec);
+
+            if has_config {
+                params.with_extra_data(Box::new([0x12, 0x10]));
+            }
+
+            assert_eq!(raw_extension(&params), want, "{codec}");
+        }
+    }
+
+    /// Reader of the packets given by the test.
+    struct MockReader {
+        tracks: Vec<Track>,
+        packets: VecDeque<Packet>,
+        metadata: MetadataLog,
+    }
+
+    impl FormatReader for MockReader {
+        fn try_new(_source: MediaSourceStream, _options: &FormatOptions) -> Result<Self> {
+            unsupported_error("mock")
+        }
+
+        fn cues(&self) -> &[Cue] {
+            &[]
+        }
+
+        fn metadata(&mut self) -> Metadata<'_> {
+            self.metadata.metadata()
+        }
+
+        fn seek(&mut self, _mode: SeekMode, _to: SeekTo) -> Result<SeekedTo> {
+            unsupported_error("mock")
+        }
+
+        fn tracks(&self) -> &[Track] {
+            &self.tracks
+        }
+
+        fn next_packet(&mut self) -> Result<Packet> {
+            self.packets
+                .pop_front()
+                .ok_or_else(|| Error::IoError(std::io::ErrorKind::UnexpectedEof.into()))
+        }
+
+        fn into_inner(self: Box<Self>) -> MediaSourceStream {
+            // packets are given by the test, there is no underlying stream.
+            MediaSourceStream::new(
+                Box::new(Cursor::new(vec![])),
+                MediaSourceStreamOptions::default(),
+            )
+        }
+    }
+
+    /// Decoder of the packets `[channels, rate in kHz, samples...]`, empty packet is corrupt.
+    struct MockDecoder {
+        params: CodecParameters,
+        buffer: AudioBuffer<i16>,
+    }
+
+    impl Decoder for MockDecoder {
+        fn try_new(params: &CodecParameters, _options: &DecoderOptions) -> Result<Self> {
+            Ok(Self {
+                params: params.clone(),
+                buffer: AudioBuffer::unused(),
+            })
+        }
+
+        fn supported_codecs() -> &'static [CodecDescriptor] {
+            &[]
+        }
+
+        fn reset(&mut self) {}
+
+        fn codec_params(&self) -> &CodecParameters {
+            &self.params
+        }
+
+        fn decode(&mut self, packet: &Packet) -> Result<AudioBufferRef<'_>> {
+            let [channels, rate, ref samples @ ..] = *packet.buf() else {
+                return decode_error("mock: corrupt packet");
+            };
+
+            let layout = match channels {
+                1 => Channels::FRONT_LEFT,
+                _ => Channels::FRONT_LEFT | Channels::FRONT_RIGHT,
+            };
+            let frames = samples.len() / usize::from(channels);
+
+            self.buffer = AudioBuffer::new(
+                frames as u64,
+                SignalSpec::new(u32::from(rate) * 1000, layout),
+            );
+            self.buffer.render_reserved(Some(frames));
+
+            for ch in 0..usize::from(channels) {
+                let channel = samples.iter().skip(ch).step_by(usize::from(channels));
+
+                for (dst, &src) in self.buffer.chan_mut(ch).iter_mut().zip(channel) {
+                    *dst = i16::from(src);
+                }
+            }
+
+            Ok(self.buffer.as_audio_buffer_ref())
+        }
+
+        fn finalize(&mut self) -> FinalizeResult {
+            FinalizeResult::default()
+        }
+
+        fn last_decoded(&self) -> AudioBufferRef<'_> {
+            self.buffer.as_audio_buffer_ref()
+        }
+    }
+
+    fn source(packets: &[&[u8]]) -> anyhow::Result<Symphonia> {
+        let params = CodecParameters::new();
+        let reader = MockReader {
+            tracks: vec![Track::new(0, params.clone())],
+            packets: packets
+                .iter()
+                .map(|data| Packet::new_from_slice(0, 0, 0, data))
+                .collect(),
+            metadata: MetadataLog::default(),
+        };
+        let decoder = MockDecoder::try_new(&params, &DecoderOptions::default())?;
+
+        Symphonia::new(Box::new(reader), Box::new(decoder))
+    }
+
+    #[test]
+    fn test_format_change() {
+        let mut source = source(&[&[1, 8, 1, 2], &[], &[2, 16, 3, 4, 5, 6], &[]]).unwrap();
+
+        // (current frame len, channels, sample rate, sample).
+        let mut got = vec![];
+
+        while source.current_frame_len() != Some(0) {
+            let frame = (
+                source.current_frame_len(),
+                source.channels(),
+                source.sample_rate(),
+            );
+            got.push((frame, source.next().unwrap()));
+        }
+
+        assert_eq!(source.next(), None);
+        assert_eq!(
+            got,
+            [
+                ((Some(2), 1, 8000), 1),
+                ((Some(1), 1, 8000), 2),
+                ((Some(4), 2, 16000), 3),
+                ((Some(3), 2, 16000), 4),
+                ((Some(2), 2, 16000), 5),
+                ((Some(1), 2, 16000), 6),
+            ]
+        );
+        assert_eq!(source.errors.skipped, 2);
+    }
+
+    #[test]
+    fn test_corrupt_stream() {
+        let corrupt: Vec<&[u8]> = vec![&[]; 25];
+
+        // stream is ended after too many corrupt packets in a row.
+        let mut packets = corrupt.clone();
+        packets.push(&[1, 8, 1]);
+        assert!(source(&packets).is_err());
+
+        // errors in row are reset by decoded packet.
+        let mut packets = vec![&[1, 8, 1][..]];
+        packets.extend(&corrupt[..15]);
+        packets.push(&[1, 8, 2]);
+        packets.extend(&corrupt[..15]);
+        packets.push(&[1, 8, 3]);
+
+        let source = source(&packets).unwrap();
+        assert_eq!(source.collect::<Vec<_>>(), [1, 2, 3]);
+    }
+
+    /// Reader returning chunks sent by the test, ends once the sender is dropped.
+    struct ChannelReader(Mutex<Receiver<Vec<u8>>>);
+
+    impl Read for ChannelReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Ok(chunk) = self.0.lock().unwrap().recv() else {
+                return Ok(0);
+            };
+
+            buf[..chunk.len()].copy_from_slice(&chunk);
+
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn test_buffered_stream() {
+        let (tx, rx) = mpsc::channel();
+
+        // 4 s of silent 128 kbps MP3 at 44.1 kHz, 417 bytes per 1152 frames.
+        let frame = [&[0xFF, 0xFB, 0x90, 0x00][..], &[0; 413]].concat();
+
+        for _ in 0..154 {
+            tx.send(frame.clone()).unwrap();
+        }
+
+        let options = BufferOptions {
+            pre_roll: Duration::from_secs(2),
+            capacity: Duration::from_secs(10),
+        };
+        let session = Session::default();
+        let reader = ChannelReader(Mutex::new(rx));
+        let mut source = Symphonia::from_tee(reader, options, None, &session).unwrap();
+        let status = session.buffer.get().unwrap();
+
+        // byte rate is measured, the decoder holds little of the stream.
+        let buffered = status.level().buffered();
+        assert!(buffered > Duration::from_millis(3500), "{buffered:?}");
+
+        // 2.5 s of stereo, the decoder's read-ahead mustn't drain the buffer.
+        for _ in 0..220_500 {
+            source.next().unwrap();
+        }
+
+        assert!(!status.is_buffering(), "{:?}", status.level());
+    }
+}
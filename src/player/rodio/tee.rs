@@ -0,0 +1,51 @@
+use std::io;
+use std::io::Read;
+
+use super::super::recorder::Recorder;
+use super::super::Metadata;
+use super::events::Announced;
+
+/// Reader which copies stream bytes to the [`Recorder`] and lets it
+/// split files when the stream title changes.
+pub struct TeeReader<R> {
+    inner: R,
+    recorder: Recorder,
+    metadata: Announced<Option<Metadata>>,
+    title: Option<String>,
+}
+
+impl<R: Read> TeeReader<R> {
+    pub fn new(inner: R, recorder: Recorder, metadata: Announced<Option<Metadata>>) -> Self {
+        let title = Self::current_title(&metadata);
+
+        Self {
+            inner,
+            recorder,
+            metadata,
+            title,
+        }
+    }
+
+    fn current_title(metadata: &Announced<Option<Metadata>>) -> Option<String> {
+        metadata.get().and_then(|m| m.title)
+    }
+}
+
+impl<R: Read> Read for TeeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+
+        // metadata is updated by the inner reader between audio chunks,
+        // so the title change is aligned with the bytes read after it.
+        let title = Self::current_title(&self.metadata);
+
+        if title != self.title {
+            self.recorder.set_title(title.as_deref());
+            self.title = title;
+        }
+
+        self.recorder.write(&buf[..n]);
+
+        Ok(n)
+    }
+}
@@ -0,0 +1,672 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rodio::Source;
+
+use super::super::Position;
+
+/// Samples moved between the buffer and its reader or writer at once.
+const CHUNK_LEN: usize = 2048;
+
+/// How far the download may run ahead of real time, the reconnecting
+/// source yields silence as fast as it is pulled.
+const MAX_LEAD: Duration = Duration::from_secs(10);
+
+/// Silence played at once when playback catches up with the download,
+/// the source is pulled by the audio thread, so it never waits for samples.
+const UNDERRUN_SILENCE: Duration = Duration::from_millis(50);
+
+/// How much played audio is retained to pause and rewind live streams.
+#[derive(Debug, Clone)]
+pub struct TimeShiftOptions {
+    /// Retained duration, sized for 48 kHz stereo streams.
+    pub capacity: Duration,
+    /// Directory for the buffer file, buffer is kept in memory if not set.
+    pub dir: Option<PathBuf>,
+}
+
+impl TimeShiftOptions {
+    fn capacity_samples(&self) -> u64 {
+        self.capacity
+            .as_secs()
+            .saturating_mul(48_000 * 2)
+            .max(CHUNK_LEN as u64 * 4)
+    }
+}
+
+/// Decoded stream retained around the playback position, filled by the download
+/// thread regardless of the sink, so pausing doesn't stall the connection.
+#[derive(Clone)]
+pub struct Timeline(Arc<Inner>);
+
+struct Inner {
+    buffer: Mutex<Buffer>,
+    written: Condvar,
+}
+
+struct Buffer {
+    samples: Samples,
+    capacity: u64,
+    /// Total number of samples downloaded.
+    written: u64,
+    /// Position of the next sample to play.
+    read: u64,
+    /// Stream spec changes, the first one may start before the oldest retained sample.
+    segments: VecDeque<Segment>,
+    is_ended: bool,
+    is_closed: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    start: u64,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Segment {
+    fn duration(&self, samples: u64) -> Duration {
+        let per_sec = u64::from(self.channels) * u64::from(self.sample_rate);
+
+        Duration::from_micros(samples.saturating_mul(1_000_000) / per_sec.max(1))
+    }
+
+    fn samples(&self, duration: Duration) -> u64 {
+        let frames = duration.as_millis() * u128::from(self.sample_rate) / 1000;
+
+        u64::try_from(frames).unwrap_or(u64::MAX) * u64::from(self.channels)
+    }
+}
+
+impl Timeline {
+    pub fn new(options: &TimeShiftOptions) -> io::Result<Self> {
+        let capacity = options.capacity_samples();
+
+        let samples = match options.dir {
+            Some(ref dir) => Samples::file(dir)?,
+            None => Samples::Memory(vec![]),
+        };
+
+        let buffer = Buffer {
+            samples,
+            capacity,
+            written: 0,
+            read: 0,
+            segments: VecDeque::new(),
+            is_ended: false,
+            is_closed: false,
+        };
+
+        Ok(Self(Arc::new(Inner {
+            buffer: Mutex::new(buffer),
+            written: Condvar::new(),
+        })))
+    }
+
+    /// Starts downloading the source in background and returns
+    /// source playing it, waits until first samples are downloaded.
+    pub fn play<S>(&self, source: S) -> io::Result<Playback>
+    where
+        S: Source<Item = i16> + Send + 'static,
+    {
+        let timeline = self.clone();
+        let (channels, sample_rate) = (source.channels(), source.sample_rate());
+
+        thread::Builder::new()
+            .name("tradio-timeshift".to_string())
+            .spawn(move || timeline.download(source))?;
+
+        self.wait_written();
+
+        Ok(Playback::new(self.clone(), channels, sample_rate))
+    }
+
+    /// Blocks until the first samples are downloaded or the download is ended.
+    fn wait_written(&self) {
+        let mut buffer = self.0.buffer.lock().unwrap();
+
+        while buffer.written == 0 && !buffer.is_ended {
+            buffer = self.0.written.wait(buffer).unwrap();
+        }
+    }
+
+    /// Returns playback position within the retained part of the stream.
+    pub fn position(&self) -> Position {
+        let buffer = self.0.buffer.lock().unwrap();
+        let oldest = buffer.oldest();
+
+        Position {
+            position: buffer.duration(oldest, buffer.read),
+            duration: buffer.duration(oldest, buffer.written),
+        }
+    }
+
+    /// Moves playback back by the duration, at most to the oldest retained sample.
+    pub fn rewind(&self, by: Duration) {
+        let mut buffer = self.0.buffer.lock().unwrap();
+        buffer.read = buffer.offset(buffer.read, by, false);
+    }
+
+    /// Moves playback forward by the duration, at most to the live edge.
+    pub fn fast_forward(&self, by: Duration) {
+        let mut buffer = self.0.buffer.lock().unwrap();
+        buffer.read = buffer.offset(buffer.read, by, true);
+    }
+
+    /// Moves playback to the live edge, skipping buffered audio.
+    pub fn jump_to_live(&self) {
+        let mut buffer = self.0.buffer.lock().unwrap();
+        buffer.read = buffer.written;
+    }
+
+    fn download<S: Source<Item = i16>>(&self, mut source: S) {
+        let started = Instant::now();
+        let mut downloaded = Duration::ZERO;
+        let mut chunk = Vec::with_capacity(CHUNK_LEN);
+
+        loop {
+            let len = source
+                .current_frame_len()
+                .unwrap_or(CHUNK_LEN)
+                .min(CHUNK_LEN);
+            let segment = Segment {
+                start: 0,
+                channels: source.channels(),
+                sample_rate: source.sample_rate(),
+            };
+
+            chunk.clear();
+            chunk.extend(source.by_ref().take(len));
+
+            if chunk.is_empty() || !self.push(segment, &chunk) {
+                break;
+            }
+
+            downloaded += segment.duration(chunk.len() as u64);
+
+            if let Some(ahead) = downloaded.checked_sub(started.elapsed() + MAX_LEAD) {
+                thread::sleep(ahead);
+            }
+        }
+
+        self.0.buffer.lock().unwrap().is_ended = true;
+        self.0.written.notify_all();
+    }
+
+    /// Appends samples, returns `false` if nobody plays the timeline anymore.
+    fn push(&self, segment: Segment, data: &[i16]) -> bool {
+        let mut buffer = self.0.buffer.lock().unwrap();
+
+        if buffer.is_closed {
+            return false;
+        }
+
+        if let Err(e) = buffer.push(segment, data) {
+            log::error!("time-shift buffer: {e:?}");
+            return false;
+        }
+
+        self.0.written.notify_all();
+
+        true
+    }
+}
+
+impl fmt::Debug for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeline")
+            .field("position", &self.position())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Buffer {
+    fn push(&mut self, mut segment: Segment, data: &[i16]) -> io::Result<()> {
+        let is_new_spec = self
+            .segments
+            .back()
+            .is_none_or(|s| s.channels != segment.channels || s.sample_rate != segment.sample_rate);
+
+        if is_new_spec {
+            segment.start = self.written;
+            self.segments.push_back(segment);
+        }
+
+        self.samples.write(self.written, self.capacity, data)?;
+        self.written += data.len() as u64;
+
+        let oldest = self.oldest();
+        self.read = self.read.max(oldest);
+
+        while self.segments.len() > 1 && self.segments[1].start <= oldest {
+            self.segments.pop_front();
+        }
+
+        Ok(())
+    }
+
+    /// Position of the oldest retained sample.
+    fn oldest(&self) -> u64 {
+        self.written.saturating_sub(self.capacity)
+    }
+
+    /// Returns segment containing sample at position and position it ends at.
+    fn segment(&self, position: u64) -> Option<(Segment, u64)> {
+        let idx = self.segments.iter().rposition(|s| s.start <= position)?;
+
+        let end = self.segments.get(idx + 1).map_or(self.written, |s| s.start);
+
+        Some((self.segments[idx], end))
+    }
+
+    /// Returns play duration of samples between positions.
+    fn duration(&self, mut from: u64, to: u64) -> Duration {
+        let mut result = Duration::ZERO;
+
+        while from < to {
+            let Some((segment, end)) = self.segment(from) else {
+                break;
+            };
+
+            let end = end.min(to);
+            result += segment.duration(end - from);
+            from = end;
+        }
+
+        result
+    }
+
+    /// Returns position moved by the duration within retained samples,
+    /// aligned to the frame of the segment it ends in.
+    fn offset(&self, mut position: u64, by: Duration, forward: bool) -> u64 {
+        let oldest = self.oldest();
+        let mut remaining = by;
+
+        while !remaining.is_zero() {
+            let (segment, end) = if forward {
+                if position >= self.written {
+                    break;
+                }
+
+                self.segment(position)
+            } else {
+                if position <= oldest {
+                    break;
+                }
+
+                self.segment(position - 1)
+            }
+            .expect("retained samples always have segment");
+
+            let (available, step) = if forward {
+                (end - position, segment.samples(remaining))
+            } else {
+                (
+                    position - segment.start.max(oldest),
+                    segment.samples(remaining),
+                )
+            };
+
+            if step < available {
+                if forward {
+                    position += step;
+                } else {
+                    position -= step;
+                }
+
+                let frame = u64::from(segment.channels.max(1));
+                position -= (position - segment.start) % frame;
+
+                break;
+            }
+
+            remaining = remaining.saturating_sub(segment.duration(available));
+            position = if forward {
+                end
+            } else {
+                segment.start.max(oldest)
+            };
+        }
+
+        position.max(oldest)
+    }
+
+    /// Reads samples of a single segment from the read position.
+    fn read(&mut self, out: &mut Vec<i16>) -> io::Result<Option<Segment>> {
+        let Some((segment, end)) = self.segment(self.read) else {
+            return Ok(None);
+        };
+
+        #[allow(clippy::cast_possible_truncation)]
+        let len = (end - self.read).min(CHUNK_LEN as u64) as usize;
+
+        out.resize(len, 0);
+        self.samples.read(self.read, self.capacity, out)?;
+        self.read += len as u64;
+
+        Ok(Some(segment))
+    }
+}
+
+/// Storage of the retained samples, used as a ring.
+enum Samples {
+    Memory(Vec<i16>),
+    File { file: File, path: PathBuf },
+}
+
+impl Samples {
+    fn file(dir: &std::path::Path) -> io::Result<Self> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        fs::create_dir_all(dir)?;
+
+        let path = dir.join(format!(
+            "timeshift-{}-{}.pcm",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+
+        Ok(Self::File { file, path })
+    }
+
+    fn write(&mut self, position: u64, capacity: u64, data: &[i16]) -> io::Result<()> {
+        for (offset, part) in Self::parts(position, capacity, data.len()) {
+            let part = &data[part];
+
+            match self {
+                Self::Memory(samples) => {
+                    let end = offset + part.len();
+
+                    if samples.len() < end {
+                        samples.resize(end, 0);
+                    }
+
+                    samples[offset..end].copy_from_slice(part);
+                }
+                Self::File { file, .. } => {
+                    let bytes: Vec<u8> = part.iter().flat_map(|s| s.to_le_bytes()).collect();
+
+                    file.seek(SeekFrom::Start(offset as u64 * 2))?;
+                    file.write_all(&bytes)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read(&mut self, position: u64, capacity: u64, out: &mut [i16]) -> io::Result<()> {
+        for (offset, part) in Self::parts(position, capacity, out.len()) {
+            let out = &mut out[part];
+
+            match self {
+                Self::Memory(samples) => out.copy_from_slice(&samples[offset..offset + out.len()]),
+                Self::File { file, .. } => {
+                    let mut bytes = vec![0; out.len() * 2];
+
+                    file.seek(SeekFrom::Start(offset as u64 * 2))?;
+                    file.read_exact(&mut bytes)?;
+
+                    for (sample, b) in out.iter_mut().zip(bytes.chunks_exact(2)) {
+                        *sample = i16::from_le_bytes([b[0], b[1]]);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Splits range of samples at position into ring offsets and ranges of the data.
+    #[allow(clippy::cast_possible_truncation)]
+    fn parts(
+        position: u64,
+        capacity: u64,
+        len: usize,
+    ) -> impl Iterator<Item = (usize, std::ops::Range<usize>)> {
+        let offset = (position % capacity) as usize;
+        let first = len.min(capacity as usize - offset);
+
+        [(offset, 0..first), (0, first..len)]
+            .into_iter()
+            .filter(|(_, range)| !range.is_empty())
+    }
+}
+
+impl Drop for Samples {
+    fn drop(&mut self) {
+        if let Self::File { path, .. } = self {
+            if let Err(e) = fs::remove_file(&path) {
+                log::warn!("remove {}: {e:?}", path.display());
+            }
+        }
+    }
+}
+
+/// Source playing the [Timeline] from its read position.
+pub struct Playback {
+    timeline: Timeline,
+    chunk: Vec<i16>,
+    offset: usize,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Playback {
+    /// Spec of the source is used until the first chunk is read, e.g. for silence.
+    fn new(timeline: Timeline, channels: u16, sample_rate: u32) -> Self {
+        let mut playback = Self {
+            timeline,
+            chunk: vec![],
+            offset: 0,
+            channels,
+            sample_rate,
+        };
+
+        playback.refill();
+        playback
+    }
+
+    /// Takes next chunk from the timeline, plays silence if playback caught up with the download.
+    fn refill(&mut self) {
+        let mut buffer = self.timeline.0.buffer.lock().unwrap();
+
+        self.offset = 0;
+
+        if buffer.read == buffer.written {
+            self.chunk.clear();
+
+            if !buffer.is_ended {
+                let silence = Segment {
+                    start: 0,
+                    channels: self.channels,
+                    sample_rate: self.sample_rate,
+                };
+
+                #[allow(clippy::cast_possible_truncation)]
+                let len = silence.samples(UNDERRUN_SILENCE) as usize;
+                self.chunk.resize(len.max(1), 0);
+            }
+
+            return;
+        }
+
+        match buffer.read(&mut self.chunk) {
+            Ok(Some(segment)) => {
+                self.channels = segment.channels;
+                self.sample_rate = segment.sample_rate;
+            }
+            Ok(None) => self.chunk.clear(),
+            Err(e) => {
+                log::error!("time-shift buffer: {e:?}");
+                self.chunk.clear();
+                buffer.is_ended = true;
+            }
+        }
+    }
+}
+
+impl Source for Playback {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.chunk.len() - self.offset)
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Iterator for Playback {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = *self.chunk.get(self.offset)?;
+        self.offset += 1;
+
+        if self.offset == self.chunk.len() {
+            self.refill();
+        }
+
+        Some(sample)
+    }
+}
+
+impl Drop for Playback {
+    fn drop(&mut self) {
+        self.timeline.0.buffer.lock().unwrap().is_closed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use rodio::buffer::SamplesBuffer;
+    use rodio::Source;
+
+    use super::{Segment, TimeShiftOptions, Timeline};
+
+    fn segment(channels: u16, sample_rate: u32) -> Segment {
+        Segment {
+            start: 0,
+            channels,
+            sample_rate,
+        }
+    }
+
+    fn timeline(dir: Option<std::path::PathBuf>) -> Timeline {
+        let options = TimeShiftOptions {
+            capacity: Duration::ZERO,
+            dir,
+        };
+
+        Timeline::new(&options).unwrap()
+    }
+
+    #[test]
+    fn test_buffer() {
+        let dir = std::env::temp_dir().join(format!("tradio-timeshift-{}", std::process::id()));
+
+        for timeline in [timeline(None), timeline(Some(dir.clone()))] {
+            let mut buffer = timeline.0.buffer.lock().unwrap();
+            let capacity = buffer.capacity;
+            let data: Vec<i16> = (0..2000).collect();
+
+            // one second of mono 1 kHz audio, then stereo.
+            buffer.push(segment(1, 1000), &data[..1000]).unwrap();
+            buffer.push(segment(1, 1000), &[]).unwrap();
+            buffer.push(segment(2, 1000), &data[1000..]).unwrap();
+
+            assert_eq!(buffer.segments.len(), 2);
+            assert_eq!(
+                buffer.duration(buffer.read, buffer.written),
+                Duration::from_millis(1500)
+            );
+
+            let mut out = vec![];
+            let got = buffer.read(&mut out).unwrap().unwrap();
+            assert_eq!((got.channels, out.len(), out[999]), (1, 1000, 999));
+
+            let got = buffer.read(&mut out).unwrap().unwrap();
+            assert_eq!((got.channels, out.len(), out[0]), (2, 1000, 1000));
+
+            // overflow drops the oldest samples and moves reader forward.
+            let filler = vec![7; usize::try_from(capacity).unwrap() - 500];
+            buffer.read = 0;
+            buffer.push(segment(2, 1000), &filler).unwrap();
+
+            assert_eq!(buffer.read, 1500);
+            assert_eq!(buffer.segments.len(), 1);
+
+            buffer.read(&mut out).unwrap().unwrap();
+            assert_eq!(&out[..3], &[1500, 1501, 1502]);
+            assert_eq!(out.len(), 2048);
+        }
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_seek() {
+        let timeline = timeline(None);
+        let mut buffer = timeline.0.buffer.lock().unwrap();
+
+        // 2 seconds of mono 1 kHz audio, then 1 second of stereo.
+        buffer.push(segment(1, 1000), &[0; 2000]).unwrap();
+        buffer.push(segment(2, 1000), &[0; 2000]).unwrap();
+
+        let test_data = [
+            (4000, 500, false, 3000),
+            (4000, 1500, false, 1500),
+            (4000, 9000, false, 0),
+            (0, 2500, true, 3000),
+            (3001, 0, true, 3001),
+            (3001, 10, true, 3020),
+            (3000, 9000, true, 4000),
+        ];
+
+        for (from, by, forward, want) in test_data {
+            let got = buffer.offset(from, Duration::from_millis(by), forward);
+            assert_eq!(got, want, "from {from} by {by} forward {forward}");
+        }
+    }
+
+    #[test]
+    fn test_playback() {
+        let data: Vec<i16> = (1..=5000).collect();
+        let playback = timeline(None)
+            .play(SamplesBuffer::new(1, 8000, data.clone()))
+            .unwrap();
+
+        // first samples are downloaded already, in the spec of the source.
+        assert_eq!((playback.channels(), playback.sample_rate()), (1, 8000));
+        assert_ne!(playback.current_frame_len(), Some(0));
+
+        // silence may be played if the download falls behind.
+        let played: Vec<i16> = playback.filter(|s| *s != 0).collect();
+
+        assert_eq!(played, data);
+    }
+}
@@ -0,0 +1,307 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use rodio::Source;
+
+/// Samples are pulled in blocks, 10 ms each.
+const BLOCKS_PER_SECOND: u32 = 100;
+
+/// Output falling behind by more than this skips ahead instead of catching up.
+const MAX_LAG: Duration = Duration::from_secs(1);
+
+/// Output device not backed by the sound hardware.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VirtualDevice {
+    /// Discards samples.
+    Null,
+    /// Writes samples to a WAV file, it's overwritten each time the device is used.
+    Wav(PathBuf),
+}
+
+impl VirtualDevice {
+    pub fn name(&self) -> String {
+        match self {
+            Self::Null => "Null output".to_string(),
+            Self::Wav(path) => format!("WAV file {}", path.display()),
+        }
+    }
+
+    /// Starts consuming the source at real-time rate.
+    pub fn start<S>(&self, source: S) -> io::Result<VirtualOutput>
+    where
+        S: Source<Item = f32> + Send + 'static,
+    {
+        let wav = match self {
+            Self::Null => None,
+            Self::Wav(path) => {
+                let file = BufWriter::new(File::create(path)?);
+                Some(WavWriter::new(
+                    file,
+                    source.channels(),
+                    source.sample_rate(),
+                )?)
+            }
+        };
+
+        let is_stopped = Arc::<AtomicBool>::default();
+        let thread = {
+            let is_stopped = is_stopped.clone();
+
+            thread::Builder::new()
+                .name("tradio-virtual-output".to_string())
+                .spawn(move || run(source, wav, &is_stopped))?
+        };
+
+        Ok(VirtualOutput {
+            is_stopped,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// Running output of a [`VirtualDevice`], stopped on drop.
+#[derive(Debug)]
+pub struct VirtualOutput {
+    is_stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for VirtualOutput {
+    fn drop(&mut self) {
+        self.is_stopped.store(true, Ordering::SeqCst);
+
+        // source must not be pulled by the stopped output once another one is started.
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run<S, W>(mut source: S, mut wav: Option<WavWriter<W>>, is_stopped: &AtomicBool)
+where
+    S: Source<Item = f32>,
+    W: Write + Seek,
+{
+    let mut deadline = Instant::now();
+
+    while !is_stopped.load(Ordering::SeqCst) {
+        let samples_per_block =
+            source.sample_rate() * u32::from(source.channels()) / BLOCKS_PER_SECOND;
+
+        for _ in 0..samples_per_block {
+            let Some(sample) = source.next() else {
+                break;
+            };
+
+            if let Some(ref mut writer) = wav {
+                if let Err(e) = writer.write(sample) {
+                    log::error!("write wav output: {e}");
+                    // samples are discarded from now on, written part is kept readable.
+                    finish_wav(wav.take());
+                }
+            }
+        }
+
+        deadline += Duration::from_secs(1) / BLOCKS_PER_SECOND;
+        let now = Instant::now();
+
+        if now > deadline + MAX_LAG {
+            deadline = now;
+        } else if deadline > now {
+            thread::sleep(deadline - now);
+        }
+    }
+
+    finish_wav(wav);
+}
+
+fn finish_wav<W: Write + Seek>(wav: Option<WavWriter<W>>) {
+    if let Some(writer) = wav {
+        if let Err(e) = writer.finish() {
+            log::error!("finish wav output: {e}");
+        }
+    }
+}
+
+/// Writes 16-bit PCM WAV, sizes in the header are set on finish.
+/// Sizes are 32-bit, so writing fails once the file reaches 4 GiB, about 6 hours of stereo 48 kHz.
+struct WavWriter<W: Write + Seek> {
+    inner: W,
+    block_align: u32,
+    data_len: u32,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    const HEADER_LEN: u32 = 44;
+    /// RIFF size counts the header after its own field too.
+    const MAX_DATA_LEN: u32 = u32::MAX - (Self::HEADER_LEN - 8);
+
+    fn new(mut inner: W, channels: u16, sample_rate: u32) -> io::Result<Self> {
+        let block_align = channels * 2;
+
+        inner.write_all(b"RIFF")?;
+        // unknown until finished, readers treat it as the stream to the end of file.
+        inner.write_all(&u32::MAX.to_le_bytes())?;
+        inner.write_all(b"WAVEfmt ")?;
+        inner.write_all(&16_u32.to_le_bytes())?;
+        inner.write_all(&1_u16.to_le_bytes())?;
+        inner.write_all(&channels.to_le_bytes())?;
+        inner.write_all(&sample_rate.to_le_bytes())?;
+        inner.write_all(&(sample_rate * u32::from(block_align)).to_le_bytes())?;
+        inner.write_all(&block_align.to_le_bytes())?;
+        inner.write_all(&16_u16.to_le_bytes())?;
+        inner.write_all(b"data")?;
+        inner.write_all(&u32::MAX.to_le_bytes())?;
+
+        Ok(Self {
+            inner,
+            block_align: u32::from(block_align),
+            data_len: 0,
+        })
+    }
+
+    fn write(&mut self, sample: f32) -> io::Result<()> {
+        // only whole frames are written.
+        if self.data_len.is_multiple_of(self.block_align)
+            && self.data_len > Self::MAX_DATA_LEN - self.block_align
+        {
+            return Err(io::Error::other("WAV file size limit reached"));
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        let sample = (sample.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16;
+
+        self.inner.write_all(&sample.to_le_bytes())?;
+        self.data_len += 2;
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<W> {
+        let riff_len = self.data_len + (Self::HEADER_LEN - 8);
+
+        self.inner.seek(SeekFrom::Start(4))?;
+        self.inner.write_all(&riff_len.to_le_bytes())?;
+        self.inner
+            .seek(SeekFrom::Start(u64::from(Self::HEADER_LEN - 4)))?;
+        self.inner.write_all(&self.data_len.to_le_bytes())?;
+        self.inner.seek(SeekFrom::End(0))?;
+        self.inner.flush()?;
+
+        Ok(self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use rodio::Source;
+
+    use super::{VirtualDevice, WavWriter};
+
+    #[test]
+    fn test_wav_writer() {
+        let mut writer = WavWriter::new(Cursor::new(vec![]), 2, 48_000).unwrap();
+
+        for sample in [0.0, 0.5, -1.0, 2.0] {
+            writer.write(sample).unwrap();
+        }
+
+        let bytes = writer.finish().unwrap().into_inner();
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        let i16_at = |i: usize| i16::from_le_bytes(bytes[i..i + 2].try_into().unwrap());
+
+        assert_eq!(bytes.len(), 44 + 8);
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(u32_at(4), 36 + 8);
+        assert_eq!(u32_at(24), 48_000);
+        assert_eq!(u32_at(40), 8);
+        assert_eq!(
+            [i16_at(44), i16_at(46), i16_at(48), i16_at(50)],
+            [0, 16383, -32767, 32767]
+        );
+    }
+
+    #[test]
+    fn test_wav_writer_limit() {
+        let mut writer = WavWriter::new(Cursor::new(vec![]), 2, 48_000).unwrap();
+        // the last stereo frame which fits.
+        let data_len = (WavWriter::<Cursor<Vec<u8>>>::MAX_DATA_LEN - 4) / 4 * 4;
+        writer.data_len = data_len;
+
+        writer.write(0.0).unwrap();
+        writer.write(0.0).unwrap();
+        assert!(writer.write(0.0).is_err());
+
+        let bytes = writer.finish().unwrap().into_inner();
+        let riff_len = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+
+        assert_eq!(riff_len, data_len + 4 + 36);
+    }
+
+    #[test]
+    fn test_null_output_rate() {
+        let consumed = Arc::<AtomicUsize>::default();
+        let source = Silence {
+            consumed: consumed.clone(),
+        };
+
+        let started = Instant::now();
+        let output = VirtualDevice::Null.start(source).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        drop(output);
+        let elapsed = started.elapsed();
+
+        // consumed at real-time rate in 10 ms blocks, the first one at once.
+        let blocks = elapsed.as_millis() / 10 + 1;
+        let consumed = consumed.load(Ordering::SeqCst);
+
+        assert!(consumed > 0);
+        assert!(
+            consumed as u128 <= blocks * 960,
+            "consumed {consumed} samples in {elapsed:?}"
+        );
+    }
+
+    /// Endless stereo 48 kHz silence counting pulled samples.
+    struct Silence {
+        consumed: Arc<AtomicUsize>,
+    }
+
+    impl Iterator for Silence {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            self.consumed.fetch_add(1, Ordering::SeqCst);
+
+            Some(0.0)
+        }
+    }
+
+    impl Source for Silence {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            2
+        }
+
+        fn sample_rate(&self) -> u32 {
+            48_000
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+}
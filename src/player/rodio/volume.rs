@@ -0,0 +1,102 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Maps volume percentage to the amplitude of the output.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum VolumeCurve {
+    /// Amplitude is proportional to the volume.
+    #[default]
+    Linear,
+    /// Each step changes loudness by the same number of dB, as it's perceived.
+    Logarithmic,
+}
+
+impl VolumeCurve {
+    /// Range of the logarithmic curve, the lowest volume is quieter than the highest by this.
+    const RANGE_DB: f32 = 50.0;
+
+    /// Returns amplitude in range [0 - 1] of the volume in percentage [0 - 100].
+    pub fn amplitude(self, volume: i8) -> f32 {
+        let ratio = f32::from(volume.clamp(0, 100)) / 100.0;
+
+        match self {
+            Self::Linear => ratio,
+            Self::Logarithmic if volume <= 0 => 0.0,
+            Self::Logarithmic => 10_f32.powf((ratio - 1.0) * Self::RANGE_DB / 20.0),
+        }
+    }
+}
+
+impl fmt::Display for VolumeCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Linear => f.write_str("linear"),
+            Self::Logarithmic => f.write_str("log"),
+        }
+    }
+}
+
+impl FromStr for VolumeCurve {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "linear" => Ok(Self::Linear),
+            "log" | "logarithmic" => Ok(Self::Logarithmic),
+            _ => Err(anyhow::anyhow!("unknown volume curve {value}")),
+        }
+    }
+}
+
+/// Volume set by the user, kept while muted.
+#[derive(Debug, Clone, Copy)]
+pub struct Volume {
+    /// Volume in percentage [0 - 100].
+    pub level: i8,
+    pub is_muted: bool,
+    pub curve: VolumeCurve,
+}
+
+impl Volume {
+    /// Returns amplitude applied to the output.
+    pub fn amplitude(self) -> f32 {
+        if self.is_muted {
+            0.0
+        } else {
+            self.curve.amplitude(self.level)
+        }
+    }
+}
+
+impl Default for Volume {
+    fn default() -> Self {
+        Self {
+            level: 100,
+            is_muted: false,
+            curve: VolumeCurve::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VolumeCurve;
+
+    #[test]
+    fn test_amplitude() {
+        let test_data = [
+            (VolumeCurve::Linear, 0, 0.0),
+            (VolumeCurve::Linear, 50, 0.5),
+            (VolumeCurve::Linear, 120, 1.0),
+            (VolumeCurve::Logarithmic, 0, 0.0),
+            (VolumeCurve::Logarithmic, 60, 0.1),
+            (VolumeCurve::Logarithmic, 100, 1.0),
+            (VolumeCurve::Logarithmic, -5, 0.0),
+        ];
+
+        for (curve, volume, want) in test_data {
+            let got = curve.amplitude(volume);
+            assert!((got - want).abs() < 0.001, "{curve} {volume}: {got}");
+        }
+    }
+}
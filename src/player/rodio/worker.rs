@@ -0,0 +1,198 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use rodio::Source;
+
+use super::super::State;
+use super::equalizer::{EqSettings, Equalizer};
+use super::loudness::{Loudness, Normalizer};
+use super::mixer::MixerControl;
+use super::reconnect::{Reconnecting, StreamOptions};
+use super::Session;
+
+pub enum Command {
+    /// Connects to the stream and crossfades the current source with it.
+    Play {
+        url: String,
+        session: Session,
+        options: StreamOptions,
+        effects: Effects,
+    },
+}
+
+/// Processing of the decoded streams, shared by all of them.
+#[derive(Debug, Clone, Default)]
+pub struct Effects {
+    /// Time the previous stream overlaps the next one.
+    pub crossfade: Duration,
+    pub loudness: Loudness,
+    pub equalizer: EqSettings,
+}
+
+/// Background thread which takes play requests off callers of [`super::Rodio`].
+/// Each stream is opened on its own thread, so a hung connection is left behind
+/// once cancelled and never delays the next request.
+#[derive(Clone)]
+pub struct Worker {
+    mixer: Arc<MixerControl>,
+    /// Current session of the player, locked while a stream is started.
+    current: Arc<Mutex<Session>>,
+}
+
+impl Worker {
+    pub fn new(mixer: Arc<MixerControl>, current: Arc<Mutex<Session>>) -> Self {
+        Self { mixer, current }
+    }
+
+    /// Starts worker thread, it stops when the returned sender is dropped.
+    pub fn spawn(self) -> Sender<Command> {
+        let (tx, rx) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("tradio-playback".to_string())
+            .spawn(move || self.run(&rx))
+            .expect("can't spawn playback worker");
+
+        tx
+    }
+
+    fn run(&self, rx: &Receiver<Command>) {
+        while let Ok(mut command) = rx.recv() {
+            // only the latest request matters, skip superseded ones.
+            while let Ok(next) = rx.try_recv() {
+                command = next;
+            }
+
+            match command {
+                Command::Play {
+                    url,
+                    session,
+                    options,
+                    effects,
+                } => self.spawn_play(url, session, options, effects),
+            }
+        }
+    }
+
+    fn spawn_play(&self, url: String, session: Session, options: StreamOptions, effects: Effects) {
+        if session.is_cancelled() {
+            return;
+        }
+
+        let worker = self.clone();
+        let failed = session.clone();
+
+        let spawned = thread::Builder::new()
+            .name("tradio-connect".to_string())
+            .spawn(move || worker.play(&url, &session, options, effects));
+
+        if let Err(e) = spawned {
+            failed.state.set(State::Failed(e.to_string()));
+        }
+    }
+
+    fn play(&self, url: &str, session: &Session, options: StreamOptions, effects: Effects) {
+        let source = match Reconnecting::new(url, options, session.clone()) {
+            Ok(source) => source,
+            Err(e) => {
+                log::error!("play {url}: {e:?}");
+                session.state.set(State::Failed(format!("{e:#}")));
+                return;
+            }
+        };
+
+        if session.is_cancelled() {
+            log::debug!("play {url}: cancelled");
+            return;
+        }
+
+        let source: Box<dyn Source<Item = i16> + Send> = match session.timeline {
+            Some(ref timeline) => match timeline.play(source) {
+                Ok(playback) => Box::new(playback),
+                Err(e) => {
+                    log::error!("play {url}: {e:?}");
+                    session.state.set(State::Failed(e.to_string()));
+                    return;
+                }
+            },
+            None => Box::new(source),
+        };
+
+        if session.is_cancelled() {
+            return;
+        }
+
+        let source = Equalizer::new(source, effects.equalizer);
+        let source = Normalizer::new(source, effects.loudness, session.gain.clone());
+
+        if !self.start(session, source, effects.crossfade) {
+            log::debug!("play {url}: cancelled");
+        }
+    }
+
+    /// Plays the source unless its session is cancelled, reports if it's started.
+    /// Sessions are cancelled under the same lock, so a superseded connection
+    /// finishing last can't replace the stream of the current one.
+    fn start<S>(&self, session: &Session, source: S, crossfade: Duration) -> bool
+    where
+        S: Source<Item = i16> + Send + 'static,
+    {
+        let _current = self.current.lock().unwrap();
+
+        if session.is_cancelled() {
+            return false;
+        }
+
+        self.mixer.play(source, crossfade);
+        session.state.set(State::Playing);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use rodio::buffer::SamplesBuffer;
+
+    use super::super::super::State;
+    use super::super::mixer::Mixer;
+    use super::super::Session;
+    use super::Worker;
+
+    fn source(value: i16) -> SamplesBuffer<i16> {
+        SamplesBuffer::new(2, 48_000, vec![value; 48_000])
+    }
+
+    #[test]
+    fn test_superseded_start() {
+        let (mut mixer, control) = Mixer::new();
+        let current = Arc::new(Mutex::new(Session::default()));
+        let worker = Worker::new(Arc::new(control), current.clone());
+
+        let older = Session::default();
+        Session::replace(&current, older.clone());
+        let newer = Session::default();
+        Session::replace(&current, newer.clone());
+
+        // older connection finishes after the newer one.
+        assert!(worker.start(&newer, source(i16::MAX), Duration::ZERO));
+        assert!(!worker.start(&older, source(i16::MIN), Duration::ZERO));
+
+        assert_eq!(newer.state.get(), State::Playing);
+        assert_eq!(older.state.get(), State::default());
+        assert!(mixer.by_ref().take(100).all(|s| s > 0.99));
+
+        // stopped player doesn't start the connection finishing after the stop.
+        let stopped = Session::default();
+        Session::replace(&current, stopped.clone());
+        Session::replace(&current, Session::default());
+
+        assert!(!worker.start(&stopped, source(i16::MIN), Duration::ZERO));
+        assert_ne!(stopped.state.get(), State::Playing);
+    }
+}
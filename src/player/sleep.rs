@@ -0,0 +1,184 @@
+use std::time::{Duration, Instant};
+
+use super::Player;
+
+/// Stops playback after the selected duration, fading volume out over the last minute.
+/// Driven by [`SleepTimer::tick`].
+#[derive(Debug, Default)]
+pub struct SleepTimer {
+    deadline: Option<Instant>,
+    /// Fade in progress over the last minute.
+    fade: Option<Fade>,
+}
+
+/// Volume fade of the [`SleepTimer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fade {
+    /// Volume set by the user, it's faded from and restored once playback is stopped.
+    volume: i8,
+    /// Time left until the stop when fading from `volume` started.
+    length: Duration,
+    /// Volume set by the fade last, other one was set by the user.
+    set: i8,
+}
+
+impl Fade {
+    /// Moves the fade to the time left, it's restarted from the current volume
+    /// if the user changed it meanwhile.
+    fn advance(fade: Option<Self>, current: i8, remaining: Duration) -> Self {
+        let fade = match fade {
+            Some(fade) if fade.set == current => fade,
+            _ => Self {
+                volume: current,
+                length: remaining,
+                set: current,
+            },
+        };
+
+        Self {
+            set: fade_volume(fade.volume, remaining, fade.length),
+            ..fade
+        }
+    }
+}
+
+impl SleepTimer {
+    /// Durations selectable by [`SleepTimer::cycle`].
+    const DURATIONS: [Duration; 5] = [
+        Duration::from_mins(15),
+        Duration::from_mins(30),
+        Duration::from_mins(45),
+        Duration::from_hours(1),
+        Duration::from_mins(90),
+    ];
+
+    const FADE: Duration = Duration::from_mins(1);
+
+    /// Switches to the next selectable duration, turns timer off after the longest one.
+    pub fn cycle<P: Player>(&mut self, player: &P) {
+        let next = match self.selected() {
+            None => Some(Self::DURATIONS[0]),
+            Some(idx) => Self::DURATIONS.get(idx + 1).copied(),
+        };
+
+        self.restore_volume(player);
+        self.deadline = next.map(|d| Instant::now() + d);
+    }
+
+    /// Returns time left until playback is stopped.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
+
+    /// Fades volume or stops playback, returns `true` if playback was stopped.
+    pub fn tick<P: Player>(&mut self, player: &P) -> bool {
+        let Some(remaining) = self.remaining() else {
+            return false;
+        };
+
+        if remaining.is_zero() {
+            log::info!("sleep timer expired");
+
+            player.stop();
+            self.restore_volume(player);
+            self.deadline = None;
+
+            return true;
+        }
+
+        if remaining < Self::FADE {
+            let fade = Fade::advance(self.fade, player.volume(), remaining);
+            player.set_volume(fade.set);
+            self.fade = Some(fade);
+        }
+
+        false
+    }
+
+    /// Returns volume chosen by the user, the faded one isn't, e.g. to be stored.
+    pub fn user_volume<P: Player>(&self, player: &P) -> i8 {
+        let volume = player.volume();
+
+        match self.fade {
+            Some(fade) if fade.set == volume => fade.volume,
+            _ => volume,
+        }
+    }
+
+    /// Returns index of the selected duration, the closest longer one to the remaining time.
+    fn selected(&self) -> Option<usize> {
+        let remaining = self.remaining()?;
+
+        Self::DURATIONS.iter().position(|d| remaining <= *d)
+    }
+
+    fn restore_volume<P: Player>(&mut self, player: &P) {
+        if let Some(fade) = self.fade.take() {
+            player.set_volume(fade.volume);
+        }
+    }
+}
+
+/// Returns volume lowered proportionally to the time left of the fade.
+fn fade_volume(volume: i8, remaining: Duration, fade: Duration) -> i8 {
+    if fade.is_zero() {
+        return 0;
+    }
+
+    let ratio = remaining.as_secs_f32() / fade.as_secs_f32();
+
+    #[allow(clippy::cast_possible_truncation)]
+    let faded = (f32::from(volume) * ratio.min(1.0)).round() as i8;
+
+    faded
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{fade_volume, Fade};
+
+    #[test]
+    fn test_fade_volume() {
+        let fade = Duration::from_mins(1);
+        let test_data = [
+            (80, 60, 80),
+            (80, 30, 40),
+            (80, 1, 1),
+            (80, 0, 0),
+            (50, 90, 50),
+        ];
+
+        for (volume, remaining, want) in test_data {
+            assert_eq!(
+                fade_volume(volume, Duration::from_secs(remaining), fade),
+                want
+            );
+        }
+    }
+
+    #[test]
+    fn test_advance_fade() {
+        let secs = Duration::from_secs;
+        let fade = |volume, length, set| Fade {
+            volume,
+            length: secs(length),
+            set,
+        };
+
+        let test_data = [
+            // fade starts from the current volume.
+            (None, 80, 60, fade(80, 60, 80)),
+            (Some(fade(80, 60, 80)), 80, 30, fade(80, 60, 40)),
+            // volume changed by the user is faded from the time left.
+            (Some(fade(80, 60, 40)), 60, 30, fade(60, 30, 60)),
+            (Some(fade(60, 30, 60)), 60, 15, fade(60, 30, 30)),
+        ];
+
+        for (previous, current, remaining, want) in test_data {
+            assert_eq!(Fade::advance(previous, current, secs(remaining)), want);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use chrono::{Local, NaiveDateTime};
+
+use crate::http::HttpConfig;
+use crate::models::Schedule;
+use crate::player::{Capture, ReconnectPolicy, RecordOptions, State};
+use crate::storage::Storage;
+
+/// Records saved stations by their [Schedule]s over their own connections,
+/// so playback isn't affected. Driven by [`Scheduler::tick`], from the TUI loop or in headless mode.
+pub struct Scheduler<S: Storage> {
+    storage: S,
+    http: HttpConfig,
+    reconnect: ReconnectPolicy,
+    /// Scheduled recordings are skipped if `None`.
+    record: Option<RecordOptions>,
+    schedules: Vec<Schedule>,
+    loaded_at: Option<Instant>,
+    /// Recording windows being served by schedule ids, overlapping ones are recorded at once.
+    active: HashMap<i64, Active>,
+}
+
+/// Recording window being served.
+struct Active {
+    station: String,
+    end: NaiveDateTime,
+    /// `None` if the recording couldn't be started, it isn't retried within the window.
+    capture: Option<Capture>,
+}
+
+impl<S: Storage> Scheduler<S> {
+    /// Schedules are re-read periodically to catch changes made elsewhere.
+    const RELOAD_PERIOD: Duration = Duration::from_secs(30);
+
+    pub fn new(storage: S) -> Self {
+        Self {
+            storage,
+            http: HttpConfig::default(),
+            reconnect: ReconnectPolicy::default(),
+            record: None,
+            schedules: vec![],
+            loaded_at: None,
+            active: HashMap::new(),
+        }
+    }
+
+    /// Sets proxy, headers, timeouts and certificates of the recorded streams.
+    pub fn with_http(mut self, http: HttpConfig) -> Self {
+        self.http = http;
+
+        self
+    }
+
+    /// Sets how interrupted recordings are re-opened.
+    pub fn with_reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = policy;
+
+        self
+    }
+
+    /// Enables scheduled recordings.
+    pub fn with_recording(mut self, options: RecordOptions) -> Self {
+        self.record = Some(options);
+
+        self
+    }
+
+    /// Forces schedules to be re-read on the next tick.
+    pub fn reload(&mut self) {
+        self.loaded_at = None;
+    }
+
+    /// Starts, continues or finishes scheduled recordings, should be called every second.
+    pub async fn tick(&mut self) -> anyhow::Result<()> {
+        if self
+            .loaded_at
+            .is_none_or(|t| t.elapsed() >= Self::RELOAD_PERIOD)
+        {
+            self.schedules = self.storage.schedules().await?;
+            self.loaded_at = Some(Instant::now());
+        }
+
+        let now = Local::now().naive_local();
+        let (finished, started) = transitions(&self.schedules, &self.active, now);
+
+        for schedule_id in finished {
+            let Some(active) = self.active.remove(&schedule_id) else {
+                continue;
+            };
+
+            // recording is finished once the capture is dropped.
+            let path = active.capture.as_ref().and_then(Capture::path);
+            log::info!(
+                "scheduled recording of {} finished, saved to {:?}",
+                active.station,
+                path
+            );
+        }
+
+        for (schedule, end) in started {
+            let active = self.start(schedule, end).await;
+            self.active.insert(schedule.id, active);
+        }
+
+        Ok(())
+    }
+
+    async fn start(&self, schedule: &Schedule, end: NaiveDateTime) -> Active {
+        log::info!(
+            "scheduled recording of {} started, ends at {end}",
+            schedule.station.name
+        );
+
+        let station = schedule.station.name.trim().to_string();
+        let capture = self.capture(schedule, &station).await;
+
+        // window of the schedule is served anyway, so a failed recording isn't restarted each second.
+        Active {
+            station,
+            end,
+            capture: capture
+                .inspect_err(|e| log::error!("start scheduled recording: {e:?}"))
+                .ok(),
+        }
+    }
+
+    async fn capture(&self, schedule: &Schedule, station: &str) -> anyhow::Result<Capture> {
+        let record = self.record.clone().context("recording is disabled")?;
+        let headers = self
+            .storage
+            .station_headers(schedule.station.id)
+            .await
+            .context("station headers")?;
+
+        Capture::start(
+            &schedule.station.url,
+            self.http.with_headers(&headers),
+            self.reconnect,
+            record,
+            station,
+        )
+    }
+
+    /// Returns end time of the last scheduled recording in progress, `None` if none is.
+    pub fn recording_until(&self) -> Option<NaiveDateTime> {
+        self.active
+            .values()
+            .filter(|a| {
+                a.capture
+                    .as_ref()
+                    .is_some_and(|c| !matches!(c.state(), State::Failed(_)))
+            })
+            .map(|a| a.end)
+            .max()
+    }
+}
+
+/// Returns ids of the recordings to finish at `now`, since their window is over or
+/// their schedule is deleted, and schedules to start recording with their window ends.
+fn transitions<'a>(
+    schedules: &'a [Schedule],
+    active: &HashMap<i64, Active>,
+    now: NaiveDateTime,
+) -> (Vec<i64>, Vec<(&'a Schedule, NaiveDateTime)>) {
+    let mut finished: Vec<i64> = active
+        .iter()
+        .filter(|(id, a)| now >= a.end || !schedules.iter().any(|s| s.id == **id))
+        .map(|(id, _)| *id)
+        .collect();
+    finished.sort_unstable();
+
+    let started = schedules
+        .iter()
+        .filter(|s| !active.contains_key(&s.id))
+        .filter_map(|s| s.window(now).map(|(_, end)| (s, end)))
+        .collect();
+
+    (finished, started)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+    use crate::models::{Schedule, Station};
+
+    use super::{transitions, Active};
+
+    #[test]
+    fn test_transitions() {
+        // 2023-09-02 is Saturday.
+        let at = |time: &str| -> NaiveDateTime {
+            NaiveDate::from_ymd_opt(2023, 9, 2)
+                .unwrap()
+                .and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+        };
+
+        let schedules = [
+            new_schedule(1, "20:00-22:00"),
+            new_schedule(2, "21:00-23:00"),
+        ];
+
+        let test_data = [
+            // nothing is due before the windows.
+            (vec![], at("19:00"), vec![], vec![]),
+            // window opens.
+            (vec![], at("20:00"), vec![], vec![(1, at("22:00"))]),
+            // recording continues within the window.
+            (vec![(1, at("22:00"))], at("20:30"), vec![], vec![]),
+            // overlapping window starts while the other one is recorded.
+            (
+                vec![(1, at("22:00"))],
+                at("21:00"),
+                vec![],
+                vec![(2, at("23:00"))],
+            ),
+            // both windows are open, one is started late.
+            (
+                vec![],
+                at("21:30"),
+                vec![],
+                vec![(1, at("22:00")), (2, at("23:00"))],
+            ),
+            // window ends, the overlapping one continues.
+            (
+                vec![(1, at("22:00")), (2, at("23:00"))],
+                at("22:00"),
+                vec![1],
+                vec![],
+            ),
+            // schedule is deleted within its window.
+            (
+                vec![(3, at("22:00"))],
+                at("20:30"),
+                vec![3],
+                vec![(1, at("22:00"))],
+            ),
+        ];
+
+        for (active, now, want_finished, want_started) in test_data {
+            let active: HashMap<i64, Active> = active
+                .into_iter()
+                .map(|(id, end)| {
+                    let active = Active {
+                        station: String::new(),
+                        end,
+                        capture: None,
+                    };
+
+                    (id, active)
+                })
+                .collect();
+
+            let (finished, started) = transitions(&schedules, &active, now);
+            let started: Vec<(i64, NaiveDateTime)> =
+                started.into_iter().map(|(s, end)| (s.id, end)).collect();
+
+            assert_eq!(finished, want_finished, "{now}");
+            assert_eq!(started, want_started, "{now}");
+        }
+    }
+
+    fn new_schedule(id: i64, window: &str) -> Schedule {
+        let (start, end) = Schedule::parse_window(window).unwrap();
+
+        Schedule {
+            id,
+            station: Station {
+                id,
+                provider: String::new(),
+                provider_id: String::new(),
+                name: "Radio".to_string(),
+                url: String::new(),
+                codec: String::new(),
+                bitrate: 0,
+                tags: "".into(),
+                country: String::new(),
+                check: None,
+            },
+            weekdays: "sat".parse().unwrap(),
+            start,
+            end,
+        }
+    }
+}
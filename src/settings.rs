@@ -0,0 +1,81 @@
+use crate::player::{Device, OutputConfig, Player};
+use crate::storage::Storage;
+
+const VOLUME: &str = "volume";
+const MUTED: &str = "muted";
+/// Prefix of the output format keys, followed by the device id.
+const OUTPUT_CONFIG: &str = "output_config.";
+
+/// Applies volume and mute state stored by [`save_volume`], e.g. on start.
+pub async fn restore_volume<P: Player, S: Storage>(player: &P, storage: &S) -> anyhow::Result<()> {
+    // corrupt values are skipped, so they don't prevent the start.
+    if let Some(volume) = storage.setting(VOLUME).await? {
+        match volume.parse() {
+            Ok(volume) => player.set_volume(volume),
+            Err(e) => log::warn!("skip stored volume {volume:?}: {e}"),
+        }
+    }
+
+    if let Some(muted) = storage.setting(MUTED).await? {
+        match muted.parse() {
+            Ok(muted) => player.set_muted(muted),
+            Err(e) => log::warn!("skip stored mute state {muted:?}: {e}"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Stores volume and mute state chosen by the user, so they survive restarts.
+pub async fn save_volume<S: Storage>(
+    storage: &S,
+    volume: i8,
+    is_muted: bool,
+) -> anyhow::Result<()> {
+    storage.set_setting(VOLUME, &volume.to_string()).await?;
+    storage.set_setting(MUTED, &is_muted.to_string()).await
+}
+
+/// Applies output formats stored by [`save_output_config`], e.g. on start.
+/// Formats of the absent devices are applied once they are plugged in.
+pub async fn restore_output_configs<P: Player, S: Storage>(
+    player: &P,
+    storage: &S,
+) -> anyhow::Result<()> {
+    for (key, value) in storage.settings(OUTPUT_CONFIG).await? {
+        let device_id = &key[OUTPUT_CONFIG.len()..];
+
+        // empty value is stored for the device default.
+        if value.is_empty() {
+            continue;
+        }
+
+        let config = match value.parse() {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("skip stored output format {value:?} of {device_id}: {e}");
+                continue;
+            }
+        };
+
+        // device may not support the format anymore, e.g. if its driver changed.
+        if let Err(e) = player.set_output_config(device_id, Some(config)) {
+            log::warn!("restore output format of {device_id}: {e:?}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Stores output format chosen for the device, `None` stands for the device default.
+pub async fn save_output_config<S: Storage>(
+    storage: &S,
+    device: &Device,
+    config: Option<OutputConfig>,
+) -> anyhow::Result<()> {
+    let value = config.map(|c| c.to_string()).unwrap_or_default();
+
+    storage
+        .set_setting(&format!("{OUTPUT_CONFIG}{}", device.id()), &value)
+        .await
+}
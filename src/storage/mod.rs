@@ -1,6 +1,6 @@
 pub use sqlite::Sqlite;
 
-use crate::models::{Station, StationsFilter};
+use crate::models::{Alarm, EqPreset, Header, Schedule, Station, StationCheck, StationsFilter};
 
 mod sqlite;
 
@@ -12,8 +12,60 @@ pub trait Storage: Sync + Send {
     async fn search(&self, filter: &StationsFilter) -> anyhow::Result<Vec<Station>>;
 
     /// Update current [Station] in database.
+    #[allow(dead_code)]
     async fn update(&self, station: &Station) -> anyhow::Result<()>;
 
     /// Remove [Station] from database by id.
     async fn delete(&self, station_id: i64) -> anyhow::Result<()>;
+
+    /// Store new recording [Schedule] for a saved station and returns id.
+    async fn create_schedule(&self, schedule: &Schedule) -> anyhow::Result<i64>;
+
+    /// Returns all recording schedules with their stations.
+    async fn schedules(&self) -> anyhow::Result<Vec<Schedule>>;
+
+    /// Remove recording [Schedule] from database by id.
+    async fn delete_schedule(&self, schedule_id: i64) -> anyhow::Result<()>;
+
+    /// Store new [Alarm] for saved stations and returns id.
+    async fn create_alarm(&self, alarm: &Alarm) -> anyhow::Result<i64>;
+
+    /// Returns all alarms with their stations.
+    async fn alarms(&self) -> anyhow::Result<Vec<Alarm>>;
+
+    /// Remove [Alarm] from database by id.
+    async fn delete_alarm(&self, alarm_id: i64) -> anyhow::Result<()>;
+
+    /// Returns loudness normalization gain in dB remembered for the saved station.
+    async fn station_gain(&self, station_id: i64) -> anyhow::Result<Option<f32>>;
+
+    /// Remembers loudness normalization gain in dB for the saved station.
+    async fn set_station_gain(&self, station_id: i64, gain: f32) -> anyhow::Result<()>;
+
+    /// Stores result of the stream check of the saved station, replacing the previous one.
+    async fn set_station_check(&self, station_id: i64, check: &StationCheck) -> anyhow::Result<()>;
+
+    /// Returns HTTP headers sent with the stream requests of the saved station.
+    async fn station_headers(&self, station_id: i64) -> anyhow::Result<Vec<Header>>;
+
+    /// Replaces HTTP headers of the saved station, empty list removes them.
+    async fn set_station_headers(&self, station_id: i64, headers: &[Header]) -> anyhow::Result<()>;
+
+    /// Store user [`EqPreset`], replacing the one with the same name, and returns id.
+    async fn save_eq_preset(&self, preset: &EqPreset) -> anyhow::Result<i64>;
+
+    /// Returns all user equalizer presets ordered by name.
+    async fn eq_presets(&self) -> anyhow::Result<Vec<EqPreset>>;
+
+    /// Remove user [`EqPreset`] from database by id.
+    async fn delete_eq_preset(&self, preset_id: i64) -> anyhow::Result<()>;
+
+    /// Returns value of the setting, `None` if it was never stored.
+    async fn setting(&self, key: &str) -> anyhow::Result<Option<String>>;
+
+    /// Stores value of the setting, replacing the previous one.
+    async fn set_setting(&self, key: &str, value: &str) -> anyhow::Result<()>;
+
+    /// Returns keys and values of the settings which keys start with the prefix.
+    async fn settings(&self, prefix: &str) -> anyhow::Result<Vec<(String, String)>>;
 }
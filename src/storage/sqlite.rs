@@ -1,12 +1,14 @@
 use std::str::FromStr;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use futures::TryStreamExt;
-use sqlx::sqlite::{SqliteAutoVacuum, SqliteConnectOptions, SqlitePool};
+use sqlx::sqlite::{SqliteAutoVacuum, SqliteConnectOptions, SqlitePool, SqliteRow};
 use sqlx::types::chrono::{DateTime, Utc};
 use sqlx::{ConnectOptions, Row};
 
-use super::{Station, StationsFilter, Storage};
+use crate::models::Weekdays;
+
+use super::{Alarm, EqPreset, Header, Schedule, Station, StationCheck, StationsFilter, Storage};
 
 static MIGRATOR: sqlx::migrate::Migrator = sqlx::migrate!();
 
@@ -34,7 +36,7 @@ impl Storage for Sqlite {
     async fn create(&self, station: &Station) -> anyhow::Result<i64> {
         let now = DateTime::<Utc>::from(SystemTime::now());
         let id = sqlx::query(
-            r#"INSERT INTO radio_stations (
+            r"INSERT INTO radio_stations (
             created_at,
             updated_at,
             provider,
@@ -56,7 +58,7 @@ impl Storage for Sqlite {
             ?8,
             ?9,
             ?10
-        ) RETURNING id"#,
+        ) RETURNING id",
         )
         .bind(now)
         .bind(now)
@@ -77,7 +79,7 @@ impl Storage for Sqlite {
 
     async fn search(&self, _filter: &StationsFilter) -> anyhow::Result<Vec<Station>> {
         let mut rows = sqlx::query(
-            r#"SELECT
+            r"SELECT
                 id,
                 provider,
                 provider_id,
@@ -86,25 +88,21 @@ impl Storage for Sqlite {
                 codec,
                 bitrate,
                 tags,
-                country
-            FROM radio_stations"#,
+                country,
+                checked_at,
+                check_latency_ms,
+                check_error,
+                check_codec,
+                check_bitrate,
+                check_sample_rate
+            FROM radio_stations",
         )
         .fetch(&self.pool.clone());
 
         let mut result = vec![];
 
         while let Some(row) = rows.try_next().await? {
-            result.push(Station {
-                id: row.try_get("id")?,
-                provider: row.try_get("provider")?,
-                provider_id: row.try_get("provider_id")?,
-                name: row.try_get("name")?,
-                url: row.try_get("url")?,
-                codec: row.try_get("codec")?,
-                bitrate: row.try_get("bitrate")?,
-                tags: row.try_get::<'_, String, _>("tags")?.into(),
-                country: row.try_get("country")?,
-            });
+            result.push(station_from_row(&row, "")?);
         }
 
         Ok(result)
@@ -112,7 +110,7 @@ impl Storage for Sqlite {
 
     async fn update(&self, station: &Station) -> anyhow::Result<()> {
         sqlx::query(
-            r#"UPDATE radio_stations SET
+            r"UPDATE radio_stations SET
                 updated_at = ?1,
                 provider = ?2,
                 provider_id = ?3,
@@ -122,17 +120,17 @@ impl Storage for Sqlite {
                 bitrate = ?7,
                 tags = ?8,
                 country = ?9
-            WHERE id = ?10"#,
+            WHERE id = ?10",
         )
         .bind(DateTime::<Utc>::from(SystemTime::now()))
         .bind(station.provider.clone())
         .bind(station.provider_id.clone())
-        .bind(station.name.to_string())
-        .bind(station.url.to_string())
-        .bind(station.codec.to_string())
+        .bind(station.name.clone())
+        .bind(station.url.clone())
+        .bind(station.codec.clone())
         .bind(station.bitrate)
         .bind(station.tags.to_string())
-        .bind(station.country.to_string())
+        .bind(station.country.clone())
         .bind(station.id)
         .execute(&self.pool.clone())
         .await?;
@@ -148,13 +146,422 @@ impl Storage for Sqlite {
 
         Ok(())
     }
+
+    async fn create_schedule(&self, schedule: &Schedule) -> anyhow::Result<i64> {
+        let id = sqlx::query(
+            r"INSERT INTO recordings_schedule (
+            created_at,
+            station_id,
+            weekdays,
+            start_time,
+            end_time
+        ) VALUES (
+            ?1,
+            ?2,
+            ?3,
+            ?4,
+            ?5
+        ) RETURNING id",
+        )
+        .bind(DateTime::<Utc>::from(SystemTime::now()))
+        .bind(schedule.station.id)
+        .bind(schedule.weekdays.bits())
+        .bind(schedule.start)
+        .bind(schedule.end)
+        .fetch_one(&self.pool.clone())
+        .await?
+        .get("id");
+
+        Ok(id)
+    }
+
+    async fn schedules(&self) -> anyhow::Result<Vec<Schedule>> {
+        let mut rows = sqlx::query(
+            r"SELECT
+                s.id AS schedule_id,
+                s.weekdays,
+                s.start_time,
+                s.end_time,
+                r.id,
+                r.provider,
+                r.provider_id,
+                r.name,
+                r.url,
+                r.codec,
+                r.bitrate,
+                r.tags,
+                r.country,
+                r.checked_at,
+                r.check_latency_ms,
+                r.check_error,
+                r.check_codec,
+                r.check_bitrate,
+                r.check_sample_rate
+            FROM recordings_schedule s
+            JOIN radio_stations r ON r.id = s.station_id
+            ORDER BY s.start_time",
+        )
+        .fetch(&self.pool.clone());
+
+        let mut result = vec![];
+
+        while let Some(row) = rows.try_next().await? {
+            result.push(Schedule {
+                id: row.try_get("schedule_id")?,
+                station: station_from_row(&row, "")?,
+                weekdays: Weekdays::from_bits(row.try_get("weekdays")?),
+                start: row.try_get("start_time")?,
+                end: row.try_get("end_time")?,
+            });
+        }
+
+        Ok(result)
+    }
+
+    async fn delete_schedule(&self, schedule_id: i64) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM recordings_schedule WHERE id = ?1")
+            .bind(schedule_id)
+            .execute(&self.pool.clone())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn create_alarm(&self, alarm: &Alarm) -> anyhow::Result<i64> {
+        let id = sqlx::query(
+            r"INSERT INTO alarms (
+            created_at,
+            station_id,
+            fallback_station_id,
+            weekdays,
+            time,
+            volume,
+            ramp_secs
+        ) VALUES (
+            ?1,
+            ?2,
+            ?3,
+            ?4,
+            ?5,
+            ?6,
+            ?7
+        ) RETURNING id",
+        )
+        .bind(DateTime::<Utc>::from(SystemTime::now()))
+        .bind(alarm.station.id)
+        .bind(alarm.fallback.as_ref().map(|s| s.id))
+        .bind(alarm.weekdays.bits())
+        .bind(alarm.time)
+        .bind(alarm.volume)
+        .bind(i64::try_from(alarm.ramp.as_secs())?)
+        .fetch_one(&self.pool.clone())
+        .await?
+        .get("id");
+
+        Ok(id)
+    }
+
+    async fn alarms(&self) -> anyhow::Result<Vec<Alarm>> {
+        let mut rows = sqlx::query(
+            r"SELECT
+                a.id AS alarm_id,
+                a.weekdays,
+                a.time,
+                a.volume,
+                a.ramp_secs,
+                r.id,
+                r.provider,
+                r.provider_id,
+                r.name,
+                r.url,
+                r.codec,
+                r.bitrate,
+                r.tags,
+                r.country,
+                r.checked_at,
+                r.check_latency_ms,
+                r.check_error,
+                r.check_codec,
+                r.check_bitrate,
+                r.check_sample_rate,
+                f.id AS fallback_id,
+                f.provider AS fallback_provider,
+                f.provider_id AS fallback_provider_id,
+                f.name AS fallback_name,
+                f.url AS fallback_url,
+                f.codec AS fallback_codec,
+                f.bitrate AS fallback_bitrate,
+                f.tags AS fallback_tags,
+                f.country AS fallback_country,
+                f.checked_at AS fallback_checked_at,
+                f.check_latency_ms AS fallback_check_latency_ms,
+                f.check_error AS fallback_check_error,
+                f.check_codec AS fallback_check_codec,
+                f.check_bitrate AS fallback_check_bitrate,
+                f.check_sample_rate AS fallback_check_sample_rate
+            FROM alarms a
+            JOIN radio_stations r ON r.id = a.station_id
+            LEFT JOIN radio_stations f ON f.id = a.fallback_station_id
+            ORDER BY a.time",
+        )
+        .fetch(&self.pool.clone());
+
+        let mut result = vec![];
+
+        while let Some(row) = rows.try_next().await? {
+            let fallback = match row.try_get::<'_, Option<i64>, _>("fallback_id")? {
+                Some(_) => Some(station_from_row(&row, "fallback_")?),
+                None => None,
+            };
+
+            result.push(Alarm {
+                id: row.try_get("alarm_id")?,
+                station: station_from_row(&row, "")?,
+                fallback,
+                weekdays: Weekdays::from_bits(row.try_get("weekdays")?),
+                time: row.try_get("time")?,
+                volume: row.try_get("volume")?,
+                ramp: Duration::from_secs(row.try_get::<'_, i64, _>("ramp_secs")?.try_into()?),
+            });
+        }
+
+        Ok(result)
+    }
+
+    async fn delete_alarm(&self, alarm_id: i64) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM alarms WHERE id = ?1")
+            .bind(alarm_id)
+            .execute(&self.pool.clone())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn station_gain(&self, station_id: i64) -> anyhow::Result<Option<f32>> {
+        let gain = sqlx::query("SELECT gain FROM station_gains WHERE station_id = ?1")
+            .bind(station_id)
+            .fetch_optional(&self.pool.clone())
+            .await?
+            .map(|row| row.try_get("gain"))
+            .transpose()?;
+
+        Ok(gain)
+    }
+
+    async fn set_station_gain(&self, station_id: i64, gain: f32) -> anyhow::Result<()> {
+        sqlx::query(
+            r"INSERT INTO station_gains (station_id, updated_at, gain) VALUES (?1, ?2, ?3)
+            ON CONFLICT (station_id) DO UPDATE SET updated_at = ?2, gain = ?3",
+        )
+        .bind(station_id)
+        .bind(DateTime::<Utc>::from(SystemTime::now()))
+        .bind(gain)
+        .execute(&self.pool.clone())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn set_station_check(&self, station_id: i64, check: &StationCheck) -> anyhow::Result<()> {
+        sqlx::query(
+            r"UPDATE radio_stations SET
+                checked_at = ?1,
+                check_latency_ms = ?2,
+                check_error = ?3,
+                check_codec = ?4,
+                check_bitrate = ?5,
+                check_sample_rate = ?6
+            WHERE id = ?7",
+        )
+        .bind(check.checked_at)
+        .bind(i64::try_from(check.latency.as_millis())?)
+        .bind(check.error.clone())
+        .bind(check.codec.clone())
+        .bind(check.bitrate)
+        .bind(check.sample_rate)
+        .bind(station_id)
+        .execute(&self.pool.clone())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn station_headers(&self, station_id: i64) -> anyhow::Result<Vec<Header>> {
+        let mut rows = sqlx::query(
+            "SELECT name, value FROM station_headers WHERE station_id = ?1 ORDER BY rowid",
+        )
+        .bind(station_id)
+        .fetch(&self.pool.clone());
+
+        let mut result = vec![];
+
+        while let Some(row) = rows.try_next().await? {
+            result.push(Header {
+                name: row.try_get("name")?,
+                value: row.try_get("value")?,
+            });
+        }
+
+        Ok(result)
+    }
+
+    async fn set_station_headers(&self, station_id: i64, headers: &[Header]) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM station_headers WHERE station_id = ?1")
+            .bind(station_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for header in headers {
+            sqlx::query(
+                r"INSERT INTO station_headers (station_id, name, value) VALUES (?1, ?2, ?3)
+                ON CONFLICT (station_id, name) DO UPDATE SET value = ?3",
+            )
+            .bind(station_id)
+            .bind(header.name.clone())
+            .bind(header.value.clone())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn save_eq_preset(&self, preset: &EqPreset) -> anyhow::Result<i64> {
+        let id = sqlx::query(
+            r"INSERT INTO eq_presets (created_at, name, gains) VALUES (?1, ?2, ?3)
+            ON CONFLICT (name) DO UPDATE SET gains = ?3
+            RETURNING id",
+        )
+        .bind(DateTime::<Utc>::from(SystemTime::now()))
+        .bind(preset.name.clone())
+        .bind(preset.gains.to_string())
+        .fetch_one(&self.pool.clone())
+        .await?
+        .get("id");
+
+        Ok(id)
+    }
+
+    async fn eq_presets(&self) -> anyhow::Result<Vec<EqPreset>> {
+        let mut rows = sqlx::query("SELECT id, name, gains FROM eq_presets ORDER BY name")
+            .fetch(&self.pool.clone());
+
+        let mut result = vec![];
+
+        while let Some(row) = rows.try_next().await? {
+            result.push(EqPreset {
+                id: row.try_get("id")?,
+                name: row.try_get("name")?,
+                gains: row.try_get::<'_, String, _>("gains")?.parse()?,
+            });
+        }
+
+        Ok(result)
+    }
+
+    async fn delete_eq_preset(&self, preset_id: i64) -> anyhow::Result<()> {
+        sqlx::query("DELETE FROM eq_presets WHERE id = ?1")
+            .bind(preset_id)
+            .execute(&self.pool.clone())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn setting(&self, key: &str) -> anyhow::Result<Option<String>> {
+        let value = sqlx::query("SELECT value FROM settings WHERE key = ?1")
+            .bind(key)
+            .fetch_optional(&self.pool.clone())
+            .await?
+            .map(|row| row.try_get("value"))
+            .transpose()?;
+
+        Ok(value)
+    }
+
+    async fn set_setting(&self, key: &str, value: &str) -> anyhow::Result<()> {
+        sqlx::query(
+            r"INSERT INTO settings (key, updated_at, value) VALUES (?1, ?2, ?3)
+            ON CONFLICT (key) DO UPDATE SET updated_at = ?2, value = ?3",
+        )
+        .bind(key)
+        .bind(DateTime::<Utc>::from(SystemTime::now()))
+        .bind(value)
+        .execute(&self.pool.clone())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn settings(&self, prefix: &str) -> anyhow::Result<Vec<(String, String)>> {
+        // unlike LIKE, substr doesn't treat `_` and `%` of the prefix as wildcards.
+        let settings = sqlx::query(
+            "SELECT key, value FROM settings WHERE substr(key, 1, length(?1)) = ?1 ORDER BY key",
+        )
+        .bind(prefix)
+        .fetch_all(&self.pool.clone())
+        .await?
+        .into_iter()
+        .map(|row| Ok((row.try_get("key")?, row.try_get("value")?)))
+        .collect::<sqlx::Result<_>>()?;
+
+        Ok(settings)
+    }
+}
+
+/// Reads station from columns named with the prefix, used to join several stations.
+fn station_from_row(row: &SqliteRow, prefix: &str) -> sqlx::Result<Station> {
+    let column = |name: &str| format!("{prefix}{name}");
+
+    Ok(Station {
+        id: row.try_get(column("id").as_str())?,
+        provider: row.try_get(column("provider").as_str())?,
+        provider_id: row.try_get(column("provider_id").as_str())?,
+        name: row.try_get(column("name").as_str())?,
+        url: row.try_get(column("url").as_str())?,
+        codec: row.try_get(column("codec").as_str())?,
+        bitrate: row.try_get(column("bitrate").as_str())?,
+        tags: row
+            .try_get::<'_, String, _>(column("tags").as_str())?
+            .into(),
+        country: row.try_get(column("country").as_str())?,
+        check: check_from_row(row, prefix)?,
+    })
+}
+
+/// Reads result of the last stream check of the station, `None` if never checked.
+fn check_from_row(row: &SqliteRow, prefix: &str) -> sqlx::Result<Option<StationCheck>> {
+    let column = |name: &str| format!("{prefix}{name}");
+
+    let Some(checked_at) = row.try_get(column("checked_at").as_str())? else {
+        return Ok(None);
+    };
+
+    let latency_ms: i64 = row.try_get(column("check_latency_ms").as_str())?;
+
+    Ok(Some(StationCheck {
+        checked_at,
+        latency: Duration::from_millis(latency_ms.try_into().unwrap_or_default()),
+        error: row.try_get(column("check_error").as_str())?,
+        codec: row.try_get(column("check_codec").as_str())?,
+        bitrate: row.try_get(column("check_bitrate").as_str())?,
+        sample_rate: row.try_get(column("check_sample_rate").as_str())?,
+    }))
 }
 
 #[cfg(test)]
 mod tests {
-    use std::time::{SystemTime, UNIX_EPOCH};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use chrono::{NaiveTime, TimeZone, Utc};
 
-    use super::{Sqlite, Station, StationsFilter, Storage};
+    use super::{
+        Alarm, EqPreset, Header, Schedule, Sqlite, Station, StationCheck, StationsFilter, Storage,
+    };
 
     #[tokio::test]
     async fn create() {
@@ -212,6 +619,190 @@ mod tests {
         assert_eq!(stations, vec![]);
     }
 
+    #[tokio::test]
+    async fn schedules() {
+        let db = Sqlite::new(":memory:").await.unwrap();
+        let mut station = new_station(1);
+        station.id = db.create(&station).await.unwrap();
+
+        let (start, end) = Schedule::parse_window("20:00-22:00").unwrap();
+        let mut schedule = Schedule {
+            id: 0,
+            station: station.clone(),
+            weekdays: "sat,sun".parse().unwrap(),
+            start,
+            end,
+        };
+
+        schedule.id = db.create_schedule(&schedule).await.unwrap();
+        assert_eq!(db.schedules().await.unwrap(), vec![schedule.clone()]);
+
+        db.delete_schedule(schedule.id).await.unwrap();
+        assert_eq!(db.schedules().await.unwrap(), vec![]);
+
+        // schedules are removed with their station.
+        db.create_schedule(&schedule).await.unwrap();
+        db.delete(station.id).await.unwrap();
+        assert_eq!(db.schedules().await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn alarms() {
+        let db = Sqlite::new(":memory:").await.unwrap();
+        let mut station = new_station(1);
+        station.id = db.create(&station).await.unwrap();
+        let mut fallback = new_station(2);
+        fallback.id = db.create(&fallback).await.unwrap();
+
+        let mut alarm = Alarm {
+            id: 0,
+            station: station.clone(),
+            fallback: Some(fallback.clone()),
+            weekdays: "weekdays".parse().unwrap(),
+            time: NaiveTime::from_hms_opt(7, 30, 0).unwrap(),
+            volume: 40,
+            ramp: Duration::from_secs(90),
+        };
+
+        alarm.id = db.create_alarm(&alarm).await.unwrap();
+        assert_eq!(db.alarms().await.unwrap(), vec![alarm.clone()]);
+
+        // alarm stays without fallback when its station is removed.
+        db.delete(fallback.id).await.unwrap();
+        alarm.fallback = None;
+        assert_eq!(db.alarms().await.unwrap(), vec![alarm.clone()]);
+
+        db.delete_alarm(alarm.id).await.unwrap();
+        assert_eq!(db.alarms().await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn station_gains() {
+        let db = Sqlite::new(":memory:").await.unwrap();
+        let mut station = new_station(1);
+        station.id = db.create(&station).await.unwrap();
+
+        assert_eq!(db.station_gain(station.id).await.unwrap(), None);
+
+        for gain in [-3.5, 2.0] {
+            db.set_station_gain(station.id, gain).await.unwrap();
+            assert_eq!(db.station_gain(station.id).await.unwrap(), Some(gain));
+        }
+
+        // gain is removed with its station.
+        db.delete(station.id).await.unwrap();
+        assert_eq!(db.station_gain(station.id).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn station_checks() {
+        let db = Sqlite::new(":memory:").await.unwrap();
+        let mut station = new_station(1);
+        station.id = db.create(&station).await.unwrap();
+
+        let checks = [
+            StationCheck {
+                checked_at: Utc.with_ymd_and_hms(2023, 10, 14, 10, 0, 0).unwrap(),
+                latency: Duration::from_millis(350),
+                error: None,
+                codec: Some("MP3".to_string()),
+                bitrate: Some(128),
+                sample_rate: Some(44_100),
+            },
+            StationCheck {
+                checked_at: Utc.with_ymd_and_hms(2023, 10, 15, 10, 0, 0).unwrap(),
+                latency: Duration::from_secs(5),
+                error: Some("get http response".to_string()),
+                codec: None,
+                bitrate: None,
+                sample_rate: None,
+            },
+        ];
+
+        for check in checks {
+            db.set_station_check(station.id, &check).await.unwrap();
+            station.check = Some(check);
+
+            let stored = db.search(&StationsFilter::default()).await.unwrap();
+            assert_eq!(stored, vec![station.clone()]);
+        }
+
+        assert!(station.is_broken());
+    }
+
+    #[tokio::test]
+    async fn station_headers() {
+        let db = Sqlite::new(":memory:").await.unwrap();
+        let mut station = new_station(1);
+        station.id = db.create(&station).await.unwrap();
+
+        assert_eq!(db.station_headers(station.id).await.unwrap(), vec![]);
+
+        for value in [
+            "Referer: https://a.com/ | User-Agent: VLC",
+            "X-Token: 1",
+            "",
+        ] {
+            let headers = Header::parse_list(value).unwrap();
+
+            db.set_station_headers(station.id, &headers).await.unwrap();
+            assert_eq!(db.station_headers(station.id).await.unwrap(), headers);
+        }
+
+        // headers are removed with their station.
+        db.set_station_headers(station.id, &Header::parse_list("X: 1").unwrap())
+            .await
+            .unwrap();
+        db.delete(station.id).await.unwrap();
+        assert_eq!(db.station_headers(station.id).await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn eq_presets() {
+        let db = Sqlite::new(":memory:").await.unwrap();
+
+        let mut preset = EqPreset {
+            id: 0,
+            name: "Loud".to_string(),
+            gains: "6,4,2,0,0,0,0,2,4,6".parse().unwrap(),
+        };
+
+        preset.id = db.save_eq_preset(&preset).await.unwrap();
+        assert_eq!(db.eq_presets().await.unwrap(), vec![preset.clone()]);
+
+        // preset with the same name is replaced.
+        preset.gains = "1,1,1,1,1,1,1,1,1,1".parse().unwrap();
+        assert_eq!(db.save_eq_preset(&preset).await.unwrap(), preset.id);
+        assert_eq!(db.eq_presets().await.unwrap(), vec![preset.clone()]);
+
+        db.delete_eq_preset(preset.id).await.unwrap();
+        assert_eq!(db.eq_presets().await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn settings() {
+        let db = Sqlite::new(":memory:").await.unwrap();
+
+        assert_eq!(db.setting("volume").await.unwrap(), None);
+
+        for value in ["40", "75"] {
+            db.set_setting("volume", value).await.unwrap();
+            assert_eq!(db.setting("volume").await.unwrap().as_deref(), Some(value));
+        }
+
+        db.set_setting("output_config.hw", "48000 Hz")
+            .await
+            .unwrap();
+        db.set_setting("outputXconfig.hw", "44100 Hz")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            db.settings("output_config.").await.unwrap(),
+            vec![("output_config.hw".to_string(), "48000 Hz".to_string())]
+        );
+    }
+
     fn new_station(id: i64) -> Station {
         let now_secs = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -228,6 +819,7 @@ mod tests {
             bitrate: id.try_into().expect("unexpected u32 overflow"),
             tags: "a,b,c,d,e,f".into(),
             country: format!("country_{now_secs}_{id}"),
+            check: None,
         }
     }
 }
@@ -0,0 +1,154 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::NaiveTime;
+use crossterm::event::KeyCode;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Rect};
+use tui::text::Span;
+use tui::widgets::{Cell, Row};
+use tui::Frame;
+
+use crate::models::{Alarm, Station, StationsFilter, Weekdays};
+use crate::storage::Storage;
+
+use super::{Component, StationForm};
+
+pub struct Alarms<'a, S: Storage> {
+    storage: S,
+    form: StationForm<'a, Alarm>,
+    /// Volume the new alarm ramps up to.
+    volume: i8,
+}
+
+impl<S: Storage> Alarms<'_, S> {
+    const RAMP: Duration = Duration::from_mins(1);
+
+    pub fn new(storage: S) -> Self {
+        let form = StationForm::new(
+            "Alarms",
+            "Alarm",
+            "days, time and optional fallback station, e.g. \"weekdays 07:30 jazz\"",
+            &[
+                Constraint::Percentage(60),
+                Constraint::Percentage(20),
+                Constraint::Percentage(10),
+                Constraint::Percentage(10),
+            ],
+            |a: &Alarm| {
+                let mut station = format!("⏰ {}", a.station.name.trim());
+
+                if let Some(ref fallback) = a.fallback {
+                    station = format!("{station} (or {})", fallback.name.trim());
+                }
+
+                Row::new(vec![
+                    Cell::from(Span::raw(station)),
+                    Cell::from(Span::raw(a.weekdays.to_string())),
+                    Cell::from(Span::raw(a.time.format("%H:%M").to_string())),
+                    Cell::from(Span::raw(format!("{}%", a.volume))),
+                ])
+            },
+        );
+
+        Self {
+            storage,
+            form,
+            volume: 0,
+        }
+    }
+
+    /// Reads alarms from the storage.
+    pub async fn refresh(&mut self) -> anyhow::Result<()> {
+        let alarms = self.storage.alarms().await?;
+        self.form.set_list(alarms);
+
+        Ok(())
+    }
+
+    /// Starts typing a new alarm for the saved station, ramping volume up to the given one.
+    pub fn start_adding(&mut self, station: Station, volume: i8) {
+        self.volume = volume;
+        self.form.start_adding(station);
+    }
+
+    /// Reports if a new alarm is being typed, all keys go to the input then.
+    pub fn is_editing(&self) -> bool {
+        self.form.is_editing()
+    }
+
+    /// Handles key while typing, returns `true` if an alarm was stored.
+    pub async fn handle_input(&mut self, key: KeyCode) -> anyhow::Result<bool> {
+        let Some(input) = self.form.handle_input(key) else {
+            return Ok(false);
+        };
+
+        let (weekdays, time, fallback) = parse_input(&input.text)?;
+
+        let fallback = match fallback {
+            Some(name) => Some(self.find_station(name).await?),
+            None => None,
+        };
+
+        let alarm = Alarm {
+            id: 0,
+            station: input.station,
+            fallback,
+            weekdays,
+            time,
+            volume: self.volume,
+            ramp: Self::RAMP,
+        };
+
+        self.storage.create_alarm(&alarm).await?;
+        self.form.finish_adding();
+        self.refresh().await?;
+
+        Ok(true)
+    }
+
+    pub async fn handle_delete(&mut self) -> anyhow::Result<()> {
+        let alarm = self.form.get_selected().context("not selected")?;
+
+        self.storage.delete_alarm(alarm.id).await?;
+        self.refresh().await
+    }
+
+    pub fn handle_up(&mut self) {
+        self.form.handle_up();
+    }
+
+    pub fn handle_down(&mut self) {
+        self.form.handle_down();
+    }
+
+    /// Finds saved station which name contains the given one.
+    async fn find_station(&self, name: &str) -> anyhow::Result<Station> {
+        let name = name.to_lowercase();
+
+        self.storage
+            .search(&StationsFilter::default())
+            .await?
+            .into_iter()
+            .find(|s| s.name.to_lowercase().contains(&name))
+            .with_context(|| format!("saved station {name} not found"))
+    }
+}
+
+impl<S: Storage> Component for Alarms<'_, S> {
+    fn draw<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        self.form.draw(frame, area);
+    }
+}
+
+/// Splits input like `mon,fri 07:30 jazz` into days, time and fallback station name.
+fn parse_input(text: &str) -> anyhow::Result<(Weekdays, NaiveTime, Option<&str>)> {
+    let mut parts = text.trim().splitn(3, ' ');
+
+    let weekdays = parts.next().unwrap_or_default().parse()?;
+    let time = parts.next().context("time must follow days")?;
+    let time = NaiveTime::parse_from_str(time, "%H:%M")?;
+    let fallback = parts.next().map(str::trim).filter(|s| !s.is_empty());
+
+    Ok((weekdays, time, fallback))
+}
@@ -0,0 +1,232 @@
+use anyhow::Context;
+use crossterm::event::KeyCode;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, BorderType, Borders, Paragraph};
+use tui::Frame;
+
+use crate::models::{EqGains, EqPreset};
+use crate::storage::Storage;
+
+use super::Component;
+
+/// Bands of the graphic equalizer adjusted with arrows, with built-in and user presets.
+pub struct Equalizer<S: Storage> {
+    storage: S,
+    gains: EqGains,
+    /// Selected band.
+    band: usize,
+    /// Built-in presets followed by the user ones.
+    presets: Vec<EqPreset>,
+    /// Preset matching the gains.
+    preset: Option<usize>,
+    /// Name of the user preset being typed.
+    input: Option<String>,
+}
+
+impl<S: Storage> Equalizer<S> {
+    pub fn new(storage: S, gains: EqGains) -> Self {
+        let mut equalizer = Self {
+            storage,
+            gains,
+            band: 0,
+            presets: EqPreset::builtin(),
+            preset: None,
+            input: None,
+        };
+
+        equalizer.match_preset();
+        equalizer
+    }
+
+    pub fn gains(&self) -> EqGains {
+        self.gains
+    }
+
+    /// Reads user presets from the storage.
+    pub async fn refresh(&mut self) -> anyhow::Result<()> {
+        let mut presets = EqPreset::builtin();
+        presets.extend(self.storage.eq_presets().await?);
+
+        self.presets = presets;
+        self.match_preset();
+
+        Ok(())
+    }
+
+    pub fn handle_up(&mut self) {
+        self.band = self.band.saturating_sub(1);
+    }
+
+    pub fn handle_down(&mut self) {
+        self.band = (self.band + 1).min(self.gains.bands().count() - 1);
+    }
+
+    /// Lowers gain of the selected band by 1 dB.
+    pub fn handle_left(&mut self) {
+        self.gains.set(self.band, self.gains.get(self.band) - 1);
+        self.match_preset();
+    }
+
+    /// Raises gain of the selected band by 1 dB.
+    pub fn handle_right(&mut self) {
+        self.gains.set(self.band, self.gains.get(self.band) + 1);
+        self.match_preset();
+    }
+
+    /// Applies the next preset.
+    pub fn handle_enter(&mut self) {
+        let next = self.preset.map_or(0, |i| (i + 1) % self.presets.len());
+
+        if let Some(preset) = self.presets.get(next) {
+            self.gains = preset.gains;
+            self.preset = Some(next);
+        }
+    }
+
+    /// Starts typing name of a user preset for the current gains.
+    pub fn start_saving(&mut self) {
+        self.input = Some(String::new());
+    }
+
+    /// Reports if a preset name is being typed, all keys go to the input then.
+    pub fn is_editing(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// Handles key while typing, stores the preset on Enter.
+    pub async fn handle_input(&mut self, key: KeyCode) -> anyhow::Result<()> {
+        let Some(ref mut input) = self.input else {
+            return Ok(());
+        };
+
+        match key {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Enter => self.save().await?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    async fn save(&mut self) -> anyhow::Result<()> {
+        let name = self.input.as_deref().context("not editing")?.trim();
+
+        anyhow::ensure!(!name.is_empty(), "preset name is empty");
+        anyhow::ensure!(
+            !EqPreset::builtin().iter().any(|p| p.name == name),
+            "built-in preset {name} can't be replaced"
+        );
+
+        let preset = EqPreset {
+            id: 0,
+            name: name.to_string(),
+            gains: self.gains,
+        };
+
+        self.storage.save_eq_preset(&preset).await?;
+        self.input = None;
+        self.refresh().await
+    }
+
+    /// Removes the applied user preset.
+    pub async fn handle_delete(&mut self) -> anyhow::Result<()> {
+        let preset = self
+            .preset
+            .and_then(|i| self.presets.get(i))
+            .filter(|p| !p.is_builtin())
+            .context("user preset not selected")?;
+
+        self.storage.delete_eq_preset(preset.id).await?;
+        self.refresh().await
+    }
+
+    fn match_preset(&mut self) {
+        self.preset = self.presets.iter().position(|p| p.gains == self.gains);
+    }
+
+    fn band_spans(&self, band: usize, frequency: u32, gain: i8) -> Spans<'_> {
+        let label = if frequency >= 1000 {
+            format!("{} kHz", frequency / 1000)
+        } else {
+            format!("{frequency} Hz")
+        };
+
+        let slider: String = (-EqGains::MAX..=EqGains::MAX)
+            .map(|step| match step {
+                0 => '┃',
+                _ if (step > 0 && step <= gain) || (step < 0 && step >= gain) => '█',
+                _ => '·',
+            })
+            .collect();
+
+        let style = if band == self.band {
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+
+        Spans::from(Span::styled(
+            format!("{label:>7}  {slider}  {gain:+3} dB"),
+            style,
+        ))
+    }
+}
+
+impl<S: Storage> Component for Equalizer<S> {
+    fn draw<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Min(1), Constraint::Length(4)])
+            .split(area);
+
+        let bands: Vec<Spans<'_>> = self
+            .gains
+            .bands()
+            .enumerate()
+            .map(|(band, (frequency, gain))| self.band_spans(band, frequency, gain))
+            .collect();
+
+        let preset = self
+            .preset
+            .and_then(|i| self.presets.get(i))
+            .map_or("Custom", |p| p.name.as_str());
+
+        let paragraph = Paragraph::new(bands).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(format!("Equalizer: {preset}")),
+        );
+
+        frame.render_widget(paragraph, layout[0]);
+
+        let text = match self.input {
+            Some(ref input) => vec![
+                Spans::from(format!("{input}▏")),
+                Spans::from("preset name (Enter to save, Esc to cancel)"),
+            ],
+            None => vec![
+                Spans::from("↑↓ select band, ←→ adjust gain"),
+                Spans::from("Enter next preset, s save preset, Del delete preset"),
+            ],
+        };
+
+        let paragraph = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title("Presets"),
+        );
+
+        frame.render_widget(paragraph, layout[1]);
+    }
+}
@@ -1,14 +1,15 @@
 use anyhow::Context;
 
+use crossterm::event::KeyCode;
 use tui::backend::Backend;
-use tui::layout::{Constraint, Rect};
+use tui::layout::{Constraint, Direction, Layout, Rect};
 use tui::style::{Color, Modifier, Style};
-use tui::text::Span;
-use tui::widgets::{Block, BorderType, Borders, Cell, Row};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, BorderType, Borders, Cell, Paragraph, Row};
 use tui::Frame;
 
 use crate::api::Client;
-use crate::models::{Station, StationsFilter};
+use crate::models::{Header, Station, StationsFilter};
 use crate::storage::Storage;
 
 use super::{Component, Styles, Table};
@@ -20,9 +21,17 @@ pub struct Library<'a, S: Storage, C: Client> {
 
     station_table: Table<'a, Station>,
     station_filter: StationsFilter,
+
+    input: Option<HeadersInput>,
+}
+
+/// HTTP headers of a saved station being typed by the user.
+struct HeadersInput {
+    station: Station,
+    text: String,
 }
 
-impl<'a, S: Storage, C: Client> Library<'a, S, C> {
+impl<S: Storage, C: Client> Library<'_, S, C> {
     pub fn new(storage: S, client: C) -> Self
     where
         S: Clone,
@@ -53,12 +62,22 @@ impl<'a, S: Storage, C: Client> Library<'a, S, C> {
         let station_table = Table::<Station>::new(
             vec![],
             |s| {
-                Row::new(vec![
-                    Cell::from(Span::raw(format!("🔈 {}", s.name.trim()))),
+                let row = Row::new(vec![
+                    Cell::from(Span::raw(format!(
+                        "{} {}",
+                        if s.is_broken() { "✖" } else { "🔈" },
+                        s.name.trim()
+                    ))),
                     Cell::from(Span::raw(s.country.as_str())),
                     Cell::from(Span::raw(s.codec.as_str())),
                     Cell::from(Span::raw(s.bitrate.to_string())),
-                ])
+                ]);
+
+                if s.is_broken() {
+                    row.style(Style::default().fg(Color::Red))
+                } else {
+                    row
+                }
             },
             Styles::default(),
         )
@@ -70,6 +89,7 @@ impl<'a, S: Storage, C: Client> Library<'a, S, C> {
             datasource_is_active: false,
             station_table,
             station_filter: StationsFilter::default(),
+            input: None,
         }
     }
 
@@ -131,6 +151,67 @@ impl<'a, S: Storage, C: Client> Library<'a, S, C> {
         Ok(())
     }
 
+    /// Starts typing HTTP headers of the selected saved station, starting from the stored ones.
+    pub async fn start_editing_headers(&mut self) -> anyhow::Result<()> {
+        let Some(station) = self.get_selected_saved().cloned() else {
+            return Ok(());
+        };
+
+        let headers = self.storage.station_headers(station.id).await?;
+
+        self.input = Some(HeadersInput {
+            station,
+            text: Header::join_list(&headers),
+        });
+
+        Ok(())
+    }
+
+    /// Reports if headers are being typed, all keys go to the input then.
+    pub fn is_editing(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// Handles key while typing headers, they are stored on Enter.
+    pub async fn handle_input(&mut self, key: KeyCode) -> anyhow::Result<()> {
+        let Some(ref mut input) = self.input else {
+            return Ok(());
+        };
+
+        match key {
+            KeyCode::Char(c) => input.text.push(c),
+            KeyCode::Backspace => {
+                input.text.pop();
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Enter => {
+                let headers = Header::parse_list(&input.text)?;
+
+                self.storage
+                    .set_station_headers(input.station.id, &headers)
+                    .await?;
+                self.input = None;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the listed saved station with the same id, e.g. once it's checked.
+    pub fn update_station(&mut self, station: Station) {
+        if !matches!(
+            self.datasource_table.get_selected(),
+            Some(Datasource::Storage(_))
+        ) {
+            return;
+        }
+
+        if let Some(listed) = self.station_table.iter_mut().find(|s| s.id == station.id) {
+            *listed = station;
+        }
+    }
+
     pub fn get_selected(&self) -> Option<&Station> {
         if self.datasource_is_active {
             self.station_table.get_selected()
@@ -139,6 +220,14 @@ impl<'a, S: Storage, C: Client> Library<'a, S, C> {
         }
     }
 
+    /// Returns selected station if it is stored in the library.
+    pub fn get_selected_saved(&self) -> Option<&Station> {
+        match self.datasource_table.get_selected() {
+            Some(Datasource::Storage(_)) => self.get_selected(),
+            _ => None,
+        }
+    }
+
     fn draw_stations<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
         let rows = self.station_table.build_rows();
 
@@ -177,13 +266,40 @@ impl<'a, S: Storage, C: Client> Library<'a, S, C> {
     }
 }
 
-impl<'a, S: Storage, C: Client> Component for Library<'a, S, C> {
+impl<S: Storage, C: Client> Component for Library<'_, S, C> {
     fn draw<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
-        if self.datasource_is_active {
-            self.draw_stations(frame, area);
-        } else {
+        if !self.datasource_is_active {
             self.datasource_table.draw(frame, area);
+            return;
+        }
+
+        let Some(ref input) = self.input else {
+            self.draw_stations(frame, area);
+            return;
         };
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Min(1), Constraint::Length(4)])
+            .split(area);
+
+        self.draw_stations(frame, layout[0]);
+
+        let text = vec![
+            Spans::from(format!("{}▏", input.text)),
+            Spans::from(
+                "e.g. \"Referer: https://example.com/ | User-Agent: VLC\" (Enter to save, Esc to cancel)",
+            ),
+        ];
+
+        let paragraph = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(format!("Headers of {}", input.station.name.trim())),
+        );
+
+        frame.render_widget(paragraph, layout[1]);
     }
 }
 
@@ -0,0 +1,85 @@
+use tui::backend::Backend;
+use tui::layout::{Alignment, Rect};
+use tui::style::{Color, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, BorderType, Borders, Paragraph};
+use tui::Frame;
+
+use crate::player::Levels;
+
+use super::Component;
+
+/// VU meter and spectrum bars of the played audio.
+#[derive(Default)]
+pub struct Meter {
+    levels: Levels,
+}
+
+impl Meter {
+    /// Width fitting the spectrum bars with borders.
+    pub const WIDTH: u16 = 34;
+
+    /// Eighths of a character cell, from empty to full.
+    const BLOCKS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    /// Cells of the VU meter in the title.
+    const VU_CELLS: u8 = 16;
+
+    pub fn set_levels(&mut self, levels: Levels) {
+        self.levels = levels;
+    }
+
+    fn get_title(&self) -> String {
+        let filled = level_ratio(self.levels.rms()) * f32::from(Self::VU_CELLS);
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let filled = (filled.round() as usize).min(usize::from(Self::VU_CELLS));
+
+        format!(
+            "{}{} {:+3.0} dB",
+            "▮".repeat(filled),
+            "▯".repeat(usize::from(Self::VU_CELLS) - filled),
+            self.levels.peak()
+        )
+    }
+
+    /// Renders two rows of spectrum bars, two cells per band.
+    fn get_text(&self) -> Vec<Spans<'_>> {
+        let mut rows = [String::new(), String::new()];
+
+        for &band in self.levels.bands() {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let eighths = (band * 16.0).round() as usize;
+
+            let top = Self::BLOCKS[eighths.saturating_sub(8).min(8)];
+            let bottom = Self::BLOCKS[eighths.min(8)];
+
+            rows[0].extend([top, top]);
+            rows[1].extend([bottom, bottom]);
+        }
+
+        rows.into_iter()
+            .map(|row| Spans::from(Span::styled(row, Style::default().fg(Color::Green))))
+            .collect()
+    }
+}
+
+impl Component for Meter {
+    fn draw<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let paragraph = Paragraph::new(self.get_text())
+            .block(
+                Block::default()
+                    .title(self.get_title())
+                    .borders(Borders::LEFT | Borders::TOP | Borders::RIGHT)
+                    .border_type(BorderType::Rounded),
+            )
+            .alignment(Alignment::Center);
+
+        frame.render_widget(paragraph, area);
+    }
+}
+
+/// Maps level in dBFS to the part of the meter filled, in range [0 - 1].
+fn level_ratio(db: f32) -> f32 {
+    ((db - Levels::FLOOR) / -Levels::FLOOR).clamp(0.0, 1.0)
+}
@@ -4,12 +4,22 @@ use tui::style::Style;
 use tui::widgets::Block;
 use tui::Frame;
 
+pub use alarms::Alarms;
+pub use equalizer::Equalizer;
 pub use library::Library;
+pub use meter::Meter;
 pub use playbar::Playbar;
+pub use schedules::Schedules;
+pub use station_form::StationForm;
 pub use table::Table;
 
+mod alarms;
+mod equalizer;
 mod library;
+mod meter;
 mod playbar;
+mod schedules;
+mod station_form;
 mod table;
 
 pub trait Component {
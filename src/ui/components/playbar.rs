@@ -1,3 +1,7 @@
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDateTime;
+
 use tui::backend::Backend;
 use tui::layout::{Alignment, Rect};
 use tui::text::Spans;
@@ -5,54 +9,193 @@ use tui::widgets::{Block, BorderType, Borders, Paragraph};
 use tui::Frame;
 
 use crate::models::Station;
-use crate::player::{Device, Player};
+use crate::player::{BufferLevel, Device, Metadata, Player, Position, State};
 
 use super::Component;
 
 pub struct Playbar {
     is_paused: bool,
+    state: State,
+    connecting_since: Option<Instant>,
     volume: i8,
+    is_muted: bool,
     device: String,
-    station: Option<String>,
+    station: Option<Station>,
+    metadata: Option<Metadata>,
+    is_recording: bool,
+    recording_until: Option<NaiveDateTime>,
+    position: Option<Position>,
+    sleep_remaining: Option<Duration>,
+    /// Loudness normalization gain in dB, `None` if disabled.
+    gain: Option<f32>,
+    buffer: Option<BufferLevel>,
+    /// Message shown in the title for a while, with the time it was set.
+    notice: Option<(String, Instant)>,
 }
 
 impl Playbar {
+    const NOTICE_PERIOD: Duration = Duration::from_secs(5);
+
     pub fn new<P: Player>(player: &P) -> Self {
         Self {
             is_paused: player.is_paused(),
+            state: player.state(),
+            connecting_since: None,
             volume: player.volume(),
+            is_muted: player.is_muted(),
             device: Self::device_name(player),
             station: None,
+            metadata: None,
+            is_recording: player.recording().is_some(),
+            recording_until: None,
+            position: player.position(),
+            sleep_remaining: None,
+            gain: player.is_normalizing().then(|| player.gain()),
+            buffer: player.buffer(),
+            notice: None,
         }
     }
 
     pub fn set_player_settings<P: Player>(&mut self, player: &P) {
         self.is_paused = player.is_paused();
+        self.state = player.state();
+        self.connecting_since = match self.state {
+            State::Connecting => self.connecting_since.or_else(|| Some(Instant::now())),
+            _ => None,
+        };
         self.volume = player.volume();
+        self.is_muted = player.is_muted();
         self.device = Self::device_name(player);
+        self.metadata = player.metadata();
+        self.is_recording = player.recording().is_some();
+        self.position = player.position();
+        self.gain = player.is_normalizing().then(|| player.gain());
+        self.buffer = player.buffer();
     }
 
     pub fn set_station(&mut self, station: Option<&Station>) {
-        self.station = station.map(|s| s.name.trim().to_string());
+        self.station = station.cloned();
+        self.metadata = None;
+    }
+
+    /// Sets end of the scheduled recording.
+    pub fn set_recording_until(&mut self, until: Option<NaiveDateTime>) {
+        self.recording_until = until;
+    }
+
+    /// Sets time left until the sleep timer stops playback.
+    pub fn set_sleep_remaining(&mut self, remaining: Option<Duration>) {
+        self.sleep_remaining = remaining;
+    }
+
+    /// Shows message in the title for [`Self::NOTICE_PERIOD`].
+    pub fn set_notice(&mut self, notice: impl Into<String>) {
+        self.notice = Some((notice.into(), Instant::now()));
+    }
+
+    /// Name of the station being played.
+    pub fn station(&self) -> Option<&str> {
+        self.station.as_ref().map(|s| s.name.trim())
+    }
+
+    /// Station being played.
+    pub fn playing(&self) -> Option<&Station> {
+        self.station.as_ref()
     }
 
     fn get_title(&self) -> String {
-        format!(
-            "{:-7} ({} | Volume: {:-2}%)",
-            if self.is_paused || self.station.is_none() {
-                "Paused"
-            } else {
-                "Playing"
+        let volume = if self.is_muted {
+            "muted".to_string()
+        } else {
+            format!("{:-2}%", self.volume)
+        };
+
+        let mut title = format!("{:-7} ({} | Volume: {volume})", self.status(), self.device);
+
+        // less than a second is the usual download lead, not a delay.
+        if let Some(position) = self.position.filter(|p| p.behind_live().as_secs() > 0) {
+            title = format!(
+                "{title} -{} of {}",
+                format_duration(position.behind_live()),
+                format_duration(position.duration())
+            );
+        }
+
+        if let Some(buffer) = self.buffer.filter(|b| !b.is_buffering()) {
+            title = format!("{title} Buffer {:.1}s", buffer.buffered().as_secs_f32());
+        }
+
+        if let Some(gain) = self.gain {
+            title = format!("{title} Norm {gain:+.1}dB");
+        }
+
+        if let Some(remaining) = self.sleep_remaining {
+            title = format!("{title} Sleep {}", format_duration(remaining));
+        }
+
+        if let Some((ref notice, since)) = self.notice {
+            if since.elapsed() < Self::NOTICE_PERIOD {
+                title = format!("{title} {notice}");
+            }
+        }
+
+        if self.is_recording {
+            title.push_str(" ● REC");
+        }
+
+        // scheduled recording is made apart from the playback.
+        if let Some(until) = self.recording_until {
+            title = format!("{title} ● Scheduled REC until {}", until.format("%H:%M"));
+        }
+
+        title
+    }
+
+    fn status(&self) -> String {
+        match self.state {
+            _ if self.station.is_none() => "Paused".to_string(),
+            State::Connecting => format!(
+                "Connecting {}s",
+                self.connecting_since.map_or(0, |t| t.elapsed().as_secs())
+            ),
+            State::Reconnecting {
+                attempt,
+                max_retries,
+            } => format!("Reconnecting {attempt}/{max_retries}"),
+            State::Failed(_) => "Failed".to_string(),
+            State::Stopped => "Stopped".to_string(),
+            State::Playing if self.is_paused => "Paused".to_string(),
+            State::Playing => match self.buffer.filter(BufferLevel::is_buffering) {
+                Some(buffer) => format!("Buffering {:.0}%", buffer_progress(buffer)),
+                None => "Playing".to_string(),
             },
-            self.device,
-            self.volume
-        )
+        }
     }
 
-    fn get_text(&self) -> Vec<Spans> {
-        self.station.as_ref().map_or_else(Vec::new, |station| {
-            vec![Spans::from(format!("Station: {}", station.trim()))]
-        })
+    fn get_text(&self) -> Vec<Spans<'_>> {
+        let Some(ref station) = self.station else {
+            return vec![];
+        };
+
+        let mut text = vec![Spans::from(format!("Station: {}", station.name.trim()))];
+
+        if let State::Failed(ref error) = self.state {
+            text.push(Spans::from(format!("Error:   {error}")));
+        } else if let Some(ref metadata) = self.metadata {
+            let title = metadata.title().map(|t| t.replace(" - ", " – "));
+
+            // page of the station or track, announced along with the title or instead of it.
+            let line = match (title, metadata.url()) {
+                (Some(title), Some(url)) => format!("Track:   {title} ({url})"),
+                (Some(title), None) => format!("Track:   {title}"),
+                (None, Some(url)) => format!("Link:    {url}"),
+                (None, None) => return text,
+            };
+
+            text.push(Spans::from(line));
+        }
+
+        text
     }
 
     fn device_name<P: Player>(player: &P) -> String {
@@ -64,6 +207,12 @@ impl Playbar {
     }
 }
 
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
 impl Component for Playbar {
     fn draw<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
         let paragraph = Paragraph::new(self.get_text())
@@ -78,3 +227,12 @@ impl Component for Playbar {
         frame.render_widget(paragraph, area);
     }
 }
+
+/// Returns downloaded part of the pre-roll in percents.
+fn buffer_progress(buffer: BufferLevel) -> f32 {
+    if buffer.pre_roll().is_zero() {
+        return 100.0;
+    }
+
+    (buffer.buffered().as_secs_f32() / buffer.pre_roll().as_secs_f32() * 100.0).min(100.0)
+}
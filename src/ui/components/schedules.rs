@@ -0,0 +1,118 @@
+use anyhow::Context;
+use crossterm::event::KeyCode;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Rect};
+use tui::text::Span;
+use tui::widgets::{Cell, Row};
+use tui::Frame;
+
+use crate::models::{Schedule, Station, Weekdays};
+use crate::storage::Storage;
+
+use super::{Component, StationForm};
+
+pub struct Schedules<'a, S: Storage> {
+    storage: S,
+    form: StationForm<'a, Schedule>,
+}
+
+impl<S: Storage> Schedules<'_, S> {
+    pub fn new(storage: S) -> Self {
+        let form = StationForm::new(
+            "Schedules",
+            "Record",
+            "days and time, e.g. \"sat,sun 20:00-22:00\"",
+            &[
+                Constraint::Percentage(60),
+                Constraint::Percentage(25),
+                Constraint::Percentage(15),
+            ],
+            |s: &Schedule| {
+                Row::new(vec![
+                    Cell::from(Span::raw(format!("⏺ {}", s.station.name.trim()))),
+                    Cell::from(Span::raw(s.weekdays.to_string())),
+                    Cell::from(Span::raw(format!(
+                        "{}–{}",
+                        s.start.format("%H:%M"),
+                        s.end.format("%H:%M")
+                    ))),
+                ])
+            },
+        );
+
+        Self { storage, form }
+    }
+
+    /// Reads schedules from the storage.
+    pub async fn refresh(&mut self) -> anyhow::Result<()> {
+        let schedules = self.storage.schedules().await?;
+        self.form.set_list(schedules);
+
+        Ok(())
+    }
+
+    /// Starts typing a new schedule for the saved station.
+    pub fn start_adding(&mut self, station: Station) {
+        self.form.start_adding(station);
+    }
+
+    /// Reports if a new schedule is being typed, all keys go to the input then.
+    pub fn is_editing(&self) -> bool {
+        self.form.is_editing()
+    }
+
+    /// Handles key while typing, returns `true` if a schedule was stored.
+    pub async fn handle_input(&mut self, key: KeyCode) -> anyhow::Result<bool> {
+        let Some(input) = self.form.handle_input(key) else {
+            return Ok(false);
+        };
+
+        let (weekdays, window) = parse_input(&input.text)?;
+        let (start, end) = Schedule::parse_window(window)?;
+
+        let schedule = Schedule {
+            id: 0,
+            station: input.station,
+            weekdays,
+            start,
+            end,
+        };
+
+        self.storage.create_schedule(&schedule).await?;
+        self.form.finish_adding();
+        self.refresh().await?;
+
+        Ok(true)
+    }
+
+    pub async fn handle_delete(&mut self) -> anyhow::Result<()> {
+        let schedule = self.form.get_selected().context("not selected")?;
+
+        self.storage.delete_schedule(schedule.id).await?;
+        self.refresh().await
+    }
+
+    pub fn handle_up(&mut self) {
+        self.form.handle_up();
+    }
+
+    pub fn handle_down(&mut self) {
+        self.form.handle_down();
+    }
+}
+
+impl<S: Storage> Component for Schedules<'_, S> {
+    fn draw<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        self.form.draw(frame, area);
+    }
+}
+
+/// Splits input like `sat, sun 20:00-22:00` into days and time window.
+fn parse_input(text: &str) -> anyhow::Result<(Weekdays, &str)> {
+    let (days, window) = text
+        .trim()
+        .rsplit_once(' ')
+        .context("days and time must be separated by space")?;
+
+    Ok((days.replace(' ', "").parse()?, window))
+}
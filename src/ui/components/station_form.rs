@@ -0,0 +1,151 @@
+use crossterm::event::KeyCode;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+use tui::style::{Color, Modifier, Style};
+use tui::text::Spans;
+use tui::widgets::{Block, BorderType, Borders, Paragraph, Row};
+use tui::Frame;
+
+use crate::models::Station;
+
+use super::{Component, Styles, Table};
+
+/// Table of items bound to saved stations, e.g. schedules or alarms,
+/// with a line to type a new item for a station.
+pub struct StationForm<'a, T> {
+    table: Table<'a, T>,
+    input: Option<Input>,
+    /// Shown before the station name in the input title, e.g. "Record".
+    action: &'static str,
+    /// Example of the input shown under it.
+    hint: &'static str,
+}
+
+/// New item being typed by the user.
+#[derive(Debug, Clone)]
+pub struct Input {
+    pub station: Station,
+    pub text: String,
+}
+
+impl<'a, T> StationForm<'a, T> {
+    pub fn new<RB>(
+        title: &'a str,
+        action: &'static str,
+        hint: &'static str,
+        widths: &'a [Constraint],
+        row_builder: RB,
+    ) -> Self
+    where
+        RB: Fn(&T) -> Row + 'static,
+    {
+        let table = Table::new(
+            vec![],
+            row_builder,
+            Styles {
+                block: Some(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_type(BorderType::Rounded)
+                        .title(title),
+                ),
+                highlight_style: Some(
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                widths: Some(widths),
+            },
+        )
+        .with_state();
+
+        Self {
+            table,
+            input: None,
+            action,
+            hint,
+        }
+    }
+
+    pub fn set_list(&mut self, list: Vec<T>) {
+        self.table.set_list(list);
+    }
+
+    pub fn get_selected(&self) -> Option<&T> {
+        self.table.get_selected()
+    }
+
+    /// Starts typing a new item for the saved station.
+    pub fn start_adding(&mut self, station: Station) {
+        self.input = Some(Input {
+            station,
+            text: String::new(),
+        });
+    }
+
+    /// Closes the input, e.g. once the typed item is stored.
+    pub fn finish_adding(&mut self) {
+        self.input = None;
+    }
+
+    /// Reports if a new item is being typed, all keys go to the input then.
+    pub fn is_editing(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// Handles key while typing, returns the input once it's submitted by Enter.
+    /// Input is kept open until [`StationForm::finish_adding`], so it can be corrected.
+    pub fn handle_input(&mut self, key: KeyCode) -> Option<Input> {
+        let input = self.input.as_mut()?;
+
+        match key {
+            KeyCode::Char(c) => input.text.push(c),
+            KeyCode::Backspace => {
+                input.text.pop();
+            }
+            KeyCode::Esc => self.input = None,
+            KeyCode::Enter => return Some(input.clone()),
+            _ => {}
+        }
+
+        None
+    }
+
+    pub fn handle_up(&mut self) {
+        self.table.handle_up();
+    }
+
+    pub fn handle_down(&mut self) {
+        self.table.handle_down();
+    }
+}
+
+impl<T> Component for StationForm<'_, T> {
+    fn draw<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
+        let Some(ref input) = self.input else {
+            self.table.draw(frame, area);
+            return;
+        };
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Min(1), Constraint::Length(4)])
+            .split(area);
+
+        self.table.draw(frame, layout[0]);
+
+        let text = vec![
+            Spans::from(format!("{}▏", input.text)),
+            Spans::from(format!("{} (Enter to save, Esc to cancel)", self.hint)),
+        ];
+
+        let paragraph = Paragraph::new(text).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .title(format!("{} {}", self.action, input.station.name.trim())),
+        );
+
+        frame.render_widget(paragraph, layout[1]);
+    }
+}
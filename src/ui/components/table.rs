@@ -40,12 +40,18 @@ impl<'a, T> Table<'a, T> {
     pub fn set_list(&mut self, list: Vec<T>) {
         self.list = list;
 
-        if self.state.is_some() && !self.list.is_empty() {
-            self.state.as_mut().unwrap().select(Some(0));
+        if let Some(ref mut state) = self.state {
+            if !self.list.is_empty() {
+                state.select(Some(0));
+            }
         }
     }
 
     pub fn handle_up(&mut self) {
+        if self.list.is_empty() {
+            return;
+        }
+
         if let Some(ref mut state) = self.state {
             let idx = state.selected().unwrap_or(0);
 
@@ -58,6 +64,10 @@ impl<'a, T> Table<'a, T> {
     }
 
     pub fn handle_down(&mut self) {
+        if self.list.is_empty() {
+            return;
+        }
+
         if let Some(ref mut state) = self.state {
             let idx = state.selected().unwrap_or(0);
 
@@ -72,19 +82,29 @@ impl<'a, T> Table<'a, T> {
     pub fn get_selected(&self) -> Option<&T> {
         self.state
             .as_ref()
-            .map(|state| &self.list[state.selected().unwrap_or(0)])
+            .and_then(|state| self.list.get(state.selected().unwrap_or(0)))
+    }
+
+    pub fn get_selected_mut(&mut self) -> Option<&mut T> {
+        self.state
+            .as_ref()
+            .and_then(|state| self.list.get_mut(state.selected().unwrap_or(0)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.list.iter_mut()
     }
 
     pub fn get_state(&self) -> Option<TableState> {
         self.state.clone()
     }
 
-    pub(super) fn build_rows(&self) -> Vec<Row> {
+    pub(super) fn build_rows(&self) -> Vec<Row<'_>> {
         self.list.iter().map(|s| (self.row_builder)(s)).collect()
     }
 }
 
-impl<'a, T> Component for Table<'a, T> {
+impl<T> Component for Table<'_, T> {
     fn draw<B: Backend>(&self, frame: &mut Frame<B>, area: Rect) {
         let rows: Vec<Row> = self.build_rows();
         let mut table = tui::widgets::Table::new(rows);
@@ -1,4 +1,5 @@
 use std::io;
+use std::time::Duration;
 
 use anyhow::Context;
 use crossterm::event::{Event, EventStream, KeyCode, KeyEvent};
@@ -7,6 +8,8 @@ use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
 use futures::{FutureExt, StreamExt};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::MissedTickBehavior;
 use tui::backend::{Backend, CrosstermBackend};
 use tui::layout::{Constraint, Direction, Layout};
 use tui::style::{Color, Modifier, Style};
@@ -14,10 +17,19 @@ use tui::text::Span;
 use tui::widgets::{Block, BorderType, Borders, Cell, Row};
 use tui::{Frame, Terminal};
 
-use components::{Component, Playbar, Styles, Table};
+use components::{Alarms, Component, Equalizer, Meter, Playbar, Schedules, Styles, Table};
 
+use crate::alarm::AlarmClock;
 use crate::api::Client;
-use crate::player::{Device, Player};
+use crate::checker::StationChecker;
+use crate::gains::StationGains;
+use crate::http::HttpConfig;
+use crate::models::Station;
+use crate::player::{
+    Device, DeviceChange, Event as PlayerEvent, OutputConfig, Player, SleepTimer, State,
+};
+use crate::scheduler::Scheduler;
+use crate::settings;
 use crate::storage::Storage;
 use crate::ui::components::Library;
 
@@ -27,6 +39,9 @@ mod components;
 pub enum ActiveLayout {
     Library,
     Devices,
+    Schedules,
+    Alarms,
+    Equalizer,
 }
 
 pub struct Ui<'a, P, S, C>
@@ -36,22 +51,53 @@ where
     C: Client,
 {
     player: P,
+    storage: S,
+    /// Volume change per key press.
+    volume_step: i8,
 
     active_layout: ActiveLayout,
 
     library: Library<'a, S, C>,
     devices: Table<'a, Device>,
+    schedules: Schedules<'a, S>,
+    alarms: Alarms<'a, S>,
+    equalizer: Equalizer<S>,
     playbar: Playbar,
+    meter: Meter,
+
+    scheduler: Scheduler<S>,
+    alarm_clock: AlarmClock<S>,
+    sleep_timer: SleepTimer,
+    gains: StationGains<S>,
+    checker: StationChecker<S>,
 }
 
-impl<'a, P, S, C> Ui<'a, P, S, C>
+impl<P, S, C> Ui<'_, P, S, C>
 where
     P: Player,
     S: Storage + Clone,
     C: Client,
 {
-    pub fn new(player: P, storage: S, client: C) -> Self {
-        let library = Library::new(storage, client);
+    const TICK_PERIOD: Duration = Duration::from_secs(1);
+    /// Meter is redrawn at 20 fps.
+    const FRAME_PERIOD: Duration = Duration::from_millis(50);
+    const SHORT_SEEK: Duration = Duration::from_secs(10);
+    const LONG_SEEK: Duration = Duration::from_mins(1);
+
+    /// Builds UI recording by the `scheduler` and checking stations with the `http` settings.
+    pub fn new(
+        player: P,
+        storage: S,
+        client: C,
+        scheduler: Scheduler<S>,
+        http: HttpConfig,
+    ) -> Self {
+        let alarm_clock = AlarmClock::new(storage.clone());
+        let alarms = Alarms::new(storage.clone());
+        let gains = StationGains::new(storage.clone());
+        let checker = StationChecker::new(storage.clone(), http);
+        let equalizer = Equalizer::new(storage.clone(), player.equalizer());
+        let library = Library::new(storage.clone(), client);
 
         let devices = Table::<Device>::new(
             vec![],
@@ -66,6 +112,16 @@ where
                     text.push_str(" (default)");
                 }
 
+                if d.is_virtual() {
+                    text.push_str(" (virtual)");
+                } else if let Some(config) = d.config() {
+                    text = format!("{text} {} Hz, {} ch", config.sample_rate, config.channels);
+
+                    if let Some(buffer_size) = config.buffer_size {
+                        text = format!("{text}, buffer {buffer_size}");
+                    }
+                }
+
                 Row::new(vec![Cell::from(Span::raw(text))])
             },
             Styles {
@@ -89,13 +145,31 @@ where
 
         Self {
             player,
+            storage: storage.clone(),
+            volume_step: 5,
             active_layout: ActiveLayout::Library,
             library,
             devices,
+            schedules: Schedules::new(storage),
+            alarms,
+            equalizer,
             playbar,
+            meter: Meter::default(),
+            scheduler,
+            alarm_clock,
+            sleep_timer: SleepTimer::default(),
+            gains,
+            checker,
         }
     }
 
+    /// Sets volume change per key press in percentage.
+    pub fn with_volume_step(mut self, step: u8) -> Self {
+        self.volume_step = i8::try_from(step.min(100)).unwrap_or(i8::MAX);
+
+        self
+    }
+
     pub async fn start(&mut self) -> anyhow::Result<()> {
         setup_terminal()?;
 
@@ -106,6 +180,11 @@ where
         self.update_devices()?;
 
         let mut reader = EventStream::new();
+        let mut player_events = self.player.subscribe();
+        let mut is_subscribed = true;
+        let mut ticker = tokio::time::interval(Self::TICK_PERIOD);
+        let mut frames = tokio::time::interval(Self::FRAME_PERIOD);
+        frames.set_missed_tick_behavior(MissedTickBehavior::Skip);
 
         loop {
             terminal.draw(|f| self.draw(f))?;
@@ -116,10 +195,16 @@ where
 
                     match self.handle_key(key_event).await {
                         Ok(false) => break,
-                        Ok(true) => continue,
+                        Ok(true) => {}
                         Err(e) => log::error!("handle key {:?}: {:?}", key_event.code, e),
                     }
                 },
+                event = player_events.recv(), if is_subscribed => {
+                    is_subscribed = self.handle_player_event(event);
+                },
+                station = self.checker.next() => self.handle_checked(station).await,
+                _ = ticker.tick() => self.handle_tick().await,
+                _ = frames.tick() => self.meter.set_levels(self.player.levels()),
             }
         }
 
@@ -140,27 +225,107 @@ where
         match self.active_layout {
             ActiveLayout::Library => self.library.draw(f, layout[0]),
             ActiveLayout::Devices => self.devices.draw(f, layout[0]),
+            ActiveLayout::Schedules => self.schedules.draw(f, layout[0]),
+            ActiveLayout::Alarms => self.alarms.draw(f, layout[0]),
+            ActiveLayout::Equalizer => self.equalizer.draw(f, layout[0]),
+        }
+
+        let bottom = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Min(1), Constraint::Length(Meter::WIDTH)])
+            .split(layout[1]);
+
+        self.playbar.draw(f, bottom[0]);
+        self.meter.draw(f, bottom[1]);
+    }
+
+    async fn handle_tick(&mut self) {
+        if let Err(e) = self.scheduler.tick().await {
+            log::error!("scheduler: {e:?}");
+        }
+
+        match self.alarm_clock.tick(&self.player).await {
+            Ok(Some(station)) => self.playbar.set_station(Some(&station)),
+            Ok(None) => {}
+            Err(e) => log::error!("alarm clock: {e:?}"),
+        }
+
+        if self.sleep_timer.tick(&self.player) {
+            self.playbar.set_station(None);
         }
 
-        self.playbar.draw(f, layout[1]);
+        if let Err(e) = self.gains.tick(&self.player, self.playbar.playing()).await {
+            log::error!("station gains: {e:?}");
+        }
+
+        self.playbar
+            .set_recording_until(self.scheduler.recording_until());
+        self.playbar
+            .set_sleep_remaining(self.sleep_timer.remaining());
+        self.playbar.set_player_settings(&self.player);
     }
 
     async fn handle_key(&mut self, event: KeyEvent) -> anyhow::Result<bool> {
+        if self.schedules.is_editing() {
+            if self.schedules.handle_input(event.code).await? {
+                self.scheduler.reload();
+            }
+
+            return Ok(true);
+        }
+
+        if self.alarms.is_editing() {
+            if self.alarms.handle_input(event.code).await? {
+                self.alarm_clock.reload();
+            }
+
+            return Ok(true);
+        }
+
+        if self.library.is_editing() {
+            self.library.handle_input(event.code).await?;
+
+            return Ok(true);
+        }
+
+        if self.equalizer.is_editing() {
+            self.equalizer.handle_input(event.code).await?;
+
+            return Ok(true);
+        }
+
         match event.code {
             KeyCode::Char('q' | 'й') => return Ok(false),
-            KeyCode::F(1) => self.handle_set_layout(ActiveLayout::Library)?,
-            KeyCode::F(2) => self.handle_set_layout(ActiveLayout::Devices)?,
-            KeyCode::F(5) => self.handle_refresh()?,
-            KeyCode::Char('+' | '=') => self.player.set_volume(self.player.volume() + 5),
-            KeyCode::Char('-') => self.player.set_volume(self.player.volume() - 5),
+            KeyCode::F(1) => self.handle_set_layout(ActiveLayout::Library).await?,
+            KeyCode::F(2) => self.handle_set_layout(ActiveLayout::Devices).await?,
+            KeyCode::F(3) => self.handle_set_layout(ActiveLayout::Schedules).await?,
+            KeyCode::F(4) => self.handle_set_layout(ActiveLayout::Alarms).await?,
+            KeyCode::F(5) => self.handle_refresh().await?,
+            KeyCode::F(6) => self.handle_set_layout(ActiveLayout::Equalizer).await?,
+            KeyCode::Char('+' | '=') => self.handle_volume(self.volume_step).await?,
+            KeyCode::Char('-') => self.handle_volume(-self.volume_step).await?,
+            KeyCode::Char('m' | 'ь') => self.handle_mute().await?,
             KeyCode::Up => self.handle_up(),
             KeyCode::Down => self.handle_down(),
-            KeyCode::Left => self.handle_left(),
+            KeyCode::Left => self.handle_left().await?,
             KeyCode::Right => self.handle_right().await?,
-            KeyCode::Enter => self.handle_enter()?,
+            KeyCode::Enter => self.handle_enter().await?,
             KeyCode::Char('p' | 'з') => self.handle_pause(),
-            KeyCode::Char('s' | 'ы') => self.library.handle_save().await?,
-            KeyCode::Delete => self.library.handle_delete().await?,
+            KeyCode::Char('r' | 'к') => self.handle_record()?,
+            KeyCode::Char('l' | 'д') => self.player.jump_to_live(),
+            KeyCode::Char('t' | 'е') => self.handle_sleep_timer(),
+            KeyCode::Char('n' | 'т') => self.player.set_normalizing(!self.player.is_normalizing()),
+            KeyCode::Char(',' | 'б') => self.player.rewind(Self::SHORT_SEEK),
+            KeyCode::Char('.' | 'ю') => self.player.fast_forward(Self::SHORT_SEEK),
+            KeyCode::Char('<' | 'Б') => self.player.rewind(Self::LONG_SEEK),
+            KeyCode::Char('>' | 'Ю') => self.player.fast_forward(Self::LONG_SEEK),
+            KeyCode::Char('s' | 'ы') => self.handle_save().await?,
+            KeyCode::Char('a' | 'ф') => self.handle_add_schedule(),
+            KeyCode::Char('w' | 'ц') => self.handle_add_alarm(),
+            KeyCode::Char('b' | 'и') => self.handle_buffer_size().await?,
+            KeyCode::Char('h' | 'р') => self.handle_edit_headers().await?,
+            KeyCode::Char('c' | 'с') => self.handle_check_stations().await?,
+            KeyCode::Delete => self.handle_delete().await?,
             _ => {}
         }
 
@@ -169,9 +334,13 @@ where
         Ok(true)
     }
 
-    fn handle_set_layout(&mut self, layout: ActiveLayout) -> anyhow::Result<()> {
-        if layout == ActiveLayout::Devices {
-            self.update_devices()?;
+    async fn handle_set_layout(&mut self, layout: ActiveLayout) -> anyhow::Result<()> {
+        match layout {
+            ActiveLayout::Devices => self.update_devices()?,
+            ActiveLayout::Schedules => self.schedules.refresh().await?,
+            ActiveLayout::Alarms => self.alarms.refresh().await?,
+            ActiveLayout::Equalizer => self.equalizer.refresh().await?,
+            ActiveLayout::Library => {}
         }
 
         self.active_layout = layout;
@@ -179,19 +348,131 @@ where
         Ok(())
     }
 
-    fn handle_refresh(&mut self) -> anyhow::Result<()> {
-        if self.active_layout == ActiveLayout::Devices {
-            self.update_devices()?;
+    async fn handle_refresh(&mut self) -> anyhow::Result<()> {
+        match self.active_layout {
+            ActiveLayout::Devices => self.update_devices()?,
+            ActiveLayout::Schedules => self.schedules.refresh().await?,
+            ActiveLayout::Alarms => self.alarms.refresh().await?,
+            ActiveLayout::Equalizer => self.equalizer.refresh().await?,
+            ActiveLayout::Library => {}
+        }
+
+        Ok(())
+    }
+
+    async fn handle_save(&mut self) -> anyhow::Result<()> {
+        match self.active_layout {
+            ActiveLayout::Library => self.library.handle_save().await?,
+            ActiveLayout::Equalizer => self.equalizer.start_saving(),
+            _ => {}
         }
 
         Ok(())
     }
 
-    fn handle_enter(&mut self) -> anyhow::Result<()> {
+    fn handle_add_schedule(&mut self) {
+        if self.active_layout != ActiveLayout::Library {
+            return;
+        }
+
+        if let Some(station) = self.library.get_selected_saved() {
+            self.schedules.start_adding(station.clone());
+            self.active_layout = ActiveLayout::Schedules;
+        }
+    }
+
+    /// Starts checking streams of all saved stations in background.
+    async fn handle_check_stations(&mut self) -> anyhow::Result<()> {
+        if self.active_layout != ActiveLayout::Library {
+            return Ok(());
+        }
+
+        let total = self.checker.start().await?;
+        self.playbar
+            .set_notice(format!("Checking {total} stations"));
+
+        Ok(())
+    }
+
+    /// Stores result of the station check, marks it in the library and reports progress.
+    async fn handle_checked(&mut self, station: Option<Station>) {
+        let progress = self.checker.progress();
+
+        let Some(station) = station else {
+            self.playbar.set_notice(format!(
+                "{} of {} stations are broken",
+                progress.broken, progress.total
+            ));
+            return;
+        };
+
+        if let Err(e) = self.checker.save(&station).await {
+            log::error!("check stations: {e:?}");
+        }
+
+        self.library.update_station(station);
+        self.playbar.set_notice(format!(
+            "Checked {}/{} stations, {} broken",
+            progress.checked, progress.total, progress.broken
+        ));
+    }
+
+    async fn handle_edit_headers(&mut self) -> anyhow::Result<()> {
+        if self.active_layout != ActiveLayout::Library {
+            return Ok(());
+        }
+
+        self.library.start_editing_headers().await
+    }
+
+    fn handle_add_alarm(&mut self) {
+        if self.active_layout != ActiveLayout::Library {
+            return;
+        }
+
+        if let Some(station) = self.library.get_selected_saved() {
+            self.alarms
+                .start_adding(station.clone(), self.player.volume());
+            self.active_layout = ActiveLayout::Alarms;
+        }
+    }
+
+    async fn handle_delete(&mut self) -> anyhow::Result<()> {
+        match self.active_layout {
+            ActiveLayout::Library => self.library.handle_delete().await?,
+            ActiveLayout::Schedules => {
+                self.schedules.handle_delete().await?;
+                self.scheduler.reload();
+            }
+            ActiveLayout::Alarms => {
+                self.alarms.handle_delete().await?;
+                self.alarm_clock.reload();
+            }
+            ActiveLayout::Equalizer => self.equalizer.handle_delete().await?,
+            ActiveLayout::Devices => {}
+        }
+
+        Ok(())
+    }
+
+    async fn handle_enter(&mut self) -> anyhow::Result<()> {
         match self.active_layout {
             ActiveLayout::Library => {
-                if let Some(selected) = self.library.get_selected() {
-                    self.player.play(&selected.url)?;
+                if self.player.state() == State::Connecting {
+                    self.player.stop();
+                    self.playbar.set_station(None);
+                } else if let Some(selected) = self.library.get_selected() {
+                    // only stations saved to the library have their own headers and gain.
+                    let (headers, gain) = match self.library.get_selected_saved() {
+                        Some(saved) => (
+                            self.storage.station_headers(saved.id).await?,
+                            self.storage.station_gain(saved.id).await?,
+                        ),
+                        None => (vec![], None),
+                    };
+
+                    self.player
+                        .play(&selected.url, &headers, gain.unwrap_or_default())?;
                     self.playbar.set_station(Some(selected));
                 }
             }
@@ -200,11 +481,38 @@ where
                     self.player.use_device(selected)?;
                 }
             }
-        };
+            ActiveLayout::Equalizer => {
+                self.equalizer.handle_enter();
+                self.player.set_equalizer(self.equalizer.gains());
+            }
+            ActiveLayout::Schedules | ActiveLayout::Alarms => {}
+        }
 
         Ok(())
     }
 
+    /// Changes volume by the delta, unmuting the output.
+    async fn handle_volume(&mut self, delta: i8) -> anyhow::Result<()> {
+        self.player
+            .set_volume(self.player.volume().saturating_add(delta));
+        self.player.set_muted(false);
+
+        self.save_volume().await
+    }
+
+    async fn handle_mute(&mut self) -> anyhow::Result<()> {
+        self.player.set_muted(!self.player.is_muted());
+
+        self.save_volume().await
+    }
+
+    /// Stores volume chosen by the user, the one lowered by the sleep timer isn't.
+    async fn save_volume(&self) -> anyhow::Result<()> {
+        let volume = self.sleep_timer.user_volume(&self.player);
+
+        settings::save_volume(&self.storage, volume, self.player.is_muted()).await
+    }
+
     fn handle_pause(&mut self) {
         if self.player.is_paused() {
             self.player.resume();
@@ -213,34 +521,194 @@ where
         }
     }
 
+    fn handle_record(&mut self) -> anyhow::Result<()> {
+        if self.player.recording().is_some() {
+            self.player.stop_recording();
+        } else if let Some(station) = self.playbar.station() {
+            let path = self.player.start_recording(station)?;
+            log::info!("recording {station} to {}", path.display());
+        }
+
+        Ok(())
+    }
+
+    fn handle_sleep_timer(&mut self) {
+        self.sleep_timer.cycle(&self.player);
+        self.playbar
+            .set_sleep_remaining(self.sleep_timer.remaining());
+    }
+
     fn handle_up(&mut self) {
         match self.active_layout {
             ActiveLayout::Library => self.library.handle_up(),
             ActiveLayout::Devices => self.devices.handle_up(),
-        };
+            ActiveLayout::Schedules => self.schedules.handle_up(),
+            ActiveLayout::Alarms => self.alarms.handle_up(),
+            ActiveLayout::Equalizer => self.equalizer.handle_up(),
+        }
     }
 
     fn handle_down(&mut self) {
         match self.active_layout {
             ActiveLayout::Library => self.library.handle_down(),
             ActiveLayout::Devices => self.devices.handle_down(),
-        };
+            ActiveLayout::Schedules => self.schedules.handle_down(),
+            ActiveLayout::Alarms => self.alarms.handle_down(),
+            ActiveLayout::Equalizer => self.equalizer.handle_down(),
+        }
     }
 
-    fn handle_left(&mut self) {
-        if self.active_layout == ActiveLayout::Library {
-            self.library.handle_left();
-        };
+    async fn handle_left(&mut self) -> anyhow::Result<()> {
+        match self.active_layout {
+            ActiveLayout::Library => self.library.handle_left(),
+            ActiveLayout::Devices => self.handle_output_format(true).await?,
+            ActiveLayout::Equalizer => {
+                self.equalizer.handle_left();
+                self.player.set_equalizer(self.equalizer.gains());
+            }
+            _ => {}
+        }
+
+        Ok(())
     }
 
     async fn handle_right(&mut self) -> anyhow::Result<()> {
-        if self.active_layout == ActiveLayout::Library {
-            self.library.handle_right().await?;
+        match self.active_layout {
+            ActiveLayout::Library => self.library.handle_right().await?,
+            ActiveLayout::Devices => self.handle_output_format(false).await?,
+            ActiveLayout::Equalizer => {
+                self.equalizer.handle_right();
+                self.player.set_equalizer(self.equalizer.gains());
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Switches the selected device to the next supported sample rate and channels.
+    async fn handle_output_format(&mut self, backward: bool) -> anyhow::Result<()> {
+        let Some(device) = self
+            .devices
+            .get_selected()
+            .filter(|d| !d.is_virtual())
+            .cloned()
+        else {
+            return Ok(());
+        };
+
+        let config = OutputConfig::cycle(device.config(), device.configs(), backward);
+
+        self.set_output_config(&device, config).await
+    }
+
+    /// Switches the selected device to the next supported buffer size.
+    async fn handle_buffer_size(&mut self) -> anyhow::Result<()> {
+        if self.active_layout != ActiveLayout::Devices {
+            return Ok(());
+        }
+
+        let Some(device) = self
+            .devices
+            .get_selected()
+            .filter(|d| !d.is_virtual())
+            .cloned()
+        else {
+            return Ok(());
         };
 
+        // buffer size is set along with the format, the first supported one is taken by default.
+        let Some(config) = device
+            .config()
+            .or_else(|| OutputConfig::options(device.configs()).first().copied())
+        else {
+            return Ok(());
+        };
+
+        let config = config.next_buffer_size(device.configs());
+
+        self.set_output_config(&device, Some(config)).await
+    }
+
+    async fn set_output_config(
+        &mut self,
+        device: &Device,
+        config: Option<OutputConfig>,
+    ) -> anyhow::Result<()> {
+        self.player.set_output_config(device.id(), config)?;
+        settings::save_output_config(&self.storage, device, config).await?;
+
+        // list is updated in place, so the selection is kept.
+        let updated = self.player.devices()?.into_iter().find(|d| d == device);
+
+        if let (Some(selected), Some(updated)) = (self.devices.get_selected_mut(), updated) {
+            *selected = updated;
+        }
+
         Ok(())
     }
 
+    /// Updates the playbar as soon as the player reports a change, without waiting for a tick.
+    /// Returns `false` once the player is gone, no more events come then.
+    fn handle_player_event(&mut self, event: Result<PlayerEvent, RecvError>) -> bool {
+        let event = match event {
+            Ok(event) => event,
+            // the playbar is refreshed from the player on the next event or tick anyway.
+            Err(RecvError::Lagged(skipped)) => {
+                log::warn!("skipped {skipped} player events");
+                return true;
+            }
+            Err(RecvError::Closed) => return false,
+        };
+
+        match event {
+            PlayerEvent::StateChanged(_)
+            | PlayerEvent::Metadata(_)
+            | PlayerEvent::Buffer(_)
+            | PlayerEvent::Paused(_)
+            | PlayerEvent::Volume { .. } => {
+                self.playbar.set_player_settings(&self.player);
+            }
+            PlayerEvent::Error(e) => {
+                log::error!("player: {e}");
+                self.playbar.set_player_settings(&self.player);
+            }
+            PlayerEvent::DeviceChanged(change) => self.handle_device_change(change),
+        }
+
+        true
+    }
+
+    /// Reports output moved by the player after device was unplugged or plugged back.
+    fn handle_device_change(&mut self, change: DeviceChange) {
+        let notice = match change {
+            // made by the user, only the list is updated.
+            DeviceChange::Chosen(_) | DeviceChange::Reconfigured(_) => {
+                if let Err(e) = self.update_devices() {
+                    log::error!("update devices: {e:?}");
+                }
+
+                return;
+            }
+            DeviceChange::Lost {
+                lost,
+                fallback: Some(fallback),
+            } => format!("{} lost, moved to {}", lost.id(), fallback.id()),
+            DeviceChange::Lost {
+                lost,
+                fallback: None,
+            } => format!("{} lost, no output", lost.id()),
+            DeviceChange::Returned(device) => format!("Returned to {}", device.id()),
+        };
+
+        log::info!("{notice}");
+        self.playbar.set_notice(notice);
+
+        if let Err(e) = self.update_devices() {
+            log::error!("update devices: {e:?}");
+        }
+    }
+
     fn update_devices(&mut self) -> anyhow::Result<()> {
         let devices = self.player.devices()?;
         self.devices.set_list(devices);